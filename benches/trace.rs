@@ -1,6 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use opentelemetry::{
-    api::{Key, Provider, Span, Tracer},
+    api::{Key, KeyValue, Provider, Span, Tracer},
     sdk,
 };
 
@@ -29,6 +29,15 @@ fn criterion_benchmark(c: &mut Criterion) {
         span.end();
     });
 
+    trace_benchmark_group(c, "start-end-span-4-static-attrs", |tracer| {
+        let span = tracer.start("foo");
+        span.set_attribute(KeyValue::new("http.method", "GET"));
+        span.set_attribute(KeyValue::new("http.scheme", "https"));
+        span.set_attribute(KeyValue::new("db.system", "postgresql"));
+        span.set_attribute(KeyValue::new("net.transport", "IP.TCP"));
+        span.end();
+    });
+
     trace_benchmark_group(c, "start-end-span-all-attr-types", |tracer| {
         let span = tracer.start("foo");
         span.set_attribute(Key::new("key1").bool(false));
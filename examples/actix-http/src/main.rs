@@ -33,7 +33,8 @@ async fn main() -> std::io::Result<()> {
             .wrap(Logger::default())
             .wrap_fn(move |req, srv| {
                 tracer.in_span("middleware", move |cx| {
-                    cx.span().set_attribute(Key::new("path").string(req.path()));
+                    cx.span()
+                        .set_attribute(Key::new("path").string(req.path().to_string()));
                     srv.call(req).with_context(cx)
                 })
             })
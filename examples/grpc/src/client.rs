@@ -1,10 +1,11 @@
 use hello_world::greeter_client::GreeterClient;
 use hello_world::HelloRequest;
-use opentelemetry::api::{
-    Context, KeyValue, TextMapFormat, TraceContextExt, TraceContextPropagator, Tracer,
-};
+use opentelemetry::api::{Context, FutureExt, KeyValue, TraceContextExt, Tracer};
 use opentelemetry::sdk;
+use opentelemetry_http::GrpcClientLayer;
 use std::error::Error;
+use tonic::transport::Channel;
+use tower_layer::Layer;
 
 pub mod hello_world {
     tonic::include_proto!("helloworld");
@@ -19,17 +20,17 @@ fn tracing_init() -> Result<sdk::Tracer, Box<dyn Error>> {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let tracer = tracing_init()?;
-    let mut client = GreeterClient::connect("http://[::1]:50051").await?;
-    let propagator = TraceContextPropagator::new();
+    let channel = Channel::from_static("http://[::1]:50051").connect().await?;
+    // `GrpcClientLayer` starts a client span for each call and injects it into the metadata
+    let mut client = GreeterClient::new(GrpcClientLayer::new(tracer.clone()).layer(channel));
     let span = tracer.start("client-request");
     let cx = Context::current_with_span(span);
 
-    let mut request = tonic::Request::new(HelloRequest {
+    let request = tonic::Request::new(HelloRequest {
         name: "Tonic".into(),
     });
-    propagator.inject_context(&cx, request.metadata_mut());
 
-    let response = client.say_hello(request).await?;
+    let response = client.say_hello(request).with_context(cx.clone()).await?;
 
     cx.span().add_event(
         "response-received".to_string(),
@@ -2,10 +2,11 @@ use tonic::{transport::Server, Request, Response, Status};
 
 use hello_world::greeter_server::{Greeter, GreeterServer};
 use hello_world::{HelloReply, HelloRequest};
-use opentelemetry::api::{self, KeyValue, Span, TextMapFormat, Tracer};
-use opentelemetry::global;
+use opentelemetry::api::{Context, KeyValue, TraceContextExt};
 use opentelemetry::sdk;
+use opentelemetry_http::GrpcServerLayer;
 use std::error::Error;
+use tower_layer::Layer;
 
 pub mod hello_world {
     tonic::include_proto!("helloworld"); // The string specified here must match the proto package name.
@@ -20,10 +21,10 @@ impl Greeter for MyGreeter {
         &self,
         request: Request<HelloRequest>, // Accept request of type HelloRequest
     ) -> Result<Response<HelloReply>, Status> {
-        let propagator = api::TraceContextPropagator::new();
-        let parent_cx = propagator.extract(request.metadata());
-        let span = global::tracer("greeter").start_from_context("Processing reply", &parent_cx);
-        span.set_attribute(KeyValue::new("request", format!("{:?}", request)));
+        // The server span started by `GrpcServerLayer` is active while handling the call
+        Context::current()
+            .span()
+            .set_attribute(KeyValue::new("request", format!("{:?}", request)));
 
         // Return an instance of type HelloReply
         let reply = hello_world::HelloReply {
@@ -42,12 +43,12 @@ fn tracing_init() -> Result<sdk::Tracer, Box<dyn Error>> {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let _ = tracing_init()?;
+    let tracer = tracing_init()?;
     let addr = "[::1]:50051".parse()?;
     let greeter = MyGreeter::default();
 
     Server::builder()
-        .add_service(GreeterServer::new(greeter))
+        .add_service(GrpcServerLayer::new(tracer).layer(GreeterServer::new(greeter)))
         .serve(addr)
         .await?;
 
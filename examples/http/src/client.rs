@@ -1,6 +1,8 @@
 use hyper::{body::Body, Client};
-use opentelemetry::api::{Context, TextMapFormat, TraceContextExt, Tracer};
+use opentelemetry::api::{Context, TraceContextExt, Tracer};
 use opentelemetry::{api, exporter::trace::stdout, global, sdk};
+use opentelemetry_http::ClientLayer;
+use tower::{Service, ServiceBuilder};
 
 fn init_tracer() {
     // Create stdout exporter to be able to retrieve the collected spans.
@@ -23,14 +25,21 @@ fn init_tracer() {
 async fn main() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
     init_tracer();
 
-    let client = Client::new();
-    let propagator = api::TraceContextPropagator::new();
+    // `ClientLayer` starts a client span for each request and injects it into the headers
+    let mut client = ServiceBuilder::new()
+        .layer(ClientLayer::new(global::tracer("example/client")))
+        .service(Client::new());
     let span = global::tracer("example/client").start("say hello");
     let cx = Context::current_with_span(span);
 
-    let mut req = hyper::Request::builder().uri("http://127.0.0.1:3000");
-    propagator.inject_context(&cx, req.headers_mut().unwrap());
-    let res = client.request(req.body(Body::from("Hallo!"))?).await?;
+    let req = hyper::Request::builder()
+        .uri("http://127.0.0.1:3000")
+        .body(Body::from("Hallo!"))?;
+    let res = {
+        let _guard = cx.clone().attach();
+        client.call(req)
+    }
+    .await?;
 
     cx.span().add_event(
         "Got response!".to_string(),
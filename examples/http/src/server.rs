@@ -1,17 +1,19 @@
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Response, Server};
 use opentelemetry::{
-    api::{self, Span, TextMapFormat, Tracer},
+    api::{Context, TraceContextExt},
     exporter::trace::stdout,
     global, sdk,
 };
+use opentelemetry_http::ServerLayer;
 use std::{convert::Infallible, net::SocketAddr};
+use tower::ServiceBuilder;
 
-async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
-    let propagator = api::TraceContextPropagator::new();
-    let parent_cx = propagator.extract(req.headers());
-    let span = global::tracer("example/server").start_from_context("hello", &parent_cx);
-    span.add_event("handling this...".to_string(), Vec::new());
+async fn handle(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    // The server span started by `ServerLayer` is active while handling the request
+    Context::current()
+        .span()
+        .add_event("handling this...".to_string(), Vec::new());
 
     Ok(Response::new("Hello, World!".into()))
 }
@@ -38,7 +40,13 @@ async fn main() {
     init_tracer();
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
 
-    let make_svc = make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(handle)) });
+    let make_svc = make_service_fn(|_conn| async {
+        Ok::<_, Infallible>(
+            ServiceBuilder::new()
+                .layer(ServerLayer::new(global::tracer("example/server")))
+                .service(service_fn(handle)),
+        )
+    });
 
     let server = Server::bind(&addr).serve(make_svc);
 
@@ -4,10 +4,23 @@
 //! some users.
 //!
 //! Typically, those include vendor specific propagators.
+//!
+//! With the `log` feature, [`LogBridge`] records the records of the `log`
+//! facade as events on the current span.
+//!
+//! [`LogBridge`]: struct.LogBridge.html
 
+#[cfg(feature = "log")]
+mod log_bridge;
 mod trace_propagator;
 
+#[cfg(feature = "log")]
+pub use log_bridge::LogBridge;
+
 pub use trace_propagator::{
     b3_propagator::{B3Encoding, B3Propagator},
+    gcp_propagator::GoogleCloudTracePropagator,
     jaeger_propagator::JaegerPropagator,
+    ot_propagator::OpenTracingPropagator,
+    register_propagators,
 };
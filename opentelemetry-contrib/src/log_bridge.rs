@@ -0,0 +1,239 @@
+//! # Log Bridge
+//!
+//! Forwards records of the [`log`] facade to an existing logger, and records
+//! them as events on the span that is active in `Context::current()`, so that
+//! log lines show up inline in traces without changes to the logging code.
+//!
+//! Each event is named after the formatted message and has the following
+//! attributes:
+//!
+//! - `log.level`: the record level, e.g. `INFO`
+//! - `log.target`: the record target
+//! - `code.namespace`: the module path, if known
+//! - `code.filepath`: the source file, if known
+//! - `code.lineno`: the source line, if known
+//!
+//! [`log`]: https://crates.io/crates/log
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use opentelemetry::api::{Context, KeyValue, TraceContextExt};
+
+/// A [`Log`] implementation that wraps an existing logger and records every
+/// record up to a maximum level as an event on the current span.
+///
+/// # Examples
+///
+/// ```
+/// use log::LevelFilter;
+/// use opentelemetry_contrib::LogBridge;
+///
+/// # #[derive(Debug)]
+/// # struct StderrLogger;
+/// # impl log::Log for StderrLogger {
+/// #     fn enabled(&self, _metadata: &log::Metadata) -> bool { true }
+/// #     fn log(&self, record: &log::Record) { eprintln!("{}", record.args()) }
+/// #     fn flush(&self) {}
+/// # }
+/// // Use the logger that would otherwise be installed with `log::set_logger`
+/// LogBridge::new(StderrLogger)
+///     .with_max_level(LevelFilter::Debug)
+///     .install()
+///     .expect("a logger is already installed");
+///
+/// log::info!("shows up in the logs and on the current span");
+/// ```
+///
+/// [`Log`]: https://docs.rs/log/0.4/log/trait.Log.html
+#[derive(Debug)]
+pub struct LogBridge<L> {
+    inner: L,
+    max_level: LevelFilter,
+}
+
+impl<L: Log> LogBridge<L> {
+    /// Create a bridge that forwards records to `inner` and records events
+    /// for records up to `Info`.
+    pub fn new(inner: L) -> Self {
+        LogBridge {
+            inner,
+            max_level: LevelFilter::Info,
+        }
+    }
+
+    /// Set the maximum level of the records that are recorded as span events.
+    ///
+    /// Records are forwarded to the inner logger regardless of this level.
+    pub fn with_max_level(mut self, max_level: LevelFilter) -> Self {
+        self.max_level = max_level;
+        self
+    }
+
+    /// Install the bridge as the global logger.
+    ///
+    /// Raises the global `log::max_level` to the maximum event level if it is
+    /// lower, so that the records reach the bridge.
+    pub fn install(self) -> Result<(), SetLoggerError>
+    where
+        L: 'static,
+    {
+        let max_level = self.max_level.max(log::max_level());
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(max_level);
+        Ok(())
+    }
+}
+
+impl<L: Log> Log for LogBridge<L> {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.max_level || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.inner.enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+        if record.level() > self.max_level {
+            return;
+        }
+
+        let cx = Context::current();
+        let span = cx.span();
+        if !span.is_recording() {
+            return;
+        }
+
+        let mut attributes = vec![
+            KeyValue::new("log.level", record.level().to_string()),
+            KeyValue::new("log.target", record.target().to_string()),
+        ];
+        if let Some(module) = record.module_path() {
+            attributes.push(KeyValue::new("code.namespace", module.to_string()));
+        }
+        if let Some(file) = record.file() {
+            attributes.push(KeyValue::new("code.filepath", file.to_string()));
+        }
+        if let Some(line) = record.line() {
+            attributes.push(KeyValue::new("code.lineno", i64::from(line)));
+        }
+        span.add_event(record.args().to_string(), attributes);
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+    use opentelemetry::api::{self, Event, Span};
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    #[derive(Debug)]
+    struct TestSpan(Arc<Mutex<Vec<Event>>>);
+
+    impl Span for TestSpan {
+        fn add_event_with_timestamp(
+            &self,
+            name: String,
+            timestamp: SystemTime,
+            attributes: Vec<api::KeyValue>,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(Event::new(name, timestamp, attributes));
+        }
+        fn span_context(&self) -> api::SpanContext {
+            api::SpanContext::empty_context()
+        }
+        fn is_recording(&self) -> bool {
+            true
+        }
+        fn set_attribute(&self, _attribute: api::KeyValue) {}
+        fn set_status(&self, _code: api::StatusCode, _message: String) {}
+        fn update_name(&self, _new_name: String) {}
+        fn end_with_timestamp(&self, _timestamp: SystemTime) {}
+    }
+
+    /// Collects the messages of the records it is enabled for.
+    #[derive(Debug, Default)]
+    struct TestLogger(Mutex<Vec<String>>);
+
+    impl Log for TestLogger {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.level() <= Level::Warn
+        }
+        fn log(&self, record: &Record<'_>) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+        fn flush(&self) {}
+    }
+
+    fn record<'a>(level: Level, args: std::fmt::Arguments<'a>) -> Record<'a> {
+        Record::builder()
+            .level(level)
+            .target("app::db")
+            .module_path(Some("app::db"))
+            .file(Some("src/db.rs"))
+            .line(Some(42))
+            .args(args)
+            .build()
+    }
+
+    #[test]
+    fn records_events_on_current_span() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let bridge = LogBridge::new(TestLogger::default());
+
+        {
+            let _guard = Context::current_with_span(TestSpan(events.clone())).attach();
+            bridge.log(&record(Level::Warn, format_args!("slow query: {}ms", 120)));
+            bridge.log(&record(Level::Info, format_args!("connected")));
+            bridge.log(&record(Level::Debug, format_args!("checkout")));
+        }
+        // no active span
+        bridge.log(&record(Level::Error, format_args!("disconnected")));
+
+        assert_eq!(
+            *bridge.inner.0.lock().unwrap(),
+            vec!["slow query: 120ms", "disconnected"]
+        );
+
+        let events = events.lock().unwrap();
+        let names: Vec<_> = events.iter().map(|event| event.name.as_str()).collect();
+        assert_eq!(names, vec!["slow query: 120ms", "connected"]);
+
+        let attributes: Vec<_> = events[0]
+            .attributes
+            .iter()
+            .map(|kv| (kv.key.as_str().to_string(), String::from(&kv.value)))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("log.level".to_string(), "WARN".to_string()),
+                ("log.target".to_string(), "app::db".to_string()),
+                ("code.namespace".to_string(), "app::db".to_string()),
+                ("code.filepath".to_string(), "src/db.rs".to_string()),
+                ("code.lineno".to_string(), "42".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn max_level() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let bridge = LogBridge::new(TestLogger::default()).with_max_level(LevelFilter::Debug);
+        assert!(bridge.enabled(&Metadata::builder().level(Level::Debug).build()));
+        assert!(!bridge.enabled(&Metadata::builder().level(Level::Trace).build()));
+
+        let _guard = Context::current_with_span(TestSpan(events.clone())).attach();
+        bridge.log(&record(Level::Debug, format_args!("checkout")));
+        bridge.log(&record(Level::Trace, format_args!("poll")));
+
+        assert_eq!(events.lock().unwrap().len(), 1);
+        assert!(bridge.inner.0.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+//! # Google Cloud Trace Propagator
+//!
+//! Extract and inject values from Google Cloud's `X-Cloud-Trace-Context`
+//! header, as set by Google Cloud load balancers:
+//!
+//!   X-Cloud-Trace-Context: {trace_id}/{span_id};o={options}
+//!
+//! The trace id is 32 hex characters, the span id is a decimal unsigned 64-bit
+//! integer, and `o=1` marks the request as sampled. If the options are missing
+//! the sampling decision is deferred.
+//!
+//! See [`Google Cloud documentation`] for details of the format.
+//!
+//! [`Google Cloud documentation`]: https://cloud.google.com/trace/docs/setup#force-trace
+
+use opentelemetry::api::{
+    Context, Extractor, FieldIter, Injector, SpanContext, SpanId, TextMapFormat, TraceContextExt,
+    TraceId, TRACE_FLAG_DEFERRED, TRACE_FLAG_NOT_SAMPLED, TRACE_FLAG_SAMPLED,
+};
+
+/// Lower case so that it matches the keys of lower case carriers such as
+/// `HashMap` injectors and gRPC metadata.
+const CLOUD_TRACE_CONTEXT_HEADER: &str = "x-cloud-trace-context";
+
+lazy_static::lazy_static! {
+    static ref CLOUD_TRACE_CONTEXT_FIELD: [String; 1] = [CLOUD_TRACE_CONTEXT_HEADER.to_string()];
+}
+
+/// The Google Cloud Trace propagator propagates span contexts in the
+/// `X-Cloud-Trace-Context` header format.
+///
+/// Use [`GoogleCloudTracePropagator::extract_only`] to join traces started by
+/// Google Cloud load balancers without sending the header to downstream
+/// services, e.g. in a `TextMapCompositePropagator` alongside a
+/// `TraceContextPropagator`.
+///
+/// [`GoogleCloudTracePropagator::extract_only`]: struct.GoogleCloudTracePropagator.html#method.extract_only
+#[derive(Clone, Debug, Default)]
+pub struct GoogleCloudTracePropagator {
+    extract_only: bool,
+}
+
+impl GoogleCloudTracePropagator {
+    /// Create a Google Cloud Trace propagator that extracts and injects the
+    /// `X-Cloud-Trace-Context` header.
+    pub fn new() -> Self {
+        GoogleCloudTracePropagator::default()
+    }
+
+    /// Create a one-way Google Cloud Trace propagator that only extracts the
+    /// `X-Cloud-Trace-Context` header, and never injects it.
+    pub fn extract_only() -> Self {
+        GoogleCloudTracePropagator { extract_only: true }
+    }
+
+    /// Extract span context from header value
+    fn extract_span_context(&self, header_value: &str) -> Result<SpanContext, ()> {
+        let header_value = header_value.trim();
+        let (ids, options) = match header_value.find(';') {
+            Some(idx) => (&header_value[..idx], Some(&header_value[idx + 1..])),
+            None => (header_value, None),
+        };
+
+        let mut parts = ids.splitn(2, '/');
+        let trace_id = self.extract_trace_id(parts.next().ok_or(())?)?;
+        let span_id = self.extract_span_id(parts.next().ok_or(())?)?;
+        let flag = match options {
+            Some(options) => self.extract_options(options)?,
+            None => TRACE_FLAG_DEFERRED,
+        };
+
+        let span_context = SpanContext::new(trace_id, span_id, flag, true);
+        if span_context.is_valid() {
+            Ok(span_context)
+        } else {
+            Err(())
+        }
+    }
+
+    /// Extract trace id from 32 hex characters.
+    fn extract_trace_id(&self, trace_id: &str) -> Result<TraceId, ()> {
+        if trace_id.len() != 32 {
+            return Err(());
+        }
+
+        u128::from_str_radix(trace_id, 16)
+            .map(TraceId::from_u128)
+            .map_err(|_| ())
+    }
+
+    /// Extract span id from a decimal unsigned 64-bit integer.
+    fn extract_span_id(&self, span_id: &str) -> Result<SpanId, ()> {
+        if span_id.is_empty() || !span_id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(());
+        }
+
+        span_id.parse::<u64>().map(SpanId::from_u64).map_err(|_| ())
+    }
+
+    /// Extract sampled flag from the `o={options}` trace options.
+    ///
+    /// The lowest bit of the options marks the request as sampled.
+    fn extract_options(&self, options: &str) -> Result<u8, ()> {
+        let options = options.trim();
+        if !options.starts_with("o=") {
+            return Err(());
+        }
+        let options = options[2..].parse::<u8>().map_err(|_| ())?;
+        if options & 0x01 == 0x01 {
+            Ok(TRACE_FLAG_SAMPLED)
+        } else {
+            Ok(TRACE_FLAG_NOT_SAMPLED)
+        }
+    }
+}
+
+impl TextMapFormat for GoogleCloudTracePropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        if self.extract_only {
+            return;
+        }
+
+        let span_context = cx.span().span_context();
+        if span_context.is_valid() {
+            let header_value = format!(
+                "{:032x}/{};o={}",
+                span_context.trace_id().to_u128(),
+                span_context.span_id().to_u64(),
+                if span_context.is_sampled() { 1 } else { 0 },
+            );
+            injector.set(CLOUD_TRACE_CONTEXT_HEADER, header_value);
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let extract_result = extractor
+            .get(CLOUD_TRACE_CONTEXT_HEADER)
+            .ok_or(())
+            .and_then(|header_value| self.extract_span_context(header_value));
+        cx.with_remote_span_context(extract_result.unwrap_or_else(|_| SpanContext::empty_context()))
+    }
+
+    fn fields(&self) -> FieldIter {
+        FieldIter::new(CLOUD_TRACE_CONTEXT_FIELD.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::api;
+    use opentelemetry::api::{Span, TextMapCompositePropagator, TraceContextPropagator};
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    const TRACE_ID_STR: &str = "105445aa7843bc8bf206b12000100000";
+    const TRACE_ID: u128 = 0x1054_45aa_7843_bc8b_f206_b120_0010_0000;
+    const SPAN_ID: u64 = 1;
+
+    #[rustfmt::skip]
+    fn extract_data() -> Vec<(String, SpanContext)> {
+        vec![
+            (format!("{}/1;o=1", TRACE_ID_STR), SpanContext::new(TraceId::from_u128(TRACE_ID), SpanId::from_u64(SPAN_ID), TRACE_FLAG_SAMPLED, true)),
+            (format!("{}/1;o=0", TRACE_ID_STR), SpanContext::new(TraceId::from_u128(TRACE_ID), SpanId::from_u64(SPAN_ID), TRACE_FLAG_NOT_SAMPLED, true)),
+            (format!("{}/1", TRACE_ID_STR), SpanContext::new(TraceId::from_u128(TRACE_ID), SpanId::from_u64(SPAN_ID), TRACE_FLAG_DEFERRED, true)),
+            (format!("{}/18446744073709551615;o=3", TRACE_ID_STR), SpanContext::new(TraceId::from_u128(TRACE_ID), SpanId::from_u64(u64::MAX), TRACE_FLAG_SAMPLED, true)),
+            (format!("{}/18446744073709551616;o=1", TRACE_ID_STR), SpanContext::empty_context()),
+            (format!("{}/00017429;o=1", TRACE_ID_STR), SpanContext::new(TraceId::from_u128(TRACE_ID), SpanId::from_u64(17_429), TRACE_FLAG_SAMPLED, true)),
+            (format!("{}/abc;o=1", TRACE_ID_STR), SpanContext::empty_context()),
+            (format!("{}/-1;o=1", TRACE_ID_STR), SpanContext::empty_context()),
+            (format!("{}/1;o=x", TRACE_ID_STR), SpanContext::empty_context()),
+            (format!("{}/1;x=1", TRACE_ID_STR), SpanContext::empty_context()),
+            (format!("{}/0;o=1", TRACE_ID_STR), SpanContext::empty_context()),
+            ("105445aa7843bc8bf206b1200010000/1;o=1".to_string(), SpanContext::empty_context()),
+            (TRACE_ID_STR.to_string(), SpanContext::empty_context()),
+        ]
+    }
+
+    #[test]
+    fn extract() {
+        let propagator = GoogleCloudTracePropagator::new();
+        for (header_value, expected) in extract_data() {
+            let mut map: HashMap<String, String> = HashMap::new();
+            map.set(CLOUD_TRACE_CONTEXT_HEADER, header_value.clone());
+            let context = propagator.extract(&map);
+            assert_eq!(
+                context.remote_span_context(),
+                Some(&expected),
+                "{}",
+                header_value
+            );
+        }
+    }
+
+    #[test]
+    fn extract_empty() {
+        let map: HashMap<String, String> = HashMap::new();
+        let propagator = GoogleCloudTracePropagator::new();
+        let context = propagator.extract(&map);
+        assert_eq!(
+            context.remote_span_context(),
+            Some(&SpanContext::empty_context())
+        )
+    }
+
+    #[derive(Debug)]
+    struct TestSpan(SpanContext);
+
+    impl Span for TestSpan {
+        fn add_event_with_timestamp(
+            &self,
+            _name: String,
+            _timestamp: SystemTime,
+            _attributes: Vec<api::KeyValue>,
+        ) {
+        }
+        fn span_context(&self) -> api::SpanContext {
+            self.0.clone()
+        }
+        fn is_recording(&self) -> bool {
+            false
+        }
+        fn set_attribute(&self, _attribute: api::KeyValue) {}
+        fn set_status(&self, _code: api::StatusCode, _message: String) {}
+        fn update_name(&self, _new_name: String) {}
+        fn end_with_timestamp(&self, _timestamp: SystemTime) {}
+    }
+
+    #[test]
+    fn inject() {
+        let propagator = GoogleCloudTracePropagator::new();
+        let span_context = SpanContext::new(
+            TraceId::from_u128(TRACE_ID),
+            SpanId::from_u64(SPAN_ID),
+            TRACE_FLAG_SAMPLED,
+            true,
+        );
+        let mut injector = HashMap::new();
+        propagator.inject_context(
+            &Context::current_with_span(TestSpan(span_context)),
+            &mut injector,
+        );
+        assert_eq!(
+            injector.get(CLOUD_TRACE_CONTEXT_HEADER),
+            Some(&format!("{}/1;o=1", TRACE_ID_STR))
+        );
+
+        let mut injector = HashMap::new();
+        propagator.inject_context(
+            &Context::current_with_span(TestSpan(SpanContext::empty_context())),
+            &mut injector,
+        );
+        assert!(injector.is_empty());
+    }
+
+    #[test]
+    fn extract_only_composite() {
+        let composite = TextMapCompositePropagator::new(vec![
+            Box::new(TraceContextPropagator::new()),
+            Box::new(GoogleCloudTracePropagator::extract_only()),
+        ]);
+        let mut extractor: HashMap<String, String> = HashMap::new();
+        extractor.set(
+            CLOUD_TRACE_CONTEXT_HEADER,
+            format!("{}/1;o=1", TRACE_ID_STR),
+        );
+
+        let context = composite.extract(&extractor);
+        let span_context = context.remote_span_context().cloned().unwrap();
+        assert_eq!(span_context.trace_id(), TraceId::from_u128(TRACE_ID));
+
+        let mut injector = HashMap::new();
+        composite.inject_context(
+            &Context::current_with_span(TestSpan(span_context)),
+            &mut injector,
+        );
+        assert!(!injector.contains_key(CLOUD_TRACE_CONTEXT_HEADER));
+        assert!(injector.contains_key("traceparent"));
+    }
+}
@@ -1,19 +1,29 @@
 //! # Jaeger Propagator
 //!
-//! Extract and inject values from Jaeger's `uber-trace-id` header.
+//! Extract and inject values from Jaeger's `uber-trace-id` header, and
+//! baggage from Jaeger's `uberctx-{key}` headers.
 //!
 //! See [`Jaeger documentation`] for detail of Jaeger propagation format.
 //!
 //! [`Jaeger documentation`]: https://www.jaegertracing.io/docs/1.18/client-libraries/#propagation-format
 
 use opentelemetry::api::{
-    Context, Extractor, FieldIter, Injector, SpanContext, SpanId, TextMapFormat, TraceContextExt,
-    TraceId, TRACE_FLAG_DEBUG, TRACE_FLAG_NOT_SAMPLED, TRACE_FLAG_SAMPLED,
+    Context, CorrelationContextExt, Extractor, FieldIter, Injector, KeyValue, SpanContext, SpanId,
+    TextMapFormat, TraceContextExt, TraceId, TRACE_FLAG_DEBUG, TRACE_FLAG_NOT_SAMPLED,
+    TRACE_FLAG_SAMPLED,
 };
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use std::str::FromStr;
 
 const JAEGER_HEADER: &str = "uber-trace-id";
+const JAEGER_BAGGAGE_PREFIX: &str = "uberctx-";
 const DEPRECATED_PARENT_SPAN: &str = "0";
+/// Baggage values are URL encoded, leaving only unreserved characters as is.
+pub(crate) const BAGGAGE_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
 
 lazy_static::lazy_static! {
     static ref JAEGER_HEADER_FIELD: [String; 1] = [JAEGER_HEADER.to_string()];
@@ -25,6 +35,10 @@ lazy_static::lazy_static! {
 ///
 /// Note that jaeger header can be set in http header or encoded as url
 ///
+/// Correlation context entries are propagated as `uberctx-{key}` headers with
+/// URL encoded values. Baggage from `uberctx-` headers is extracted into the
+/// context's correlation context.
+///
 ///  [`Jaeger documentation`]: https://www.jaegertracing.io/docs/1.18/client-libraries/#propagation-format
 #[derive(Clone, Debug)]
 pub struct JaegerPropagator {
@@ -131,6 +145,13 @@ impl TextMapFormat for JaegerPropagator {
             );
             injector.set(JAEGER_HEADER, header_value);
         }
+
+        for (key, (value, _metadata)) in cx.correlation_context() {
+            injector.set(
+                &format!("{}{}", JAEGER_BAGGAGE_PREFIX, key.as_str().trim()),
+                utf8_percent_encode(String::from(value).as_str(), BAGGAGE_VALUE).to_string(),
+            );
+        }
     }
 
     fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
@@ -141,7 +162,36 @@ impl TextMapFormat for JaegerPropagator {
         } else {
             self.extract_span_context(header_value)
         };
-        cx.with_remote_span_context(extract_result.unwrap_or_else(|_| SpanContext::empty_context()))
+        let cx = cx.with_remote_span_context(
+            extract_result.unwrap_or_else(|_| SpanContext::empty_context()),
+        );
+
+        let baggage = extractor
+            .keys()
+            .into_iter()
+            .filter_map(|key| {
+                let prefix = key.get(..JAEGER_BAGGAGE_PREFIX.len())?;
+                if !prefix.eq_ignore_ascii_case(JAEGER_BAGGAGE_PREFIX) {
+                    return None;
+                }
+                let name = key[JAEGER_BAGGAGE_PREFIX.len()..].to_lowercase();
+                let value = percent_decode_str(extractor.get(key)?)
+                    .decode_utf8()
+                    .ok()?
+                    .into_owned();
+                if name.is_empty() {
+                    None
+                } else {
+                    Some(KeyValue::new(name, value))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if baggage.is_empty() {
+            cx
+        } else {
+            cx.with_correlations(baggage)
+        }
     }
 
     fn fields(&self) -> FieldIter {
@@ -154,8 +204,9 @@ mod tests {
     use crate::trace_propagator::jaeger_propagator::{JaegerPropagator, JAEGER_HEADER};
     use opentelemetry::api;
     use opentelemetry::api::{
-        Context, Injector, Span, SpanContext, SpanId, TextMapFormat, TraceContextExt, TraceId,
-        TRACE_FLAG_DEBUG, TRACE_FLAG_NOT_SAMPLED, TRACE_FLAG_SAMPLED,
+        Context, CorrelationContextExt, Injector, KeyValue, Span, SpanContext, SpanId,
+        TextMapFormat, TraceContextExt, TraceId, Value, TRACE_FLAG_DEBUG, TRACE_FLAG_NOT_SAMPLED,
+        TRACE_FLAG_SAMPLED,
     };
     use std::collections::HashMap;
     use std::time::SystemTime;
@@ -375,4 +426,58 @@ mod tests {
             assert_eq!(injector.get(JAEGER_HEADER), Some(&header_value));
         }
     }
+
+    #[test]
+    fn test_extract_baggage() {
+        let mut map: HashMap<String, String> = HashMap::new();
+        map.set(
+            JAEGER_HEADER,
+            format!("{}:{}:0:1", LONG_TRACE_ID_STR, SPAN_ID_STR),
+        );
+        map.set("uberctx-user-id", "42".to_string());
+        map.set(
+            "UBERCTX-Greeting",
+            "hello%20world%2C%20%F0%9F%91%8B".to_string(),
+        );
+        map.set("uberctx-invalid", "%ff".to_string());
+        map.set("uberctx-", "no key".to_string());
+        map.set("other-header", "ignored".to_string());
+
+        let propagator = JaegerPropagator::new();
+        let context = propagator.extract(&map);
+        let correlations = context.correlation_context();
+
+        assert_eq!(correlations.len(), 2);
+        assert_eq!(correlations.get("user-id"), Some(&Value::from("42")));
+        assert_eq!(
+            correlations.get("greeting"),
+            Some(&Value::from("hello world, \u{1f44b}"))
+        );
+        assert!(context.remote_span_context().unwrap().is_valid());
+    }
+
+    #[test]
+    fn test_inject_baggage() {
+        let propagator = JaegerPropagator::new();
+        let cx = Context::new().with_correlations(vec![
+            KeyValue::new("user-id", 42i64),
+            KeyValue::new("greeting", "hello world, \u{1f44b}"),
+        ]);
+        let mut injector = HashMap::new();
+        propagator.inject_context(&cx, &mut injector);
+
+        assert_eq!(injector.get(JAEGER_HEADER), None);
+        assert_eq!(injector.get("uberctx-user-id"), Some(&"42".to_string()));
+        assert_eq!(
+            injector.get("uberctx-greeting"),
+            Some(&"hello%20world%2C%20%F0%9F%91%8B".to_string())
+        );
+
+        // round trip
+        let extracted = propagator.extract(&injector);
+        assert_eq!(
+            extracted.correlation_context().get("greeting"),
+            Some(&Value::from("hello world, \u{1f44b}"))
+        );
+    }
 }
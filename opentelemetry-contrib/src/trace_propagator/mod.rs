@@ -1,6 +1,77 @@
 //! External context propagators
 //!
+//! Call [`register_propagators`] to make them available to
+//! `OTEL_PROPAGATORS` and `global::TextMapPropagatorBuilder`.
 //!
+//! [`register_propagators`]: fn.register_propagators.html
 
 pub mod b3_propagator;
+pub mod gcp_propagator;
 pub mod jaeger_propagator;
+pub mod ot_propagator;
+
+use opentelemetry::global;
+
+/// Registers the contrib propagators with their `OTEL_PROPAGATORS` names:
+///
+/// - `b3`: [`B3Propagator`] with a single `b3` header
+/// - `b3multi`: [`B3Propagator`] with multiple `X-B3-` headers
+/// - `jaeger`: [`JaegerPropagator`]
+/// - `ottrace`: [`OpenTracingPropagator`]
+///
+/// [`B3Propagator`]: b3_propagator/struct.B3Propagator.html
+/// [`JaegerPropagator`]: jaeger_propagator/struct.JaegerPropagator.html
+/// [`OpenTracingPropagator`]: ot_propagator/struct.OpenTracingPropagator.html
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::global;
+///
+/// opentelemetry_contrib::register_propagators();
+///
+/// // e.g. OTEL_PROPAGATORS=tracecontext,baggage,b3multi,jaeger
+/// global::TextMapPropagatorBuilder::from_env()
+///     .install()
+///     .expect("unknown propagators in OTEL_PROPAGATORS");
+/// ```
+pub fn register_propagators() {
+    global::register_text_map_propagator("b3", || {
+        b3_propagator::B3Propagator::with_encoding(b3_propagator::B3Encoding::SingleHeader)
+    });
+    global::register_text_map_propagator("b3multi", || {
+        b3_propagator::B3Propagator::with_encoding(b3_propagator::B3Encoding::MultipleHeader)
+    });
+    global::register_text_map_propagator("jaeger", jaeger_propagator::JaegerPropagator::new);
+    global::register_text_map_propagator("ottrace", ot_propagator::OpenTracingPropagator::new);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::api::TextMapFormat;
+
+    #[test]
+    fn build_registered_propagators() {
+        register_propagators();
+
+        let propagator = global::TextMapPropagatorBuilder::new()
+            .with_propagator("tracecontext")
+            .with_propagator("b3")
+            .with_propagator("jaeger")
+            .build()
+            .unwrap();
+        let mut fields = propagator.fields().collect::<Vec<_>>();
+        fields.sort_unstable();
+        assert_eq!(
+            fields,
+            vec!["b3", "traceparent", "tracestate", "uber-trace-id"]
+        );
+
+        assert!(global::TextMapPropagatorBuilder::new()
+            .with_propagator("b3multi")
+            .with_propagator("ottrace")
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,347 @@
+//! # OpenTracing Propagator
+//!
+//! Extract and inject values in the header format used by the OpenTracing
+//! basic tracer:
+//!
+//!  - `ot-tracer-traceid`: 64-bit or 128-bit trace id as 16 or 32 hex characters
+//!  - `ot-tracer-spanid`: 64-bit span id as 16 hex characters
+//!  - `ot-tracer-sampled`: `true` or `false`, spans are not sampled if missing
+//!  - `ot-baggage-{key}`: URL encoded baggage items
+//!
+//! Baggage items are mapped onto the context's correlation context.
+
+use super::jaeger_propagator::BAGGAGE_VALUE;
+use opentelemetry::api::{
+    Context, CorrelationContextExt, Extractor, FieldIter, Injector, KeyValue, SpanContext, SpanId,
+    TextMapFormat, TraceContextExt, TraceId, TRACE_FLAG_NOT_SAMPLED, TRACE_FLAG_SAMPLED,
+};
+use percent_encoding::{percent_decode_str, utf8_percent_encode};
+
+const OT_TRACE_ID_HEADER: &str = "ot-tracer-traceid";
+const OT_SPAN_ID_HEADER: &str = "ot-tracer-spanid";
+const OT_SAMPLED_HEADER: &str = "ot-tracer-sampled";
+const OT_BAGGAGE_PREFIX: &str = "ot-baggage-";
+
+lazy_static::lazy_static! {
+    static ref OT_HEADER_FIELDS: [String; 3] = [
+        OT_TRACE_ID_HEADER.to_string(),
+        OT_SPAN_ID_HEADER.to_string(),
+        OT_SAMPLED_HEADER.to_string(),
+    ];
+}
+
+/// The OpenTracing propagator propagates span contexts in the OpenTracing
+/// basic tracer header format.
+///
+/// Trace ids that fit in 64 bits are injected as 16 hex characters so they can
+/// be read by tracers that only support 64-bit trace ids, larger trace ids are
+/// injected as 32 hex characters.
+///
+/// Correlation context entries are injected as `ot-baggage-{key}` headers with
+/// URL encoded values, and `ot-baggage-` headers are extracted into the
+/// correlation context whether or not they come with a valid span context.
+#[derive(Clone, Debug)]
+pub struct OpenTracingPropagator {
+    _private: (),
+}
+
+impl Default for OpenTracingPropagator {
+    fn default() -> Self {
+        OpenTracingPropagator { _private: () }
+    }
+}
+
+impl OpenTracingPropagator {
+    /// Create an OpenTracing propagator
+    pub fn new() -> Self {
+        OpenTracingPropagator::default()
+    }
+
+    /// Extract span context from the headers.
+    fn extract_span_context(&self, extractor: &dyn Extractor) -> Result<SpanContext, ()> {
+        let trace_id = self.extract_trace_id(extractor.get(OT_TRACE_ID_HEADER).ok_or(())?)?;
+        let span_id = self.extract_span_id(extractor.get(OT_SPAN_ID_HEADER).ok_or(())?)?;
+        let flag = extractor
+            .get(OT_SAMPLED_HEADER)
+            .map_or(Ok(TRACE_FLAG_NOT_SAMPLED), |sampled| {
+                self.extract_sampled(sampled)
+            })?;
+
+        let span_context = SpanContext::new(trace_id, span_id, flag, true);
+        if span_context.is_valid() {
+            Ok(span_context)
+        } else {
+            Err(())
+        }
+    }
+
+    /// Extract a 64-bit or 128-bit trace id.
+    fn extract_trace_id(&self, trace_id: &str) -> Result<TraceId, ()> {
+        if trace_id.len() != 16 && trace_id.len() != 32 {
+            return Err(());
+        }
+
+        u128::from_str_radix(trace_id, 16)
+            .map(TraceId::from_u128)
+            .map_err(|_| ())
+    }
+
+    /// Extract a 64-bit span id.
+    fn extract_span_id(&self, span_id: &str) -> Result<SpanId, ()> {
+        if span_id.len() != 16 {
+            return Err(());
+        }
+
+        u64::from_str_radix(span_id, 16)
+            .map(SpanId::from_u64)
+            .map_err(|_| ())
+    }
+
+    /// Extract the sampled flag.
+    fn extract_sampled(&self, sampled: &str) -> Result<u8, ()> {
+        match sampled.trim() {
+            s if s.eq_ignore_ascii_case("true") || s == "1" => Ok(TRACE_FLAG_SAMPLED),
+            s if s.eq_ignore_ascii_case("false") || s == "0" => Ok(TRACE_FLAG_NOT_SAMPLED),
+            _ => Err(()),
+        }
+    }
+}
+
+impl TextMapFormat for OpenTracingPropagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span_context = cx.span().span_context();
+        if span_context.is_valid() {
+            let trace_id = span_context.trace_id().to_u128();
+            let trace_id = if trace_id > u128::from(u64::MAX) {
+                format!("{:032x}", trace_id)
+            } else {
+                format!("{:016x}", trace_id)
+            };
+            injector.set(OT_TRACE_ID_HEADER, trace_id);
+            injector.set(
+                OT_SPAN_ID_HEADER,
+                format!("{:016x}", span_context.span_id().to_u64()),
+            );
+            injector.set(OT_SAMPLED_HEADER, span_context.is_sampled().to_string());
+        }
+
+        for (key, (value, _metadata)) in cx.correlation_context() {
+            injector.set(
+                &format!("{}{}", OT_BAGGAGE_PREFIX, key.as_str().trim()),
+                utf8_percent_encode(String::from(value).as_str(), BAGGAGE_VALUE).to_string(),
+            );
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let span_context = self
+            .extract_span_context(extractor)
+            .unwrap_or_else(|_| SpanContext::empty_context());
+        let cx = cx.with_remote_span_context(span_context);
+
+        let baggage = extractor
+            .keys()
+            .into_iter()
+            .filter_map(|key| {
+                let prefix = key.get(..OT_BAGGAGE_PREFIX.len())?;
+                if !prefix.eq_ignore_ascii_case(OT_BAGGAGE_PREFIX) {
+                    return None;
+                }
+                let name = key[OT_BAGGAGE_PREFIX.len()..].to_lowercase();
+                let value = percent_decode_str(extractor.get(key)?)
+                    .decode_utf8()
+                    .ok()?
+                    .into_owned();
+                if name.is_empty() {
+                    None
+                } else {
+                    Some(KeyValue::new(name, value))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if baggage.is_empty() {
+            cx
+        } else {
+            cx.with_correlations(baggage)
+        }
+    }
+
+    fn fields(&self) -> FieldIter {
+        FieldIter::new(OT_HEADER_FIELDS.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::api;
+    use opentelemetry::api::{Span, Value};
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    const TRACE_ID_64_STR: &str = "4d00000000000016";
+    const TRACE_ID_64: u128 = 0x4d00_0000_0000_0016;
+    const TRACE_ID_128_STR: &str = "000000000000004d0000000000000016";
+    const TRACE_ID_128_HIGH_STR: &str = "100000000000004d0000000000000016";
+    const TRACE_ID_128_HIGH: u128 = 0x1000_0000_0000_004d_0000_0000_0000_0016;
+    const SPAN_ID_STR: &str = "0000000000017c29";
+    const SPAN_ID: u64 = 0x0000_0000_0001_7c29;
+
+    #[rustfmt::skip]
+    fn extract_data() -> Vec<(&'static str, &'static str, &'static str, SpanContext)> {
+        vec![
+            (TRACE_ID_64_STR, SPAN_ID_STR, "true", SpanContext::new(TraceId::from_u128(TRACE_ID_64), SpanId::from_u64(SPAN_ID), TRACE_FLAG_SAMPLED, true)),
+            (TRACE_ID_128_STR, SPAN_ID_STR, "true", SpanContext::new(TraceId::from_u128(0x4d_0000_0000_0000_0016), SpanId::from_u64(SPAN_ID), TRACE_FLAG_SAMPLED, true)),
+            (TRACE_ID_128_HIGH_STR, SPAN_ID_STR, "false", SpanContext::new(TraceId::from_u128(TRACE_ID_128_HIGH), SpanId::from_u64(SPAN_ID), TRACE_FLAG_NOT_SAMPLED, true)),
+            (TRACE_ID_64_STR, SPAN_ID_STR, "1", SpanContext::new(TraceId::from_u128(TRACE_ID_64), SpanId::from_u64(SPAN_ID), TRACE_FLAG_SAMPLED, true)),
+            ("4d0000000000016", SPAN_ID_STR, "true", SpanContext::empty_context()),
+            ("invalidtraceid00", SPAN_ID_STR, "true", SpanContext::empty_context()),
+            (TRACE_ID_64_STR, "17c29", "true", SpanContext::empty_context()),
+            (TRACE_ID_64_STR, SPAN_ID_STR, "maybe", SpanContext::empty_context()),
+            ("0000000000000000", SPAN_ID_STR, "true", SpanContext::empty_context()),
+        ]
+    }
+
+    #[test]
+    fn extract_span_context() {
+        let propagator = OpenTracingPropagator::new();
+        for (trace_id, span_id, sampled, expected) in extract_data() {
+            let mut map: HashMap<String, String> = HashMap::new();
+            map.set(OT_TRACE_ID_HEADER, trace_id.to_string());
+            map.set(OT_SPAN_ID_HEADER, span_id.to_string());
+            map.set(OT_SAMPLED_HEADER, sampled.to_string());
+            let context = propagator.extract(&map);
+            assert_eq!(context.remote_span_context(), Some(&expected));
+        }
+    }
+
+    #[test]
+    fn extract_missing_headers() {
+        let propagator = OpenTracingPropagator::new();
+        let mut map: HashMap<String, String> = HashMap::new();
+        map.set(OT_TRACE_ID_HEADER, TRACE_ID_64_STR.to_string());
+        map.set(OT_SPAN_ID_HEADER, SPAN_ID_STR.to_string());
+        map.set("ot-baggage-user-id", "42".to_string());
+        let context = propagator.extract(&map);
+        assert_eq!(
+            context.remote_span_context(),
+            Some(&SpanContext::new(
+                TraceId::from_u128(TRACE_ID_64),
+                SpanId::from_u64(SPAN_ID),
+                TRACE_FLAG_NOT_SAMPLED,
+                true
+            ))
+        );
+        assert_eq!(
+            context.correlation_context().get("user-id"),
+            Some(&Value::from("42"))
+        );
+
+        map.remove(OT_SPAN_ID_HEADER);
+        let context = propagator.extract(&map);
+        assert_eq!(
+            context.remote_span_context(),
+            Some(&SpanContext::empty_context())
+        );
+        assert_eq!(
+            context.correlation_context().get("user-id"),
+            Some(&Value::from("42"))
+        );
+    }
+
+    #[test]
+    fn extract_baggage() {
+        let propagator = OpenTracingPropagator::new();
+        let mut map: HashMap<String, String> = HashMap::new();
+        map.set("ot-baggage-user-id", "42".to_string());
+        map.set("OT-Baggage-Tenant", "acme%20corp".to_string());
+        map.set("ot-baggage-invalid", "%ff".to_string());
+        map.set("ot-baggage-", "no key".to_string());
+        map.set("other-header", "ignored".to_string());
+        let context = propagator.extract(&map);
+        let correlations = context.correlation_context();
+
+        assert_eq!(correlations.len(), 2);
+        assert_eq!(correlations.get("user-id"), Some(&Value::from("42")));
+        assert_eq!(correlations.get("tenant"), Some(&Value::from("acme corp")));
+    }
+
+    #[derive(Debug)]
+    struct TestSpan(SpanContext);
+
+    impl Span for TestSpan {
+        fn add_event_with_timestamp(
+            &self,
+            _name: String,
+            _timestamp: SystemTime,
+            _attributes: Vec<api::KeyValue>,
+        ) {
+        }
+        fn span_context(&self) -> api::SpanContext {
+            self.0.clone()
+        }
+        fn is_recording(&self) -> bool {
+            false
+        }
+        fn set_attribute(&self, _attribute: api::KeyValue) {}
+        fn set_status(&self, _code: api::StatusCode, _message: String) {}
+        fn update_name(&self, _new_name: String) {}
+        fn end_with_timestamp(&self, _timestamp: SystemTime) {}
+    }
+
+    #[rustfmt::skip]
+    fn inject_data() -> Vec<(SpanContext, &'static str, &'static str)> {
+        vec![
+            (SpanContext::new(TraceId::from_u128(TRACE_ID_64), SpanId::from_u64(SPAN_ID), TRACE_FLAG_SAMPLED, true), TRACE_ID_64_STR, "true"),
+            (SpanContext::new(TraceId::from_u128(TRACE_ID_128_HIGH), SpanId::from_u64(SPAN_ID), TRACE_FLAG_NOT_SAMPLED, true), TRACE_ID_128_HIGH_STR, "false"),
+        ]
+    }
+
+    #[test]
+    fn inject_span_context() {
+        let propagator = OpenTracingPropagator::new();
+        for (span_context, trace_id, sampled) in inject_data() {
+            let mut injector = HashMap::new();
+            propagator.inject_context(
+                &Context::current_with_span(TestSpan(span_context)),
+                &mut injector,
+            );
+            assert_eq!(
+                injector.get(OT_TRACE_ID_HEADER),
+                Some(&trace_id.to_string())
+            );
+            assert_eq!(
+                injector.get(OT_SPAN_ID_HEADER),
+                Some(&SPAN_ID_STR.to_string())
+            );
+            assert_eq!(injector.get(OT_SAMPLED_HEADER), Some(&sampled.to_string()));
+        }
+    }
+
+    #[test]
+    fn inject_baggage() {
+        let propagator = OpenTracingPropagator::new();
+        let cx = Context::current_with_span(TestSpan(SpanContext::empty_context()))
+            .with_correlations(vec![
+                KeyValue::new("user-id", 42i64),
+                KeyValue::new("greeting", "hello world, \u{1f44b}"),
+            ]);
+        let mut injector = HashMap::new();
+        propagator.inject_context(&cx, &mut injector);
+
+        assert_eq!(injector.get(OT_TRACE_ID_HEADER), None);
+        assert_eq!(injector.get("ot-baggage-user-id"), Some(&"42".to_string()));
+        assert_eq!(
+            injector.get("ot-baggage-greeting"),
+            Some(&"hello%20world%2C%20%F0%9F%91%8B".to_string())
+        );
+
+        // round trip
+        let extracted = propagator.extract(&injector);
+        assert_eq!(
+            extracted.correlation_context().get("greeting"),
+            Some(&Value::from("hello world, \u{1f44b}"))
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! # Client Middleware
+//!
+//! Creates a `Client` span for every request sent by the inner service and
+//! injects its context into the request headers.
+use crate::{content_length, request_attributes, status_code_from_http};
+use opentelemetry::api::{self, Context, TraceContextExt, Tracer};
+use opentelemetry::global;
+use opentelemetry_semantic_conventions::trace::{
+    HTTP_RESPONSE_CONTENT_LENGTH, HTTP_STATUS_CODE, HTTP_URL, NET_PEER_NAME, NET_PEER_PORT,
+};
+use pin_project::pin_project;
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A [`Layer`] that wraps services in a [`ClientService`].
+///
+/// [`Layer`]: https://docs.rs/tower-layer/0.3/tower_layer/trait.Layer.html
+/// [`ClientService`]: struct.ClientService.html
+#[derive(Debug)]
+pub struct ClientLayer<T> {
+    tracer: Arc<T>,
+}
+
+impl<T> ClientLayer<T> {
+    /// Create a layer that starts client spans with the given tracer.
+    pub fn new(tracer: T) -> Self {
+        ClientLayer {
+            tracer: Arc::new(tracer),
+        }
+    }
+}
+
+impl<T> Clone for ClientLayer<T> {
+    fn clone(&self) -> Self {
+        ClientLayer {
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+impl<S, T> Layer<S> for ClientLayer<T> {
+    type Service = ClientService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientService {
+            inner,
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+/// A [`Service`] that starts a `Client` span as a child of the current
+/// context for every request, and injects it with the global propagator.
+///
+/// [`Service`]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
+#[derive(Clone, Debug)]
+pub struct ClientService<S, T> {
+    inner: S,
+    tracer: Arc<T>,
+}
+
+impl<S, T, ReqBody, ResBody> Service<http::Request<ReqBody>> for ClientService<S, T>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Error,
+    T: Tracer,
+    T::Span: Send + Sync,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ClientFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        let mut attributes = request_attributes(&request);
+        attributes.push(HTTP_URL.string(request.uri().to_string()));
+        if let Some(host) = request.uri().host() {
+            attributes.push(NET_PEER_NAME.string(host.to_string()));
+        }
+        if let Some(port) = request
+            .uri()
+            .port_u16()
+            .or_else(|| match request.uri().scheme_str() {
+                Some("http") => Some(80),
+                Some("https") => Some(443),
+                _ => None,
+            })
+        {
+            attributes.push(NET_PEER_PORT.i64(i64::from(port)));
+        }
+
+        let parent_cx = Context::current();
+        let span = self.tracer.build_with_context(
+            self.tracer
+                .span_builder(format!("HTTP {}", request.method()))
+                .with_kind(api::SpanKind::Client)
+                .with_attributes(attributes),
+            &parent_cx,
+        );
+        let cx = parent_cx.with_span(span);
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, request.headers_mut())
+        });
+
+        let inner = {
+            let _guard = cx.clone().attach();
+            self.inner.call(request)
+        };
+
+        ClientFuture { inner, cx }
+    }
+}
+
+/// Response future of [`ClientService`], ends the span with the response
+/// status or transport error.
+///
+/// [`ClientService`]: struct.ClientService.html
+#[pin_project]
+#[derive(Debug)]
+pub struct ClientFuture<F> {
+    #[pin]
+    inner: F,
+    cx: Context,
+}
+
+impl<F, ResBody, E> Future for ClientFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: Error,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, task_cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.cx.clone().attach();
+        let result = futures::ready!(this.inner.poll(task_cx));
+
+        let span = this.cx.span();
+        match &result {
+            Ok(response) => {
+                span.set_attribute(HTTP_STATUS_CODE.i64(i64::from(response.status().as_u16())));
+                if let Some(length) =
+                    content_length(&HTTP_RESPONSE_CONTENT_LENGTH, response.headers())
+                {
+                    span.set_attribute(length);
+                }
+                span.set_status(status_code_from_http(response.status()), String::new());
+            }
+            Err(err) => {
+                span.record_exception(err);
+                span.set_status(api::StatusCode::Unknown, err.to_string());
+            }
+        }
+        span.end();
+
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{attributes, tracer};
+    use std::io;
+
+    /// Fails requests to `/error`, and responds with the injected
+    /// `traceparent` header otherwise.
+    #[derive(Debug)]
+    struct EchoService;
+
+    impl Service<http::Request<()>> for EchoService {
+        type Response = http::Response<String>;
+        type Error = io::Error;
+        type Future = futures::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<()>) -> Self::Future {
+            if request.uri().path() == "/error" {
+                return futures::future::err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    "connection refused",
+                ));
+            }
+            let traceparent = request.headers()["traceparent"].to_str().unwrap();
+            futures::future::ok(http::Response::new(traceparent.to_string()))
+        }
+    }
+
+    #[test]
+    fn client_span() {
+        let (tracer, spans) = tracer();
+        let mut service = ClientLayer::new(tracer).layer(EchoService);
+
+        let request = http::Request::builder()
+            .uri("http://example.com/users?id=1")
+            .body(())
+            .unwrap();
+        let response = futures::executor::block_on(service.call(request)).unwrap();
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name.as_str(), "HTTP GET");
+        assert_eq!(span.span_kind, api::SpanKind::Client);
+        assert_eq!(span.status_code, api::StatusCode::OK);
+        assert_eq!(
+            response.body(),
+            &format!(
+                "00-{:032x}-{:016x}-01",
+                span.span_context.trace_id().to_u128(),
+                span.span_context.span_id().to_u64()
+            )
+        );
+
+        let attributes = attributes(span);
+        assert_eq!(attributes["http.method"], "GET");
+        assert_eq!(attributes["http.url"], "http://example.com/users?id=1");
+        assert_eq!(attributes["net.peer.name"], "example.com");
+        assert_eq!(attributes["net.peer.port"], "80");
+        assert_eq!(attributes["http.status_code"], "200");
+    }
+
+    #[test]
+    fn client_transport_error() {
+        let (tracer, spans) = tracer();
+        let mut service = ClientLayer::new(tracer).layer(EchoService);
+
+        let request = http::Request::post("https://example.com:8443/error")
+            .body(())
+            .unwrap();
+        assert!(futures::executor::block_on(service.call(request)).is_err());
+
+        let spans = spans.lock().unwrap();
+        let span = &spans[0];
+        assert_eq!(span.status_code, api::StatusCode::Unknown);
+        assert_eq!(span.status_message, "connection refused");
+        assert_eq!(attributes(span)["net.peer.port"], "8443");
+        let event = span.message_events.iter().next().unwrap();
+        assert_eq!(event.name, "exception");
+    }
+}
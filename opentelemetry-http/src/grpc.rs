@@ -0,0 +1,587 @@
+//! # gRPC Middleware
+//!
+//! Creates `Client` and `Server` spans for [`tonic`] clients and servers,
+//! named `package.Service/Method` and with the `rpc.*` semantic convention
+//! attributes.
+//!
+//! gRPC reports the status of a call in the `grpc-status` trailer, so the
+//! response body is wrapped in a [`GrpcBody`] that ends the span once the
+//! trailers are received.
+//!
+//! [`tonic`]: https://crates.io/crates/tonic
+//! [`GrpcBody`]: struct.GrpcBody.html
+use futures::future::{MapOk, TryFutureExt};
+use http_body::Body as HttpBody;
+use opentelemetry::api::{self, Context, KeyValue, TraceContextExt, Tracer};
+use opentelemetry::global;
+use opentelemetry_semantic_conventions::trace::{
+    NET_PEER_NAME, NET_PEER_PORT, RPC_METHOD, RPC_SERVICE, RPC_SYSTEM,
+};
+use pin_project::{pin_project, pinned_drop};
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tonic::body::BoxBody;
+use tonic::client::GrpcService;
+use tonic::transport::NamedService;
+use tower_layer::Layer;
+use tower_service::Service;
+
+const GRPC_STATUS_HEADER: &str = "grpc-status";
+const GRPC_MESSAGE_HEADER: &str = "grpc-message";
+
+/// Maps a gRPC status code onto a span status.
+///
+/// The span status codes mirror the gRPC codes, unknown codes map to
+/// `StatusCode::Unknown`.
+pub fn status_code_from_grpc(code: i32) -> api::StatusCode {
+    match code {
+        0 => api::StatusCode::OK,
+        1 => api::StatusCode::Canceled,
+        3 => api::StatusCode::InvalidArgument,
+        4 => api::StatusCode::DeadlineExceeded,
+        5 => api::StatusCode::NotFound,
+        6 => api::StatusCode::AlreadyExists,
+        7 => api::StatusCode::PermissionDenied,
+        8 => api::StatusCode::ResourceExhausted,
+        9 => api::StatusCode::FailedPrecondition,
+        10 => api::StatusCode::Aborted,
+        11 => api::StatusCode::OutOfRange,
+        12 => api::StatusCode::Unimplemented,
+        13 => api::StatusCode::Internal,
+        14 => api::StatusCode::Unavailable,
+        15 => api::StatusCode::DataLoss,
+        16 => api::StatusCode::Unauthenticated,
+        _ => api::StatusCode::Unknown,
+    }
+}
+
+/// Span name and `rpc.*` attributes from a `/package.Service/Method` path.
+fn rpc_span<B>(request: &http::Request<B>) -> (String, Vec<KeyValue>) {
+    let name = request.uri().path().trim_start_matches('/');
+    let mut attributes = vec![RPC_SYSTEM.string("grpc")];
+    if let Some(idx) = name.rfind('/') {
+        attributes.push(RPC_SERVICE.string(name[..idx].to_string()));
+        attributes.push(RPC_METHOD.string(name[idx + 1..].to_string()));
+    }
+
+    (name.to_string(), attributes)
+}
+
+/// Ends the span with the `grpc-status` of the given headers, returns `false`
+/// if they do not contain a status.
+fn end_with_status(cx: &Context, headers: &http::HeaderMap) -> bool {
+    let code = match headers
+        .get(GRPC_STATUS_HEADER)
+        .and_then(|status| status.to_str().ok())
+    {
+        Some(code) => code.parse().unwrap_or(-1),
+        None => return false,
+    };
+    let message = headers
+        .get(GRPC_MESSAGE_HEADER)
+        .and_then(|message| message.to_str().ok())
+        .unwrap_or_default();
+
+    let span = cx.span();
+    span.set_status(status_code_from_grpc(code), message.to_string());
+    span.end();
+    true
+}
+
+/// A [`Layer`] that wraps tonic services in a [`GrpcServerService`].
+///
+/// [`Layer`]: https://docs.rs/tower-layer/0.3/tower_layer/trait.Layer.html
+/// [`GrpcServerService`]: struct.GrpcServerService.html
+///
+/// # Examples
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_http::GrpcServerLayer;
+/// use tonic::transport::Server;
+/// use tower_layer::Layer;
+///
+/// let layer = GrpcServerLayer::new(global::tracer("greeter"));
+/// Server::builder()
+///     .add_service(layer.layer(GreeterServer::new(greeter)))
+///     .serve(addr)
+///     .await?;
+/// ```
+#[derive(Debug)]
+pub struct GrpcServerLayer<T> {
+    tracer: Arc<T>,
+}
+
+impl<T> GrpcServerLayer<T> {
+    /// Create a layer that starts server spans with the given tracer.
+    pub fn new(tracer: T) -> Self {
+        GrpcServerLayer {
+            tracer: Arc::new(tracer),
+        }
+    }
+}
+
+impl<T> Clone for GrpcServerLayer<T> {
+    fn clone(&self) -> Self {
+        GrpcServerLayer {
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+impl<S, T> Layer<S> for GrpcServerLayer<T> {
+    type Service = GrpcServerService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcServerService {
+            inner,
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+/// A tonic [`Service`] that extracts the remote context with the global
+/// propagator and starts a `Server` span for every call.
+///
+/// [`Service`]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
+#[derive(Debug)]
+pub struct GrpcServerService<S, T> {
+    inner: S,
+    tracer: Arc<T>,
+}
+
+impl<S: Clone, T> Clone for GrpcServerService<S, T> {
+    fn clone(&self) -> Self {
+        GrpcServerService {
+            inner: self.inner.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+impl<S: NamedService, T> NamedService for GrpcServerService<S, T> {
+    const NAME: &'static str = S::NAME;
+}
+
+type BoxResponse = fn(http::Response<GrpcBody<BoxBody>>) -> http::Response<BoxBody>;
+
+impl<S, T, ReqBody> Service<http::Request<ReqBody>> for GrpcServerService<S, T>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>>,
+    S::Error: Error,
+    T: Tracer,
+    T::Span: Send + Sync,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = S::Error;
+    type Future = MapOk<GrpcFuture<S::Future>, BoxResponse>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let parent_cx =
+            global::get_text_map_propagator(|propagator| propagator.extract(request.headers()));
+        let (name, attributes) = rpc_span(&request);
+        let span = self.tracer.build_with_context(
+            self.tracer
+                .span_builder(name)
+                .with_kind(api::SpanKind::Server)
+                .with_attributes(attributes),
+            &parent_cx,
+        );
+        let cx = parent_cx.with_span(span);
+
+        let inner = {
+            let _guard = cx.clone().attach();
+            self.inner.call(request)
+        };
+
+        let box_response: BoxResponse = |response| response.map(BoxBody::new);
+        GrpcFuture { inner, cx }.map_ok(box_response)
+    }
+}
+
+/// A [`Layer`] that wraps tonic channels in a [`GrpcClientService`].
+///
+/// [`Layer`]: https://docs.rs/tower-layer/0.3/tower_layer/trait.Layer.html
+/// [`GrpcClientService`]: struct.GrpcClientService.html
+///
+/// # Examples
+///
+/// ```ignore
+/// use opentelemetry::global;
+/// use opentelemetry_http::GrpcClientLayer;
+/// use tonic::transport::Channel;
+/// use tower_layer::Layer;
+///
+/// let channel = Channel::from_static("http://[::1]:50051").connect().await?;
+/// let layer = GrpcClientLayer::new(global::tracer("greeter"));
+/// let mut client = GreeterClient::new(layer.layer(channel));
+/// ```
+#[derive(Debug)]
+pub struct GrpcClientLayer<T> {
+    tracer: Arc<T>,
+}
+
+impl<T> GrpcClientLayer<T> {
+    /// Create a layer that starts client spans with the given tracer.
+    pub fn new(tracer: T) -> Self {
+        GrpcClientLayer {
+            tracer: Arc::new(tracer),
+        }
+    }
+}
+
+impl<T> Clone for GrpcClientLayer<T> {
+    fn clone(&self) -> Self {
+        GrpcClientLayer {
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+impl<S, T> Layer<S> for GrpcClientLayer<T> {
+    type Service = GrpcClientService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcClientService {
+            inner,
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+/// A [`Service`] that starts a `Client` span as a child of the current
+/// context for every call, and injects it with the global propagator.
+///
+/// The inner service can be any [`GrpcService`], e.g. a tonic `Channel`.
+///
+/// [`Service`]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
+/// [`GrpcService`]: https://docs.rs/tonic/0.2/tonic/client/trait.GrpcService.html
+#[derive(Debug)]
+pub struct GrpcClientService<S, T> {
+    inner: S,
+    tracer: Arc<T>,
+}
+
+impl<S: Clone, T> Clone for GrpcClientService<S, T> {
+    fn clone(&self) -> Self {
+        GrpcClientService {
+            inner: self.inner.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+}
+
+impl<S, T, ReqBody> Service<http::Request<ReqBody>> for GrpcClientService<S, T>
+where
+    S: GrpcService<ReqBody>,
+    S::Error: Error,
+    T: Tracer,
+    T::Span: Send + Sync,
+{
+    type Response = http::Response<GrpcBody<S::ResponseBody>>;
+    type Error = S::Error;
+    type Future = GrpcFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        let (name, mut attributes) = rpc_span(&request);
+        if let Some(host) = request.uri().host() {
+            attributes.push(NET_PEER_NAME.string(host.to_string()));
+        }
+        if let Some(port) = request.uri().port_u16() {
+            attributes.push(NET_PEER_PORT.i64(i64::from(port)));
+        }
+
+        let parent_cx = Context::current();
+        let span = self.tracer.build_with_context(
+            self.tracer
+                .span_builder(name)
+                .with_kind(api::SpanKind::Client)
+                .with_attributes(attributes),
+            &parent_cx,
+        );
+        let cx = parent_cx.with_span(span);
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, request.headers_mut())
+        });
+
+        let inner = {
+            let _guard = cx.clone().attach();
+            self.inner.call(request)
+        };
+
+        GrpcFuture { inner, cx }
+    }
+}
+
+/// Response future of the gRPC services, wraps the response body in a
+/// [`GrpcBody`].
+///
+/// [`GrpcBody`]: struct.GrpcBody.html
+#[pin_project]
+#[derive(Debug)]
+pub struct GrpcFuture<F> {
+    #[pin]
+    inner: F,
+    cx: Context,
+}
+
+impl<F, B, E> Future for GrpcFuture<F>
+where
+    F: Future<Output = Result<http::Response<B>, E>>,
+    E: Error,
+{
+    type Output = Result<http::Response<GrpcBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, task_cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.cx.clone().attach();
+        let result = futures::ready!(this.inner.poll(task_cx));
+
+        Poll::Ready(match result {
+            // trailers-only responses carry the status in the headers
+            Ok(response) if end_with_status(this.cx, response.headers()) => {
+                Ok(response.map(|inner| GrpcBody { inner, cx: None }))
+            }
+            Ok(response) => {
+                let cx = Some(this.cx.clone());
+                Ok(response.map(|inner| GrpcBody { inner, cx }))
+            }
+            Err(err) => {
+                let span = this.cx.span();
+                span.record_exception(&err);
+                span.set_status(api::StatusCode::Unavailable, err.to_string());
+                span.end();
+                Err(err)
+            }
+        })
+    }
+}
+
+/// A response body that ends the span with the `grpc-status` trailer, or as
+/// canceled if it is dropped before the trailers are received.
+#[pin_project(PinnedDrop)]
+#[derive(Debug)]
+pub struct GrpcBody<B> {
+    #[pin]
+    inner: B,
+    cx: Option<Context>,
+}
+
+impl<B: HttpBody> HttpBody for GrpcBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        task_cx: &mut TaskContext<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        self.project().inner.poll_data(task_cx)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        task_cx: &mut TaskContext<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+        let this = self.project();
+        let trailers = futures::ready!(this.inner.poll_trailers(task_cx));
+        if let Some(cx) = this.cx.take() {
+            let ended = match &trailers {
+                Ok(Some(trailers)) => end_with_status(&cx, trailers),
+                _ => false,
+            };
+            if !ended {
+                let span = cx.span();
+                span.set_status(api::StatusCode::Unknown, "missing grpc-status".to_string());
+                span.end();
+            }
+        }
+
+        Poll::Ready(trailers)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[pinned_drop]
+impl<B> PinnedDrop for GrpcBody<B> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(cx) = self.project().cx.take() {
+            let span = cx.span();
+            span.set_status(api::StatusCode::Canceled, String::new());
+            span.end();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{attributes, tracer};
+    use futures::executor::block_on;
+    use std::convert::Infallible;
+    use std::io;
+
+    /// An empty body with the given trailers.
+    #[derive(Debug)]
+    struct TestBody(Option<http::HeaderMap>);
+
+    impl HttpBody for TestBody {
+        type Data = io::Cursor<Vec<u8>>;
+        type Error = Infallible;
+
+        fn poll_data(
+            self: Pin<&mut Self>,
+            _cx: &mut TaskContext<'_>,
+        ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+            Poll::Ready(None)
+        }
+
+        fn poll_trailers(
+            mut self: Pin<&mut Self>,
+            _cx: &mut TaskContext<'_>,
+        ) -> Poll<Result<Option<http::HeaderMap>, Self::Error>> {
+            Poll::Ready(Ok(self.0.take()))
+        }
+    }
+
+    fn status(code: &str) -> http::HeaderMap {
+        let mut headers = http::HeaderMap::new();
+        headers.insert(GRPC_STATUS_HEADER, code.parse().unwrap());
+        headers
+    }
+
+    /// Responds with the `grpc-status` from the request's `x-status` header,
+    /// in the headers for `/trailers-only` and in the trailers otherwise.
+    #[derive(Clone, Debug)]
+    struct TestService;
+
+    impl Service<http::Request<()>> for TestService {
+        type Response = http::Response<BoxBody>;
+        type Error = io::Error;
+        type Future = futures::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<()>) -> Self::Future {
+            assert!(request.headers().contains_key("traceparent"));
+            let headers = status(request.headers()["x-status"].to_str().unwrap());
+            let mut response = http::Response::new(BoxBody::empty());
+            if request.uri().path().ends_with("/trailers-only") {
+                *response.headers_mut() = headers;
+            } else {
+                *response.body_mut() = BoxBody::map_from(TestBody(Some(headers)));
+            }
+            futures::future::ok(response)
+        }
+    }
+
+    #[rustfmt::skip]
+    fn status_code_data() -> Vec<(i32, api::StatusCode)> {
+        vec![
+            (0, api::StatusCode::OK),
+            (2, api::StatusCode::Unknown),
+            (5, api::StatusCode::NotFound),
+            (14, api::StatusCode::Unavailable),
+            (16, api::StatusCode::Unauthenticated),
+            (17, api::StatusCode::Unknown),
+        ]
+    }
+
+    #[test]
+    fn status_code_mapping() {
+        for (code, expected) in status_code_data() {
+            assert_eq!(status_code_from_grpc(code), expected, "{}", code);
+        }
+    }
+
+    #[test]
+    fn client_span() {
+        let (tracer, spans) = tracer();
+        let mut service = GrpcClientLayer::new(tracer).layer(TestService);
+
+        let request = http::Request::post("http://[::1]:50051/helloworld.Greeter/SayHello")
+            .header("x-status", "5")
+            .body(())
+            .unwrap();
+        let mut body = block_on(Service::call(&mut service, request))
+            .unwrap()
+            .into_body();
+        assert!(spans.lock().unwrap().is_empty());
+        block_on(body.trailers()).unwrap();
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name.as_str(), "helloworld.Greeter/SayHello");
+        assert_eq!(span.span_kind, api::SpanKind::Client);
+        assert_eq!(span.status_code, api::StatusCode::NotFound);
+
+        let attributes = attributes(span);
+        assert_eq!(attributes["rpc.system"], "grpc");
+        assert_eq!(attributes["rpc.service"], "helloworld.Greeter");
+        assert_eq!(attributes["rpc.method"], "SayHello");
+        assert_eq!(attributes["net.peer.name"], "[::1]");
+        assert_eq!(attributes["net.peer.port"], "50051");
+    }
+
+    #[test]
+    fn server_span() {
+        let (tracer, spans) = tracer();
+        let mut service = GrpcServerLayer::new(tracer).layer(TestService);
+
+        let request = http::Request::post("/helloworld.Greeter/trailers-only")
+            .header("x-status", "14")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .body(())
+            .unwrap();
+        block_on(Service::call(&mut service, request)).unwrap();
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name.as_str(), "helloworld.Greeter/trailers-only");
+        assert_eq!(span.span_kind, api::SpanKind::Server);
+        assert_eq!(
+            span.parent_span_id,
+            api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7)
+        );
+        assert_eq!(span.status_code, api::StatusCode::Unavailable);
+    }
+
+    #[test]
+    fn dropped_body_is_canceled() {
+        let (tracer, spans) = tracer();
+        let mut service = GrpcClientLayer::new(tracer).layer(TestService);
+
+        let request = http::Request::post("/helloworld.Greeter/SayHello")
+            .header("x-status", "0")
+            .body(())
+            .unwrap();
+        drop(block_on(Service::call(&mut service, request)).unwrap());
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans[0].status_code, api::StatusCode::Canceled);
+    }
+}
@@ -0,0 +1,231 @@
+//! # OpenTelemetry HTTP
+//!
+//! [`tower`] middleware that creates spans and propagates context for HTTP
+//! services, e.g. [`hyper`] servers and clients.
+//!
+//! ### Server
+//!
+//! [`ServerLayer`] extracts the remote context of every request with the
+//! global propagator and starts a `Server` span with the HTTP semantic
+//! convention attributes. The span and its context are active while the inner
+//! service handles the request.
+//!
+//! ```no_run
+//! use hyper::service::{make_service_fn, service_fn};
+//! use hyper::{Body, Request, Response, Server};
+//! use opentelemetry::global;
+//! use opentelemetry_http::ServerLayer;
+//! use std::convert::Infallible;
+//! use tower::ServiceBuilder;
+//!
+//! async fn handle(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
+//!     Ok(Response::new("Hello, World!".into()))
+//! }
+//!
+//! # async fn run() {
+//! let make_svc = make_service_fn(|_conn| async {
+//!     Ok::<_, Infallible>(
+//!         ServiceBuilder::new()
+//!             .layer(ServerLayer::new(global::tracer("example/server")))
+//!             .service(service_fn(handle)),
+//!     )
+//! });
+//!
+//! Server::bind(&([127, 0, 0, 1], 3000).into())
+//!     .serve(make_svc)
+//!     .await
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! ### Client
+//!
+//! [`ClientLayer`] starts a `Client` span as a child of the current context
+//! for every request, and injects it into the request headers with the global
+//! propagator. Transport errors are recorded as exceptions on the span.
+//!
+//! ```no_run
+//! use hyper::{Body, Client, Request};
+//! use opentelemetry::global;
+//! use opentelemetry_http::ClientLayer;
+//! use tower::{Service, ServiceBuilder};
+//!
+//! # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let mut client = ServiceBuilder::new()
+//!     .layer(ClientLayer::new(global::tracer("example/client")))
+//!     .service(Client::new());
+//!
+//! let request = Request::get("http://127.0.0.1:3000").body(Body::empty())?;
+//! let response = client.call(request).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ### gRPC
+//!
+//! With the `grpc` feature, [`GrpcServerLayer`] and [`GrpcClientLayer`] do
+//! the same for [`tonic`] servers and channels. Spans are named
+//! `package.Service/Method`, get the `rpc.*` attributes, and end with the
+//! status of the `grpc-status` trailer.
+//!
+//! [`tower`]: https://crates.io/crates/tower
+//! [`hyper`]: https://crates.io/crates/hyper
+//! [`ServerLayer`]: struct.ServerLayer.html
+//! [`ClientLayer`]: struct.ClientLayer.html
+//! [`GrpcServerLayer`]: struct.GrpcServerLayer.html
+//! [`GrpcClientLayer`]: struct.GrpcClientLayer.html
+//! [`tonic`]: https://crates.io/crates/tonic
+#![deny(missing_docs, unreachable_pub, missing_debug_implementations)]
+use opentelemetry::api::{self, Key, KeyValue};
+use opentelemetry_semantic_conventions::trace::{
+    HTTP_FLAVOR, HTTP_HOST, HTTP_METHOD, HTTP_SCHEME, HTTP_TARGET, HTTP_USER_AGENT,
+};
+
+mod client;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod server;
+
+pub use client::{ClientFuture, ClientLayer, ClientService};
+#[cfg(feature = "grpc")]
+pub use grpc::{
+    status_code_from_grpc, GrpcBody, GrpcClientLayer, GrpcClientService, GrpcFuture,
+    GrpcServerLayer, GrpcServerService,
+};
+pub use server::{ServerFuture, ServerLayer, ServerService};
+
+/// Maps an HTTP response status onto a span status.
+///
+/// See the [HTTP semantic conventions] for details of the mapping.
+///
+/// [HTTP semantic conventions]: https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/trace/semantic_conventions/http.md#status
+pub fn status_code_from_http(status: http::StatusCode) -> api::StatusCode {
+    match status.as_u16() {
+        100..=399 => api::StatusCode::OK,
+        401 => api::StatusCode::Unauthenticated,
+        403 => api::StatusCode::PermissionDenied,
+        404 => api::StatusCode::NotFound,
+        429 => api::StatusCode::ResourceExhausted,
+        400..=499 => api::StatusCode::InvalidArgument,
+        501 => api::StatusCode::Unimplemented,
+        503 => api::StatusCode::Unavailable,
+        504 => api::StatusCode::DeadlineExceeded,
+        500..=599 => api::StatusCode::Internal,
+        _ => api::StatusCode::Unknown,
+    }
+}
+
+/// Attributes shared by client and server spans.
+fn request_attributes<B>(request: &http::Request<B>) -> Vec<KeyValue> {
+    let mut attributes = vec![HTTP_METHOD.string(request.method().to_string())];
+
+    if let Some(target) = request.uri().path_and_query() {
+        attributes.push(HTTP_TARGET.string(target.to_string()));
+    }
+    if let Some(scheme) = request.uri().scheme_str() {
+        attributes.push(HTTP_SCHEME.string(scheme.to_string()));
+    }
+    if let Some(host) = header_str(request.headers(), http::header::HOST)
+        .map(str::to_string)
+        .or_else(|| request.uri().authority().map(ToString::to_string))
+    {
+        attributes.push(HTTP_HOST.string(host));
+    }
+    if let Some(flavor) = flavor(request.version()) {
+        attributes.push(HTTP_FLAVOR.string(flavor));
+    }
+    if let Some(user_agent) = header_str(request.headers(), http::header::USER_AGENT) {
+        attributes.push(HTTP_USER_AGENT.string(user_agent.to_string()));
+    }
+
+    attributes
+}
+
+/// The `content-length` header as an attribute with the given key.
+fn content_length(key: &Key, headers: &http::HeaderMap) -> Option<KeyValue> {
+    header_str(headers, http::header::CONTENT_LENGTH)
+        .and_then(|length| length.parse::<i64>().ok())
+        .map(|length| key.i64(length))
+}
+
+fn header_str<K: http::header::AsHeaderName>(headers: &http::HeaderMap, name: K) -> Option<&str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn flavor(version: http::Version) -> Option<&'static str> {
+    match version {
+        http::Version::HTTP_10 => Some("1.0"),
+        http::Version::HTTP_11 => Some("1.1"),
+        http::Version::HTTP_2 => Some("2"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use opentelemetry::api::Provider;
+    use opentelemetry::exporter::trace::{ExportResult, SpanData, SpanExporter};
+    use opentelemetry::sdk;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct TestExporter(Arc<Mutex<Vec<Arc<SpanData>>>>);
+
+    impl SpanExporter for TestExporter {
+        fn export(&self, batch: Vec<Arc<SpanData>>) -> ExportResult {
+            self.0.lock().unwrap().extend(batch);
+            ExportResult::Success
+        }
+    }
+
+    /// A tracer that samples all spans, and the spans it exported.
+    pub(crate) fn tracer() -> (sdk::Tracer, Arc<Mutex<Vec<Arc<SpanData>>>>) {
+        let exporter = TestExporter::default();
+        let spans = exporter.0.clone();
+        let provider = sdk::Provider::builder()
+            .with_simple_exporter(exporter)
+            .with_config(sdk::Config {
+                default_sampler: Box::new(sdk::Sampler::AlwaysOn),
+                ..Default::default()
+            })
+            .build();
+
+        (provider.get_tracer("test"), spans)
+    }
+
+    pub(crate) fn attributes(span: &SpanData) -> HashMap<String, String> {
+        span.attributes
+            .iter()
+            .map(|(key, value)| (key.as_str().to_string(), String::from(value)))
+            .collect()
+    }
+
+    #[rustfmt::skip]
+    fn status_code_data() -> Vec<(u16, api::StatusCode)> {
+        vec![
+            (200, api::StatusCode::OK),
+            (302, api::StatusCode::OK),
+            (400, api::StatusCode::InvalidArgument),
+            (401, api::StatusCode::Unauthenticated),
+            (403, api::StatusCode::PermissionDenied),
+            (404, api::StatusCode::NotFound),
+            (418, api::StatusCode::InvalidArgument),
+            (429, api::StatusCode::ResourceExhausted),
+            (500, api::StatusCode::Internal),
+            (501, api::StatusCode::Unimplemented),
+            (503, api::StatusCode::Unavailable),
+            (504, api::StatusCode::DeadlineExceeded),
+            (600, api::StatusCode::Unknown),
+        ]
+    }
+
+    #[test]
+    fn status_code_mapping() {
+        for (status, expected) in status_code_data() {
+            let status = http::StatusCode::from_u16(status).unwrap();
+            assert_eq!(status_code_from_http(status), expected, "{}", status);
+        }
+    }
+}
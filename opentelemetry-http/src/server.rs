@@ -0,0 +1,289 @@
+//! # Server Middleware
+//!
+//! Creates a `Server` span for every request handled by the inner service.
+use crate::{content_length, header_str, request_attributes, status_code_from_http};
+use opentelemetry::api::{self, Context, TraceContextExt, Tracer};
+use opentelemetry::global;
+use opentelemetry_semantic_conventions::trace::{
+    HTTP_CLIENT_IP, HTTP_REQUEST_CONTENT_LENGTH, HTTP_RESPONSE_CONTENT_LENGTH, HTTP_ROUTE,
+    HTTP_STATUS_CODE,
+};
+use pin_project::pin_project;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+type RouteFn = Arc<dyn Fn(&http::Uri) -> Option<String> + Send + Sync>;
+
+/// A [`Layer`] that wraps services in a [`ServerService`].
+///
+/// [`Layer`]: https://docs.rs/tower-layer/0.3/tower_layer/trait.Layer.html
+/// [`ServerService`]: struct.ServerService.html
+pub struct ServerLayer<T> {
+    tracer: Arc<T>,
+    route: Option<RouteFn>,
+}
+
+impl<T> ServerLayer<T> {
+    /// Create a layer that starts server spans with the given tracer.
+    pub fn new(tracer: T) -> Self {
+        ServerLayer {
+            tracer: Arc::new(tracer),
+            route: None,
+        }
+    }
+
+    /// Resolve the route template of a request, e.g. `/users/:id` for
+    /// `/users/42`.
+    ///
+    /// Spans are named `{method} {route}` and get an `http.route` attribute if
+    /// the route is known, or `HTTP {method}` otherwise, so that span names
+    /// stay low cardinality.
+    pub fn with_route<F>(mut self, route: F) -> Self
+    where
+        F: Fn(&http::Uri) -> Option<String> + Send + Sync + 'static,
+    {
+        self.route = Some(Arc::new(route));
+        self
+    }
+}
+
+impl<T> Clone for ServerLayer<T> {
+    fn clone(&self) -> Self {
+        ServerLayer {
+            tracer: self.tracer.clone(),
+            route: self.route.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ServerLayer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerLayer")
+            .field("tracer", &self.tracer)
+            .field("route", &self.route.is_some())
+            .finish()
+    }
+}
+
+impl<S, T> Layer<S> for ServerLayer<T> {
+    type Service = ServerService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ServerService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// A [`Service`] that starts a `Server` span for every request and makes it
+/// active while the inner service handles the request.
+///
+/// [`Service`]: https://docs.rs/tower-service/0.3/tower_service/trait.Service.html
+#[derive(Clone, Debug)]
+pub struct ServerService<S, T> {
+    inner: S,
+    layer: ServerLayer<T>,
+}
+
+impl<S, T, ReqBody, ResBody> Service<http::Request<ReqBody>> for ServerService<S, T>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: fmt::Display,
+    T: Tracer,
+    T::Span: Send + Sync,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ServerFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let parent_cx =
+            global::get_text_map_propagator(|propagator| propagator.extract(request.headers()));
+
+        let mut attributes = request_attributes(&request);
+        if let Some(client_ip) = header_str(request.headers(), "x-forwarded-for")
+            .and_then(|forwarded| forwarded.split(',').next())
+        {
+            attributes.push(HTTP_CLIENT_IP.string(client_ip.trim().to_string()));
+        }
+        if let Some(length) = content_length(&HTTP_REQUEST_CONTENT_LENGTH, request.headers()) {
+            attributes.push(length);
+        }
+
+        let route = self
+            .layer
+            .route
+            .as_ref()
+            .and_then(|route| route(request.uri()));
+        let name = match &route {
+            Some(route) => format!("{} {}", request.method(), route),
+            None => format!("HTTP {}", request.method()),
+        };
+        if let Some(route) = route {
+            attributes.push(HTTP_ROUTE.string(route));
+        }
+
+        let tracer = &self.layer.tracer;
+        let span = tracer.build_with_context(
+            tracer
+                .span_builder(name)
+                .with_kind(api::SpanKind::Server)
+                .with_attributes(attributes),
+            &parent_cx,
+        );
+        let cx = parent_cx.with_span(span);
+
+        let inner = {
+            let _guard = cx.clone().attach();
+            self.inner.call(request)
+        };
+
+        ServerFuture { inner, cx }
+    }
+}
+
+/// Response future of [`ServerService`], ends the span with the response
+/// status.
+///
+/// [`ServerService`]: struct.ServerService.html
+#[pin_project]
+#[derive(Debug)]
+pub struct ServerFuture<F> {
+    #[pin]
+    inner: F,
+    cx: Context,
+}
+
+impl<F, ResBody, E> Future for ServerFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: fmt::Display,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, task_cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = this.cx.clone().attach();
+        let result = futures::ready!(this.inner.poll(task_cx));
+
+        let span = this.cx.span();
+        match &result {
+            Ok(response) => {
+                span.set_attribute(HTTP_STATUS_CODE.i64(i64::from(response.status().as_u16())));
+                if let Some(length) =
+                    content_length(&HTTP_RESPONSE_CONTENT_LENGTH, response.headers())
+                {
+                    span.set_attribute(length);
+                }
+                span.set_status(status_code_from_http(response.status()), String::new());
+            }
+            Err(err) => span.set_status(api::StatusCode::Internal, err.to_string()),
+        }
+        span.end();
+
+        Poll::Ready(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{attributes, tracer};
+    use futures::executor::block_on;
+    use std::convert::Infallible;
+
+    /// Responds with the status in the path, e.g. `/404`, and the trace id
+    /// of the active span.
+    #[derive(Debug)]
+    struct StatusService;
+
+    impl Service<http::Request<()>> for StatusService {
+        type Response = http::Response<()>;
+        type Error = Infallible;
+        type Future = futures::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<()>) -> Self::Future {
+            let status = request.uri().path()[1..].parse().unwrap_or(200);
+            let trace_id = Context::current().span().span_context().trace_id();
+            let response = http::Response::builder()
+                .status(status)
+                .header("x-trace-id", format!("{:032x}", trace_id.to_u128()))
+                .body(())
+                .unwrap();
+            futures::future::ready(Ok(response))
+        }
+    }
+
+    #[test]
+    fn server_span() {
+        let (tracer, spans) = tracer();
+        let mut service = ServerLayer::new(tracer).layer(StatusService);
+
+        let request = http::Request::builder()
+            .uri("/404?q=1")
+            .header("host", "example.com")
+            .header("user-agent", "test")
+            .header("x-forwarded-for", "10.0.0.1, 10.0.0.2")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .body(())
+            .unwrap();
+        let response = block_on(service.call(request)).unwrap();
+        assert_eq!(
+            response.headers()["x-trace-id"],
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.name.as_str(), "HTTP GET");
+        assert_eq!(span.span_kind, api::SpanKind::Server);
+        assert_eq!(
+            span.parent_span_id,
+            api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7)
+        );
+        assert_eq!(span.status_code, api::StatusCode::NotFound);
+
+        let attributes = attributes(span);
+        assert_eq!(attributes["http.method"], "GET");
+        assert_eq!(attributes["http.target"], "/404?q=1");
+        assert_eq!(attributes["http.host"], "example.com");
+        assert_eq!(attributes["http.flavor"], "1.1");
+        assert_eq!(attributes["http.user_agent"], "test");
+        assert_eq!(attributes["http.client_ip"], "10.0.0.1");
+        assert_eq!(attributes["http.status_code"], "404");
+    }
+
+    #[test]
+    fn server_span_with_route() {
+        let (tracer, spans) = tracer();
+        let mut service = ServerLayer::new(tracer)
+            .with_route(|_uri| Some("/:status".to_string()))
+            .layer(StatusService);
+
+        let request = http::Request::post("/200").body(()).unwrap();
+        block_on(service.call(request)).unwrap();
+
+        let spans = spans.lock().unwrap();
+        assert_eq!(spans[0].name.as_str(), "POST /:status");
+        assert_eq!(spans[0].status_code, api::StatusCode::OK);
+        assert_eq!(attributes(&spans[0])["http.route"], "/:status");
+    }
+}
@@ -182,7 +182,10 @@ impl trace::SpanExporter for Exporter {
                 let jaeger_spans = batch.into_iter().map(Into::into).collect();
                 uploader.upload(jaeger::Batch::new(self.process.clone(), jaeger_spans))
             }
-            Err(_) => trace::ExportResult::FailedNotRetryable,
+            Err(err) => {
+                global::handle_error(api::TraceError::Other(err.to_string()));
+                trace::ExportResult::FailedNotRetryable
+            }
         }
     }
 }
@@ -390,15 +393,15 @@ impl Into<jaeger::Tag> for api::KeyValue {
     fn into(self) -> jaeger::Tag {
         let api::KeyValue { key, value } = self;
         match value {
-            api::Value::String(s) => jaeger::Tag::new(key.into(), jaeger::TagType::String, Some(s), None, None, None, None),
+            api::Value::String(s) => jaeger::Tag::new(key.into(), jaeger::TagType::String, Some(s.into()), None, None, None, None),
             api::Value::F64(f) => jaeger::Tag::new(key.into(), jaeger::TagType::Double, None, Some(f.into()), None, None, None),
             api::Value::Bool(b) => jaeger::Tag::new(key.into(), jaeger::TagType::Bool, None, None, Some(b), None, None),
             api::Value::I64(i) => jaeger::Tag::new(key.into(), jaeger::TagType::Long, None, None, None, Some(i), None),
             api::Value::Bytes(b) => jaeger::Tag::new(key.into(), jaeger::TagType::Binary, None, None, None, None, Some(b)),
             // TODO: better u64 handling, jaeger thrift only has i64 support
             api::Value::U64(u) => jaeger::Tag::new(key.into(), jaeger::TagType::String, Some(u.to_string()), None, None, None, None),
-            // TODO: better Array handling, jaeger thrift doesn't support arrays
-            v @ api::Value::Array(_) => jaeger::Tag::new(key.into(), jaeger::TagType::String, Some(v.into()), None, None, None, None),
+            // jaeger thrift doesn't support arrays or maps, encode them as JSON strings
+            v @ api::Value::Array(_) | v @ api::Value::Map(_) => jaeger::Tag::new(key.into(), jaeger::TagType::String, Some(v.into()), None, None, None, None),
         }
     }
 }
@@ -432,7 +435,7 @@ impl Into<jaeger::Span> for Arc<trace::SpanData> {
             trace_id_high,
             span_id: self.span_context.span_id().to_u64() as i64,
             parent_span_id: self.parent_span_id.to_u64() as i64,
-            operation_name: self.name.clone(),
+            operation_name: self.name.to_string(),
             references: links_to_references(&self.links),
             flags: self.span_context.trace_flags() as i32,
             start_time: self
@@ -2,7 +2,8 @@
 use crate::{agent, jaeger, thrift::agent::TAgentSyncClient};
 #[cfg(feature = "collector_client")]
 use crate::{collector, thrift::jaeger::TCollectorSyncClient};
-use opentelemetry::exporter::trace;
+use opentelemetry::exporter::{trace, ExportError};
+use opentelemetry::{api, global};
 
 /// Uploads a batch of spans to Jaeger
 #[derive(Debug)]
@@ -17,18 +18,40 @@ pub(crate) enum BatchUploader {
 impl BatchUploader {
     /// Emit a jaeger batch for the given uploader
     pub(crate) fn upload(&mut self, batch: jaeger::Batch) -> trace::ExportResult {
-        match self {
-            BatchUploader::Agent(client) => match client.emit_batch(batch) {
-                Ok(_) => trace::ExportResult::Success,
-                // TODO determine if the error is retryable
-                Err(_) => trace::ExportResult::FailedNotRetryable,
-            },
+        let result = match self {
+            BatchUploader::Agent(client) => client.emit_batch(batch).map_err(Error::Agent),
             #[cfg(feature = "collector_client")]
-            BatchUploader::Collector(collector) => match collector.submit_batches(vec![batch]) {
-                Ok(_) => trace::ExportResult::Success,
+            BatchUploader::Collector(collector) => collector
+                .submit_batches(vec![batch])
+                .map(|_| ())
+                .map_err(Error::Collector),
+        };
+
+        match result {
+            Ok(_) => trace::ExportResult::Success,
+            Err(err) => {
+                global::handle_error(api::TraceError::from(err));
                 // TODO determine if the error is retryable
-                Err(_) => trace::ExportResult::FailedNotRetryable,
-            },
+                trace::ExportResult::FailedNotRetryable
+            }
         }
     }
 }
+
+/// Errors returned when uploading spans to Jaeger
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    /// Failed to emit a batch to the agent
+    #[error("thrift agent failed with {0}")]
+    Agent(#[source] ::thrift::Error),
+    /// Failed to submit a batch to the collector
+    #[cfg(feature = "collector_client")]
+    #[error("thrift collector failed with {0}")]
+    Collector(#[source] ::thrift::Error),
+}
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "jaeger"
+    }
+}
@@ -0,0 +1,244 @@
+//! # OpenTelemetry Macros
+//!
+//! Attribute macros that wrap functions in OpenTelemetry spans, instead of
+//! wrapping every function body in `Tracer::in_span`.
+//!
+//! ```
+//! use opentelemetry_macros::instrument;
+//!
+//! #[instrument(fields(id), err)]
+//! fn load_user(id: u64, verbose: bool) -> Result<String, std::io::Error> {
+//!     Ok(format!("user {}", id))
+//! }
+//! ```
+//!
+//! See [`instrument`] for the supported arguments.
+//!
+//! [`instrument`]: attr.instrument.html
+#![deny(missing_docs, unreachable_pub, missing_debug_implementations)]
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{AttributeArgs, FnArg, Ident, ItemFn, Lit, LitStr, Meta, NestedMeta, Pat, ReturnType};
+
+/// Wraps a function in a span started with the global tracer.
+///
+/// The span is a child of the current context and is active while the
+/// function body runs. For an `async fn` the span stays active across every
+/// poll of the returned future, using [`FutureExt::with_context`]. As the body
+/// of an `async fn` only runs once the future is polled, its span is a child
+/// of the context that is current on the first poll. The span ends when the
+/// function returns.
+///
+/// # Arguments
+///
+/// - `name = "..."`: the span name, defaults to the function name
+/// - `tracer = "..."`: the name of the tracer, defaults to the name of the
+///   crate that defines the function
+/// - `fields(arg, ...)`: the function arguments to record as span attributes,
+///   formatted with `Debug`. Other arguments are skipped.
+/// - `err`: if the function returns a `Result`, set the span status to
+///   `Unknown` and record an `exception` event when it is an `Err`. The error
+///   type must implement `std::fmt::Display`, its message is recorded.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::api::{Context, Span, TraceContextExt};
+/// use opentelemetry_macros::instrument;
+///
+/// #[instrument(name = "fetch", tracer = "my-component", fields(url))]
+/// async fn fetch_page(url: &str, token: &str) -> usize {
+///     // the span is available from the current context
+///     Context::current().span().add_event("fetching".to_string(), vec![]);
+///     url.len()
+/// }
+///
+/// #[instrument(err)]
+/// fn parse_port(port: &str) -> Result<u16, std::num::ParseIntError> {
+///     Ok(port.parse()?)
+/// }
+/// ```
+///
+/// [`FutureExt::with_context`]: https://docs.rs/opentelemetry/0.8/opentelemetry/api/trace/futures/trait.FutureExt.html#method.with_context
+#[proc_macro_attribute]
+pub fn instrument(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(args as AttributeArgs);
+    let item = syn::parse_macro_input!(item as ItemFn);
+
+    match InstrumentArgs::parse(args).and_then(|args| expand(args, item)) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Default)]
+struct InstrumentArgs {
+    name: Option<LitStr>,
+    tracer: Option<LitStr>,
+    fields: Vec<Ident>,
+    err: bool,
+}
+
+impl InstrumentArgs {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut parsed = InstrumentArgs::default();
+        for arg in args {
+            match arg {
+                NestedMeta::Meta(Meta::NameValue(kv)) if kv.path.is_ident("name") => {
+                    parsed.name = Some(lit_str(kv.lit)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(kv)) if kv.path.is_ident("tracer") => {
+                    parsed.tracer = Some(lit_str(kv.lit)?);
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("fields") => {
+                    for field in list.nested {
+                        match field {
+                            NestedMeta::Meta(Meta::Path(path)) if path.get_ident().is_some() => {
+                                parsed.fields.push(path.get_ident().unwrap().clone());
+                            }
+                            other => {
+                                return Err(syn::Error::new(
+                                    other.span(),
+                                    "expected an argument name",
+                                ))
+                            }
+                        }
+                    }
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("err") => parsed.err = true,
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "unknown argument, expected `name`, `tracer`, `fields` or `err`",
+                    ))
+                }
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+fn lit_str(lit: Lit) -> syn::Result<LitStr> {
+    match lit {
+        Lit::Str(lit) => Ok(lit),
+        other => Err(syn::Error::new(other.span(), "expected a string literal")),
+    }
+}
+
+fn expand(args: InstrumentArgs, item: ItemFn) -> syn::Result<TokenStream2> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+
+    let arg_names: Vec<&Ident> = sig
+        .inputs
+        .iter()
+        .filter_map(|input| match input {
+            FnArg::Typed(pat_type) => match &*pat_type.pat {
+                Pat::Ident(pat_ident) => Some(&pat_ident.ident),
+                _ => None,
+            },
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+    for field in &args.fields {
+        if !arg_names.contains(&field) {
+            return Err(syn::Error::new(
+                field.span(),
+                format!("`{}` is not an argument of the function", field),
+            ));
+        }
+    }
+
+    let name = match args.name {
+        Some(name) => quote!(#name),
+        None => {
+            let name = sig.ident.to_string();
+            quote!(#name)
+        }
+    };
+    let tracer = match args.tracer {
+        Some(tracer) => quote!(#tracer),
+        None => quote!(env!("CARGO_PKG_NAME")),
+    };
+    let attributes = args.fields.iter().map(|field| {
+        let key = field.to_string();
+        quote!(::opentelemetry::api::KeyValue::new(#key, format!("{:?}", #field)))
+    });
+
+    let start = quote! {
+        let __otel_cx = {
+            use ::opentelemetry::api::{TraceContextExt as _, Tracer as _};
+            let tracer = ::opentelemetry::global::tracer(#tracer);
+            let span = tracer
+                .span_builder(#name)
+                .with_attributes(vec![#(#attributes),*])
+                .start(&tracer);
+            ::opentelemetry::api::Context::current_with_span(span)
+        };
+    };
+
+    // `impl Trait` types are not allowed in `let` bindings
+    let result_type = match &sig.output {
+        ReturnType::Type(_, ty) if !matches!(**ty, syn::Type::ImplTrait(_)) => quote!(: #ty),
+        ReturnType::Type(..) => quote!(),
+        ReturnType::Default => quote!(: ()),
+    };
+    let record_err = if args.err {
+        quote! {
+            if let Err(err) = &__otel_result {
+                use ::opentelemetry::api::{Span as _, TraceContextExt as _};
+                let span = __otel_cx.span();
+                let message = err.to_string();
+                span.add_event(
+                    "exception".to_string(),
+                    vec![::opentelemetry::api::KeyValue::new("exception.message", message.clone())],
+                );
+                span.set_status(::opentelemetry::api::StatusCode::Unknown, message);
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let body = match (sig.asyncness.is_some(), args.err) {
+        (true, _) => quote! {
+            #start
+            let __otel_result #result_type = ::opentelemetry::api::FutureExt::with_context(
+                async move #block,
+                __otel_cx.clone(),
+            )
+            .await;
+            #record_err
+            __otel_result
+        },
+        (false, true) => quote! {
+            #start
+            let __otel_guard = __otel_cx.clone().attach();
+            #[allow(clippy::redundant_closure_call)]
+            let __otel_result #result_type = (move || #block)();
+            #record_err
+            __otel_result
+        },
+        (false, false) => quote! {
+            #start
+            let __otel_guard = __otel_cx.attach();
+            #block
+        },
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            #body
+        }
+    })
+}
@@ -0,0 +1,176 @@
+use opentelemetry::api::{self, Context, FutureExt, Span, TraceContextExt, Tracer};
+use opentelemetry::exporter::trace::{ExportResult, SpanData, SpanExporter};
+use opentelemetry::{global, sdk};
+use opentelemetry_macros::instrument;
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct TestExporter(Arc<Mutex<Vec<Arc<SpanData>>>>);
+
+impl SpanExporter for TestExporter {
+    fn export(&self, batch: Vec<Arc<SpanData>>) -> ExportResult {
+        self.0.lock().unwrap().extend(batch);
+        ExportResult::Success
+    }
+}
+
+lazy_static::lazy_static! {
+    /// The spans exported by a global provider that samples all spans.
+    static ref SPANS: Arc<Mutex<Vec<Arc<SpanData>>>> = {
+        let exporter = TestExporter::default();
+        let spans = exporter.0.clone();
+        let provider = sdk::Provider::builder()
+            .with_simple_exporter(exporter)
+            .with_config(sdk::Config {
+                default_sampler: Box::new(sdk::Sampler::AlwaysOn),
+                ..Default::default()
+            })
+            .build();
+        global::set_provider(provider);
+        spans
+    };
+}
+
+/// Tests share the global provider, so every test uses its own span names.
+fn exported(name: &str) -> Arc<SpanData> {
+    SPANS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|span| span.name.as_str() == name)
+        .cloned()
+        .unwrap_or_else(|| panic!("span {} was not exported", name))
+}
+
+fn attributes(span: &SpanData) -> HashMap<String, String> {
+    span.attributes
+        .iter()
+        .map(|(key, value)| (key.as_str().to_string(), String::from(value)))
+        .collect()
+}
+
+#[instrument(fields(id, tags))]
+fn load_user(id: u64, tags: &[&str], token: &str) -> usize {
+    Context::current()
+        .span()
+        .add_event("loading".to_string(), vec![]);
+    id as usize + tags.len() + token.len()
+}
+
+#[instrument(name = "parse port", tracer = "ports", err)]
+fn parse_port(port: &str) -> Result<u16, std::num::ParseIntError> {
+    let port = port.parse()?;
+    Ok(port)
+}
+
+#[instrument(fields(path), err)]
+async fn read_file(path: &str) -> io::Result<String> {
+    futures::future::ready(()).await;
+    // still a child of the function span after the first poll
+    global::tracer("test").in_span("read_file inner", |_cx| {});
+    Err(io::Error::new(io::ErrorKind::NotFound, path.to_string()))
+}
+
+#[instrument(err)]
+fn load_config(path: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Err(format!("{} is not readable", path).into())
+}
+
+struct Repository;
+
+impl Repository {
+    #[instrument(fields(key))]
+    async fn get(&self, key: u32) -> Option<u32> {
+        Some(key)
+    }
+}
+
+#[test]
+fn sync_function() {
+    lazy_static::initialize(&SPANS);
+    assert_eq!(load_user(1, &["a", "b"], "secret"), 9);
+
+    let span = exported("load_user");
+    assert_eq!(span.status_code, api::StatusCode::OK);
+    assert_eq!(span.message_events.iter().next().unwrap().name, "loading");
+    let attributes = attributes(&span);
+    assert_eq!(attributes["id"], "1");
+    assert_eq!(attributes["tags"], r#"["a", "b"]"#);
+    assert!(!attributes.contains_key("token"));
+}
+
+#[test]
+fn sync_error() {
+    lazy_static::initialize(&SPANS);
+    assert_eq!(parse_port("8080"), Ok(8080));
+    assert!(parse_port("http").is_err());
+
+    let spans = SPANS.lock().unwrap();
+    let statuses = spans
+        .iter()
+        .filter(|span| span.name.as_str() == "parse port")
+        .map(|span| span.status_code.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        statuses,
+        vec![api::StatusCode::OK, api::StatusCode::Unknown]
+    );
+    let failed = spans
+        .iter()
+        .find(|span| {
+            span.name.as_str() == "parse port" && span.status_code == api::StatusCode::Unknown
+        })
+        .unwrap();
+    assert_eq!(failed.status_message, "invalid digit found in string");
+    assert_eq!(
+        failed.message_events.iter().next().unwrap().name,
+        "exception"
+    );
+}
+
+#[test]
+fn boxed_error() {
+    lazy_static::initialize(&SPANS);
+    assert!(load_config("/etc/app.toml").is_err());
+
+    let span = exported("load_config");
+    assert_eq!(span.status_code, api::StatusCode::Unknown);
+    assert_eq!(span.status_message, "/etc/app.toml is not readable");
+    let event = span.message_events.iter().next().unwrap();
+    assert_eq!(event.name, "exception");
+    assert_eq!(
+        String::from(&event.attributes[0].value),
+        "/etc/app.toml is not readable"
+    );
+}
+
+#[test]
+fn async_function() {
+    lazy_static::initialize(&SPANS);
+    let parent = global::tracer("test").start("read_file parent");
+    let parent_id = parent.span_context().span_id();
+    let cx = Context::current_with_span(parent);
+
+    let future = read_file("/etc/missing").with_context(cx);
+    assert!(futures::executor::block_on(future).is_err());
+
+    let span = exported("read_file");
+    assert_eq!(span.parent_span_id, parent_id);
+    assert_eq!(span.status_code, api::StatusCode::Unknown);
+    assert_eq!(span.status_message, "/etc/missing");
+    assert_eq!(attributes(&span)["path"], r#""/etc/missing""#);
+    assert_eq!(
+        exported("read_file inner").parent_span_id,
+        span.span_context.span_id()
+    );
+}
+
+#[test]
+fn async_method() {
+    lazy_static::initialize(&SPANS);
+    assert_eq!(futures::executor::block_on(Repository.get(7)), Some(7));
+
+    assert_eq!(attributes(&exported("get"))["key"], "7");
+}
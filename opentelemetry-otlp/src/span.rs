@@ -8,6 +8,8 @@ use grpcio::{
 };
 use opentelemetry::exporter::trace::ExportResult::{FailedNotRetryable, Success};
 use opentelemetry::exporter::trace::{ExportResult, SpanData, SpanExporter};
+use opentelemetry::exporter::ExportError;
+use opentelemetry::{api, global};
 use protobuf::RepeatedField;
 use std::collections::HashMap;
 use std::fmt;
@@ -151,10 +153,24 @@ impl SpanExporter for Exporter {
 
         match self.trace_exporter.export_opt(&request, call_options) {
             Ok(_) => Success,
-            Err(_) => FailedNotRetryable,
+            Err(err) => {
+                global::handle_error(api::TraceError::from(Error::from(err)));
+                FailedNotRetryable
+            }
         }
     }
 
     /// Unimplemented for now. Channel will shutdown on drop
     fn shutdown(&self) {}
 }
+
+/// Errors returned when exporting spans to the collector
+#[derive(thiserror::Error, Debug)]
+#[error("gRPC export failed: {0}")]
+struct Error(#[from] grpcio::Error);
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "otlp"
+    }
+}
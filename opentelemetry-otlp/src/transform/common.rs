@@ -1,5 +1,5 @@
-use crate::proto::common::{AnyValue, ArrayValue, KeyValue};
-use opentelemetry::api::Value;
+use crate::proto::common::{AnyValue, ArrayValue, KeyValue, KeyValueList};
+use opentelemetry::api::{Array, Value};
 use opentelemetry::sdk::EvictedHashMap;
 use protobuf::RepeatedField;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -45,14 +45,22 @@ impl From<Value> for AnyValue {
             Value::I64(val) => any_value.set_int_value(val),
             Value::U64(val) => any_value.set_int_value(val as i64),
             Value::F64(val) => any_value.set_double_value(val),
-            Value::String(val) => any_value.set_string_value(val),
+            Value::String(val) => any_value.set_string_value(val.into()),
             Value::Bytes(_val) => any_value.set_string_value("INVALID".to_string()),
-            Value::Array(vals) => any_value.set_array_value({
-                let mut array_value = ArrayValue::new();
-                array_value.set_values(RepeatedField::from_vec(
-                    vals.into_iter().map(AnyValue::from).collect(),
+            Value::Array(array) => any_value.set_array_value(array.into()),
+            Value::Map(map) => any_value.set_kvlist_value({
+                let mut kvlist_value = KeyValueList::new();
+                kvlist_value.set_values(RepeatedField::from_vec(
+                    map.into_iter()
+                        .map(|(key, value)| {
+                            let mut kv: KeyValue = KeyValue::new();
+                            kv.set_key(key.into());
+                            kv.set_value(value.into());
+                            kv
+                        })
+                        .collect(),
                 ));
-                array_value
+                kvlist_value
             }),
         };
 
@@ -60,6 +68,25 @@ impl From<Value> for AnyValue {
     }
 }
 
+impl From<Array> for ArrayValue {
+    fn from(array: Array) -> Self {
+        let values = match array {
+            Array::Bool(vals) => vals.into_iter().map(Value::Bool).map(Into::into).collect(),
+            Array::I64(vals) => vals.into_iter().map(Value::I64).map(Into::into).collect(),
+            Array::F64(vals) => vals.into_iter().map(Value::F64).map(Into::into).collect(),
+            Array::String(vals) => vals
+                .into_iter()
+                .map(Value::String)
+                .map(Into::into)
+                .collect(),
+        };
+
+        let mut array_value = ArrayValue::new();
+        array_value.set_values(RepeatedField::from_vec(values));
+        array_value
+    }
+}
+
 pub(crate) fn to_nanos(time: SystemTime) -> u64 {
     time.duration_since(UNIX_EPOCH)
         .unwrap_or_else(|_| Duration::from_secs(0))
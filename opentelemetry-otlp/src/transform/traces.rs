@@ -103,7 +103,7 @@ impl From<Arc<SpanData>> for ResourceSpans {
                                 vec![]
                             }
                         },
-                        name: source_span.name.clone(),
+                        name: source_span.name.to_string(),
                         kind: source_span.span_kind.clone().into(),
                         start_time_unix_nano: to_nanos(source_span.start_time),
                         end_time_unix_nano: to_nanos(source_span.end_time),
@@ -61,12 +61,12 @@ use opentelemetry::api::{
 };
 use opentelemetry::global;
 use opentelemetry::sdk::{
-    export::metrics::{CheckpointSet, ExportKind, Histogram, Record, Sum},
+    export::metrics::{CheckpointSet, Count, ExportKind, Histogram, Quantile, Record, Sum},
     metrics::{
-        aggregators::{HistogramAggregator, SumAggregator},
+        aggregators::{ExponentialHistogramAggregator, HistogramAggregator, SumAggregator},
         controllers,
         selectors::simple::Selector,
-        PullController,
+        PullController, View,
     },
     Resource,
 };
@@ -108,6 +108,13 @@ pub struct ExporterBuilder {
     /// Defines the default histogram bucket boundaries.
     default_histogram_boundaries: Option<Vec<f64>>,
 
+    /// Views customize the aggregation and export of the instruments they
+    /// match.
+    views: Vec<View>,
+
+    /// The maximum number of distinct label sets exported per instrument.
+    cardinality_limit: Option<usize>,
+
     /// The prometheus registry that will be used to register instruments.
     ///
     /// If not set a new empty `Registry` is created.
@@ -150,6 +157,26 @@ impl ExporterBuilder {
         }
     }
 
+    /// Set the views used to customize the exported instruments.
+    ///
+    /// Prometheus only exports sum and histogram aggregations, and exponential
+    /// histograms as summaries of the 0.5, 0.9, 0.99 and 1 quantiles.
+    /// Instruments that a view aggregates otherwise are not exported.
+    pub fn with_views(self, views: Vec<View>) -> Self {
+        ExporterBuilder { views, ..self }
+    }
+
+    /// Set the maximum number of distinct label sets exported per instrument.
+    ///
+    /// Past the limit, measurements with new label sets are exported in a
+    /// single series labeled `otel.metric.overflow="true"`.
+    pub fn with_cardinality_limit(self, limit: usize) -> Self {
+        ExporterBuilder {
+            cardinality_limit: Some(limit),
+            ..self
+        }
+    }
+
     /// Set the prometheus registry to be used by this exporter
     pub fn with_registry(self, registry: prometheus::Registry) -> Self {
         ExporterBuilder {
@@ -171,10 +198,14 @@ impl ExporterBuilder {
         let selector = Box::new(Selector::Histogram(default_histogram_boundaries.clone()));
         let mut controller_builder = controllers::pull(selector, Box::new(EXPORT_KIND))
             .with_cache_period(self.cache_period.unwrap_or(DEFAULT_CACHE_PERIOD))
-            .with_memory(true);
+            .with_memory(true)
+            .with_views(self.views);
         if let Some(resource) = self.resource {
             controller_builder = controller_builder.with_resource(resource);
         }
+        if let Some(limit) = self.cardinality_limit {
+            controller_builder = controller_builder.with_cardinality_limit(limit);
+        }
         let controller = controller_builder.build();
 
         global::set_meter_provider(controller.provider());
@@ -298,6 +329,11 @@ impl prometheus::core::Collector for Collector {
                     metrics.push(build_histogram(hist, number_kind, desc, label_values)?);
                 } else if let Some(sum) = agg.as_any().downcast_ref::<SumAggregator>() {
                     metrics.push(build_counter(sum, number_kind, desc, label_values)?);
+                } else if let Some(hist) = agg
+                    .as_any()
+                    .downcast_ref::<ExponentialHistogramAggregator>()
+                {
+                    metrics.push(build_summary(hist, number_kind, desc, label_values)?);
                 }
 
                 Ok(())
@@ -379,6 +415,43 @@ fn build_histogram(
     Ok(mf)
 }
 
+/// Exponential histograms are exported as summaries of these quantiles.
+const SUMMARY_QUANTILES: [f64; 4] = [0.5, 0.9, 0.99, 1.0];
+
+fn build_summary(
+    hist: &ExponentialHistogramAggregator,
+    kind: &NumberKind,
+    desc: prometheus::core::Desc,
+    labels: Vec<KeyValue>,
+) -> Result<prometheus::proto::MetricFamily, MetricsError> {
+    let mut s = prometheus::proto::Summary::default();
+    s.set_sample_sum(hist.sum()?.to_f64(kind));
+    s.set_sample_count(hist.count()?);
+
+    let mut quantiles = Vec::with_capacity(SUMMARY_QUANTILES.len());
+    for quantile in SUMMARY_QUANTILES.iter() {
+        let mut q = prometheus::proto::Quantile::default();
+        q.set_quantile(*quantile);
+        q.set_value(hist.quantile(*quantile)?.to_f64(kind));
+        quantiles.push(q);
+    }
+    s.set_quantile(protobuf::RepeatedField::from_vec(quantiles));
+
+    let mut m = prometheus::proto::Metric::default();
+    m.set_label(protobuf::RepeatedField::from_vec(
+        labels.into_iter().map(build_label_pair).collect(),
+    ));
+    m.set_summary(s);
+
+    let mut mf = prometheus::proto::MetricFamily::default();
+    mf.set_name(desc.fq_name);
+    mf.set_help(desc.help);
+    mf.set_field_type(prometheus::proto::MetricType::SUMMARY);
+    mf.set_metric(protobuf::RepeatedField::from_vec(vec![m]));
+
+    Ok(mf)
+}
+
 fn build_label_pair(label: KeyValue) -> prometheus::proto::LabelPair {
     let mut lp = prometheus::proto::LabelPair::new();
     lp.set_name(label.key.into());
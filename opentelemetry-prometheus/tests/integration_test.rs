@@ -1,5 +1,8 @@
-use opentelemetry::api::{metrics::MeterProvider, KeyValue};
-use opentelemetry::sdk::Resource;
+use opentelemetry::api::{metrics::MeterProvider, Key, KeyValue};
+use opentelemetry::sdk::{
+    metrics::{Aggregation, View},
+    Resource,
+};
 use opentelemetry_prometheus::PrometheusExporter;
 use prometheus::{Encoder, TextEncoder};
 
@@ -38,6 +41,112 @@ fn test_add() {
     compare_export(&exporter, expected)
 }
 
+#[test]
+fn test_views() {
+    let exporter = opentelemetry_prometheus::exporter()
+        .with_views(vec![
+            View::new("http.server.duration")
+                .with_name("request_duration")
+                .with_aggregation(Aggregation::Histogram(vec![0.1, 1.0]))
+                .with_label_keys(vec![Key::new("method")]),
+            View::new("requests").with_label_keys(vec![]),
+        ])
+        .init();
+
+    let meter = exporter.provider().unwrap().meter("test");
+
+    let duration = meter.f64_value_recorder("http.server.duration").init();
+    let requests = meter.u64_counter("requests").init();
+
+    duration.record(
+        0.05,
+        &[KeyValue::new("method", "GET"), KeyValue::new("path", "/a")],
+    );
+    duration.record(
+        0.5,
+        &[KeyValue::new("method", "GET"), KeyValue::new("path", "/b")],
+    );
+    duration.record(2.0, &[KeyValue::new("method", "POST")]);
+    requests.add(1, &[KeyValue::new("path", "/a")]);
+    requests.add(2, &[KeyValue::new("path", "/b")]);
+
+    let expected = vec![
+        "request_duration_bucket{method=\"GET\",le=\"+Inf\"} 2",
+        "request_duration_bucket{method=\"GET\",le=\"0.1\"} 1",
+        "request_duration_bucket{method=\"GET\",le=\"1\"} 2",
+        "request_duration_count{method=\"GET\"} 2",
+        "request_duration_sum{method=\"GET\"} 0.55",
+        "request_duration_bucket{method=\"POST\",le=\"+Inf\"} 1",
+        "request_duration_bucket{method=\"POST\",le=\"0.1\"} 0",
+        "request_duration_bucket{method=\"POST\",le=\"1\"} 0",
+        "request_duration_count{method=\"POST\"} 1",
+        "request_duration_sum{method=\"POST\"} 2",
+        "requests 3",
+    ];
+
+    compare_export(&exporter, expected)
+}
+
+#[test]
+fn test_exponential_histogram() {
+    let exporter = opentelemetry_prometheus::exporter()
+        .with_views(vec![
+            View::new("latency").with_aggregation(Aggregation::ExponentialHistogram(160))
+        ])
+        .init();
+
+    let meter = exporter.provider().unwrap().meter("test");
+    let latency = meter.u64_value_recorder("latency").init();
+    for value in 1..=100 {
+        latency.record(value, &[]);
+    }
+
+    // quantiles are estimated from the bucket midpoints, rounded for integers
+    let expected = vec![
+        "latency{quantile=\"0.5\"} 50",
+        "latency{quantile=\"0.9\"} 93",
+        "latency{quantile=\"0.99\"} 100",
+        "latency{quantile=\"1\"} 100",
+        "latency_count 100",
+        "latency_sum 5050",
+    ];
+
+    compare_export(&exporter, expected)
+}
+
+#[test]
+fn test_cardinality_limit() {
+    let exporter = opentelemetry_prometheus::exporter()
+        .with_cardinality_limit(2)
+        .init();
+
+    let meter = exporter.provider().unwrap().meter("test");
+
+    let counter = meter.u64_counter("logins").init();
+    let _observer = meter
+        .u64_sum_observer("sessions", |result| {
+            for user in &["a", "b", "c", "d"] {
+                result.observe(1, &[KeyValue::new("user", *user)]);
+            }
+        })
+        .init();
+
+    for (user, logins) in &[("a", 1), ("b", 2), ("c", 3), ("a", 4), ("d", 5)] {
+        counter.add(*logins, &[KeyValue::new("user", *user)]);
+    }
+
+    let expected = vec![
+        "logins{user=\"a\"} 5",
+        "logins{user=\"b\"} 2",
+        "logins{otel.metric.overflow=\"true\"} 8",
+        "sessions{user=\"a\"} 1",
+        "sessions{user=\"b\"} 1",
+        "sessions{otel.metric.overflow=\"true\"} 2",
+    ];
+
+    compare_export(&exporter, expected)
+}
+
 fn compare_export(exporter: &PrometheusExporter, mut expected: Vec<&'static str>) {
     let mut output = Vec::new();
     let encoder = TextEncoder::new();
@@ -158,6 +158,11 @@ pub const HOST_ID: Key = Key::from_static_str("host.id");
 /// or a name specified by the user.
 pub const HOST_NAME: Key = Key::from_static_str("host.name");
 
+/// The CPU architecture the host system is running on.
+///
+/// Example values are amd64, arm32, arm64, ppc32, ppc64, x86.
+pub const HOST_ARCH: Key = Key::from_static_str("host.arch");
+
 /// Name of the VM image or OS install the host was instantiated from.
 pub const HOST_IMAGE_NAME: Key = Key::from_static_str("host.image.name");
 
@@ -184,6 +189,9 @@ pub const K8S_POD_NAME: Key = Key::from_static_str("k8s.pod.name");
 /// The name of the Container in a Pod template.
 pub const K8S_CONTAINER_NAME: Key = Key::from_static_str("k8s.container.name");
 
+/// The name of the Node the Pod is running on.
+pub const K8S_NODE_NAME: Key = Key::from_static_str("k8s.node.name");
+
 /// The uid of the ReplicaSet.
 pub const K8S_REPLICASET_UID: Key = Key::from_static_str("k8s.replicaset.uid");
 
@@ -254,3 +262,15 @@ pub const PROCESS_COMMAND_LINE: Key = Key::from_static_str("process.command_line
 
 /// The username of the user that owns the process.
 pub const PROCESS_OWNER: Key = Key::from_static_str("process.owner");
+
+/// The name of the runtime of this process. For compiled native binaries, this
+/// SHOULD be the name of the compiler.
+pub const PROCESS_RUNTIME_NAME: Key = Key::from_static_str("process.runtime.name");
+
+/// The version of the runtime of this process, as returned by the runtime
+/// without modification.
+pub const PROCESS_RUNTIME_VERSION: Key = Key::from_static_str("process.runtime.version");
+
+/// An additional description about the runtime of the process, for example a
+/// specific vendor customization of the runtime environment.
+pub const PROCESS_RUNTIME_DESCRIPTION: Key = Key::from_static_str("process.runtime.description");
@@ -236,7 +236,7 @@ fn into_zipkin_span(config: &ExporterConfig, span_data: Arc<trace::SpanData>) ->
             "{:016x}",
             span_data.span_context.span_id().to_u64()
         ))
-        .name(span_data.name.clone())
+        .name(span_data.name.to_string())
         .kind(if user_defined_span_kind {
             None
         } else {
@@ -275,7 +275,36 @@ where
 {
     let mut map: HashMap<String, String> = HashMap::new();
     for kv in kvs {
-        map.insert(kv.key.into(), kv.value.into());
+        let value = match kv.value {
+            // zipkin tags are strings, encode arrays and maps as JSON
+            v @ api::Value::Array(_) | v @ api::Value::Map(_) => into_json(v).to_string(),
+            v => v.into(),
+        };
+        map.insert(kv.key.into(), value);
     }
     map
 }
+
+fn into_json(value: api::Value) -> serde_json::Value {
+    match value {
+        api::Value::Bool(b) => b.into(),
+        api::Value::I64(i) => i.into(),
+        api::Value::U64(u) => u.into(),
+        api::Value::F64(f) => f.into(),
+        api::Value::String(s) => String::from(s).into(),
+        v @ api::Value::Bytes(_) => String::from(v).into(),
+        api::Value::Array(api::Array::Bool(vals)) => vals.into(),
+        api::Value::Array(api::Array::I64(vals)) => vals.into(),
+        api::Value::Array(api::Array::F64(vals)) => vals.into(),
+        api::Value::Array(api::Array::String(vals)) => vals
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .into(),
+        api::Value::Map(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k.into(), into_json(v)))
+                .collect(),
+        ),
+    }
+}
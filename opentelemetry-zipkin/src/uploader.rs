@@ -1,6 +1,7 @@
 //! # Zipkin Span Exporter
 use crate::model::span::ListOfSpans;
-use opentelemetry::exporter::trace;
+use opentelemetry::exporter::{trace, ExportError};
+use opentelemetry::{api, global};
 
 /// Default v2 HTTP Zipkin API route for recording spans
 static API_V2_COLLECTOR_ROUTE: &str = "/api/v2/spans";
@@ -36,7 +37,10 @@ impl Uploader {
     fn upload_http(&self, spans: ListOfSpans) -> trace::ExportResult {
         let zipkin_span_json = match serde_json::to_string(&spans) {
             Ok(json) => json,
-            Err(_) => return trace::ExportResult::FailedNotRetryable,
+            Err(err) => {
+                global::handle_error(api::TraceError::from(Error::Serialization(err)));
+                return trace::ExportResult::FailedNotRetryable;
+            }
         };
 
         let resp = self
@@ -46,12 +50,35 @@ impl Uploader {
             .body(zipkin_span_json)
             .send();
 
-        if let Ok(response) = resp {
-            if response.status().is_success() {
+        let err = match resp {
+            Ok(response) if response.status().is_success() => {
                 return trace::ExportResult::Success;
             }
-        }
+            Ok(response) => Error::Status(response.status()),
+            Err(err) => Error::Request(err),
+        };
+        global::handle_error(api::TraceError::from(err));
 
         trace::ExportResult::FailedRetryable
     }
 }
+
+/// Errors returned when uploading spans to the Zipkin collector
+#[derive(thiserror::Error, Debug)]
+pub(crate) enum Error {
+    /// Spans could not be serialized to JSON
+    #[error("failed to serialize spans: {0}")]
+    Serialization(#[source] serde_json::Error),
+    /// The request to the collector failed
+    #[error("request to the collector failed: {0}")]
+    Request(#[source] reqwest::Error),
+    /// The collector responded with a non-success status code
+    #[error("collector responded with status {0}")]
+    Status(reqwest::StatusCode),
+}
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "zipkin"
+    }
+}
@@ -4,3 +4,5 @@ set -eu
 
 cargo test --all "$@"
 cargo test --all "$@" --features="default serialize base64_format binary_propagator"
+cargo test --all "$@" --features="opentelemetry-http/grpc"
+cargo test --all "$@" --features="opentelemetry-contrib/log"
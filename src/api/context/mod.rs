@@ -24,8 +24,18 @@
 //! values of the context, a snapshot can be created via the [`Context::current`]
 //! method.
 //!
+//! ## Threads
+//!
+//! The current context is thread-local. Use [`thread::spawn`] or
+//! [`thread::Builder`] to start threads that inherit it, and the [`wrap`]
+//! methods to run closures and iterators on other threads with a given
+//! context.
+//!
 //! [`Context`]: struct.Context.html
 //! [`Context::current`]: struct.Context.html#method.current
+//! [`thread::spawn`]: thread/fn.spawn.html
+//! [`thread::Builder`]: thread/struct.Builder.html
+//! [`wrap`]: struct.Context.html#method.wrap
 //! [`ContextGuard`]: struct.ContextGuard.html
 //! [`get`]: struct.Context.html#method.get
 //! [`with_value`]: struct.Context.html#method.with_value
@@ -72,6 +82,7 @@ use std::sync::Arc;
 
 #[cfg(feature = "trace")]
 pub mod propagation;
+pub mod thread;
 
 thread_local! {
     static CURRENT_CONTEXT: RefCell<Context> = RefCell::new(Context::default());
@@ -295,6 +306,79 @@ impl Context {
             .ok();
         ContextGuard(prior)
     }
+
+    /// Wraps a closure so that it runs with this context attached, e.g. on
+    /// another thread or in a thread pool.
+    ///
+    /// The prior context of the thread that runs the closure is restored when
+    /// the closure returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::api::Context;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct ValueA(&'static str);
+    ///
+    /// let _guard = Context::new().with_value(ValueA("a")).attach();
+    ///
+    /// let handle = std::thread::spawn(Context::current().wrap(|| {
+    ///     // the context is available on the new thread
+    ///     assert_eq!(Context::current().get::<ValueA>(), Some(&ValueA("a")));
+    /// }));
+    /// handle.join().unwrap();
+    /// ```
+    pub fn wrap<F, T>(self, f: F) -> impl FnOnce() -> T
+    where
+        F: FnOnce() -> T,
+    {
+        move || {
+            let _guard = self.attach();
+            f()
+        }
+    }
+
+    /// Wraps a closure that is called for many items, e.g. by a parallel
+    /// iterator, so that every call runs with this context attached.
+    ///
+    /// The prior context of the thread that runs the closure is restored after
+    /// every call.
+    pub fn wrap_fn<F, A, T>(self, f: F) -> impl Fn(A) -> T
+    where
+        F: Fn(A) -> T,
+    {
+        move |arg| {
+            let _guard = self.clone().attach();
+            f(arg)
+        }
+    }
+
+    /// Wraps an iterator so that this context is attached while every item
+    /// is produced, e.g. by lazy adapters that are driven on another thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::api::Context;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct ValueA(&'static str);
+    ///
+    /// let cx = Context::new().with_value(ValueA("a"));
+    /// let values = cx
+    ///     .wrap_iter((0..2).map(|_| Context::current().get::<ValueA>().is_some()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(values, vec![true, true]);
+    /// assert_eq!(Context::current().get::<ValueA>(), None);
+    /// ```
+    pub fn wrap_iter<I: Iterator>(self, iter: I) -> ContextIter<I> {
+        ContextIter {
+            inner: iter,
+            cx: self,
+        }
+    }
 }
 
 impl fmt::Debug for Context {
@@ -317,6 +401,30 @@ impl Drop for ContextGuard {
     }
 }
 
+/// An iterator that attaches a context while it produces every item.
+///
+/// Created with [`Context::wrap_iter`].
+///
+/// [`Context::wrap_iter`]: struct.Context.html#method.wrap_iter
+#[derive(Clone, Debug)]
+pub struct ContextIter<I> {
+    inner: I,
+    cx: Context,
+}
+
+impl<I: Iterator> Iterator for ContextIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let _guard = self.cx.clone().attach();
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
 /// Executes a closure with a reference to this thread's current context.
 ///
 /// Note: This function will panic if you attempt to attach another context
@@ -379,4 +487,22 @@ mod tests {
         assert_eq!(current.get(), Some(&ValueA("a")));
         assert_eq!(current.get::<ValueB>(), None);
     }
+
+    #[test]
+    fn wrapped_closures() {
+        #[derive(Debug, PartialEq)]
+        struct ValueA(&'static str);
+        let cx = Context::new().with_value(ValueA("a"));
+
+        let f = cx
+            .clone()
+            .wrap(|| Context::current().get::<ValueA>().is_some());
+        assert!(f());
+        assert_eq!(Context::current().get::<ValueA>(), None);
+
+        let f = cx.wrap_fn(|i: usize| (i, Context::current().get::<ValueA>().is_some()));
+        let handle = std::thread::spawn(move || vec![f(1), f(2)]);
+        assert_eq!(handle.join().unwrap(), vec![(1, true), (2, true)]);
+        assert_eq!(Context::current().get::<ValueA>(), None);
+    }
 }
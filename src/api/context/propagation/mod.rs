@@ -159,6 +159,7 @@
 //!
 use crate::api;
 use std::collections::HashMap;
+use thiserror::Error;
 
 pub mod composite_propagator;
 pub mod text_propagator;
@@ -173,6 +174,16 @@ pub trait Injector {
 pub trait Extractor {
     /// Get a value from a key from the underlying data.
     fn get(&self, key: &str) -> Option<&str>;
+
+    /// Collect all the keys from the underlying data.
+    ///
+    /// Propagators that read fields by prefix rather than by name, such as
+    /// the Jaeger `uberctx-` baggage headers, need extractors to override
+    /// this. The default returns no keys, so those propagators find no
+    /// fields.
+    fn keys(&self) -> Vec<&str> {
+        Vec::new()
+    }
 }
 
 impl<S: std::hash::BuildHasher> api::Injector for HashMap<String, String, S> {
@@ -187,6 +198,67 @@ impl<S: std::hash::BuildHasher> api::Extractor for HashMap<String, String, S> {
     fn get(&self, key: &str) -> Option<&str> {
         self.get(&key.to_lowercase()).map(|v| v.as_str())
     }
+
+    /// Collect all the keys from the HashMap.
+    fn keys(&self) -> Vec<&str> {
+        self.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// Error when injecting or extracting context with a propagator.
+#[derive(Error, Debug)]
+#[error("Cannot {ops} using {propagator_name}: {message}")]
+pub struct PropagationError {
+    message: String,
+    // which propagator does this error comes from
+    propagator_name: &'static str,
+    // are we extracting or injecting information across cross boundary
+    ops: &'static str,
+    // propagator names that have no registered factory
+    unknown_propagators: Vec<String>,
+}
+
+impl PropagationError {
+    /// Error happens when extracting information
+    pub fn extract<T: Into<String>>(message: T, propagator_name: &'static str) -> Self {
+        PropagationError {
+            message: message.into(),
+            propagator_name,
+            ops: "extract",
+            unknown_propagators: Vec::new(),
+        }
+    }
+
+    /// Error happens when injecting information
+    pub fn inject<T: Into<String>>(message: T, propagator_name: &'static str) -> Self {
+        PropagationError {
+            message: message.into(),
+            propagator_name,
+            ops: "inject",
+            unknown_propagators: Vec::new(),
+        }
+    }
+
+    /// Error happens when building a propagator from names without a
+    /// registered factory
+    pub fn unknown(names: Vec<String>) -> Self {
+        PropagationError {
+            message: format!("unknown propagators {}", names.join(", ")),
+            propagator_name: "text map propagator builder",
+            ops: "build",
+            unknown_propagators: names,
+        }
+    }
+
+    /// The name of the propagator that returned the error
+    pub fn propagator_name(&self) -> &'static str {
+        self.propagator_name
+    }
+
+    /// The propagator names that have no registered factory, if any
+    pub fn unknown_propagators(&self) -> &[String] {
+        &self.unknown_propagators
+    }
 }
 
 #[cfg(feature = "http")]
@@ -207,6 +279,11 @@ impl api::Extractor for http::HeaderMap {
     fn get(&self, key: &str) -> Option<&str> {
         self.get(key).and_then(|value| value.to_str().ok())
     }
+
+    /// Collect all the keys from the HeaderMap.
+    fn keys(&self) -> Vec<&str> {
+        self.keys().map(|name| name.as_str()).collect()
+    }
 }
 
 #[cfg(feature = "tonic")]
@@ -227,6 +304,16 @@ impl api::Extractor for tonic::metadata::MetadataMap {
     fn get(&self, key: &str) -> Option<&str> {
         self.get(key).and_then(|metadata| metadata.to_str().ok())
     }
+
+    /// Collect all the keys from the MetadataMap.
+    fn keys(&self) -> Vec<&str> {
+        self.keys()
+            .map(|key| match key {
+                tonic::metadata::KeyRef::Ascii(key) => key.as_str(),
+                tonic::metadata::KeyRef::Binary(key) => key.as_str(),
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -243,7 +330,8 @@ mod tests {
             Extractor::get(&carrier, "HEADERNAME"),
             Some("value"),
             "case insensitive extraction"
-        )
+        );
+        assert_eq!(Extractor::keys(&carrier), vec!["headername"]);
     }
 
     #[test]
@@ -256,7 +344,8 @@ mod tests {
             Extractor::get(&carrier, "HEADERNAME"),
             Some("value"),
             "case insensitive extraction"
-        )
+        );
+        assert_eq!(Extractor::keys(&carrier), vec!["headername"]);
     }
 
     #[test]
@@ -269,6 +358,7 @@ mod tests {
             Extractor::get(&carrier, "HEADERNAME"),
             Some("value"),
             "case insensitive extraction"
-        )
+        );
+        assert_eq!(Extractor::keys(&carrier), vec!["headername"]);
     }
 }
@@ -0,0 +1,103 @@
+//! # Threads
+//!
+//! The current [`Context`] is thread-local, so threads started with
+//! `std::thread` begin with an empty context. [`spawn`] and [`Builder`]
+//! start threads that run with the context of the spawning thread attached.
+//!
+//! [`Context`]: ../struct.Context.html
+//! [`spawn`]: fn.spawn.html
+//! [`Builder`]: struct.Builder.html
+use crate::api::Context;
+use std::io;
+use std::thread::{self, JoinHandle};
+
+/// Spawns a new thread with the current context attached, see
+/// `std::thread::spawn`.
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::api::{context::thread, Context};
+///
+/// #[derive(Debug, PartialEq)]
+/// struct ValueA(&'static str);
+///
+/// let _guard = Context::new().with_value(ValueA("a")).attach();
+///
+/// let handle = thread::spawn(|| {
+///     // the context is available on the new thread
+///     assert_eq!(Context::current().get::<ValueA>(), Some(&ValueA("a")));
+/// });
+/// handle.join().unwrap();
+/// ```
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    thread::spawn(Context::current().wrap(f))
+}
+
+/// Thread factory that attaches the current context to the threads it
+/// spawns, see `std::thread::Builder`.
+#[derive(Debug)]
+pub struct Builder(thread::Builder);
+
+impl Builder {
+    /// Create a builder with the default configuration.
+    pub fn new() -> Self {
+        Builder(thread::Builder::new())
+    }
+
+    /// Names the thread-to-be.
+    pub fn name(self, name: String) -> Self {
+        Builder(self.0.name(name))
+    }
+
+    /// Sets the size of the stack, in bytes, of the new thread.
+    pub fn stack_size(self, size: usize) -> Self {
+        Builder(self.0.stack_size(size))
+    }
+
+    /// Spawns a new thread with the context of the calling thread attached.
+    pub fn spawn<F, T>(self, f: F) -> io::Result<JoinHandle<T>>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        self.0.spawn(Context::current().wrap(f))
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct ValueA(&'static str);
+
+    #[test]
+    fn spawn_with_current_context() {
+        let _guard = Context::new().with_value(ValueA("a")).attach();
+
+        let handle = spawn(|| Context::current().get::<ValueA>().is_some());
+        assert!(handle.join().unwrap());
+
+        let handle = Builder::new()
+            .name("worker".to_string())
+            .spawn(|| {
+                (
+                    thread::current().name().map(str::to_string),
+                    Context::current().get::<ValueA>().is_some(),
+                )
+            })
+            .unwrap();
+        assert_eq!(handle.join().unwrap(), (Some("worker".to_string()), true));
+    }
+}
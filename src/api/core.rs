@@ -1,7 +1,13 @@
 //! OpenTelemetry shared core date types
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
 
 /// Key used for metric `LabelSet`s and trace `Span` attributes.
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
@@ -51,8 +57,10 @@ impl Key {
         }
     }
 
-    /// Create a `KeyValue` pair for `String` values.
-    pub fn string<T: Into<String>>(&self, value: T) -> KeyValue {
+    /// Create a `KeyValue` pair for string values.
+    ///
+    /// Static and shared strings are stored without copying.
+    pub fn string<T: Into<StringValue>>(&self, value: T) -> KeyValue {
         KeyValue {
             key: self.clone(),
             value: Value::String(value.into()),
@@ -68,13 +76,21 @@ impl Key {
     }
 
     /// Create a `KeyValue` pair for arrays.
-    pub fn array<T: Into<Vec<Value>>>(&self, value: T) -> KeyValue {
+    pub fn array<T: Into<Array>>(&self, value: T) -> KeyValue {
         KeyValue {
             key: self.clone(),
             value: Value::Array(value.into()),
         }
     }
 
+    /// Create a `KeyValue` pair for nested key/value maps.
+    pub fn map<T: Into<BTreeMap<Key, Value>>>(&self, value: T) -> KeyValue {
+        KeyValue {
+            key: self.clone(),
+            value: Value::Map(value.into()),
+        }
+    }
+
     /// Returns a reference to the underlying key name
     pub fn as_str(&self) -> &str {
         self.0.as_ref()
@@ -102,6 +118,309 @@ impl From<Key> for String {
     }
 }
 
+/// An immutable string that avoids copying static and shared strings.
+///
+/// `&'static str`, `Cow<'static, str>` and `Arc<str>` values are stored as is,
+/// so setting the same constant attribute value or span name repeatedly does
+/// not allocate. Strings borrowed for a shorter lifetime are converted from a
+/// `String` or a `&String`, which is copied.
+#[derive(Clone)]
+pub struct StringValue(StringInner);
+
+#[derive(Clone)]
+enum StringInner {
+    Owned(String),
+    Static(&'static str),
+    Shared(Arc<str>),
+}
+
+impl StringValue {
+    /// Create a new `StringValue` from a `&'static str` without allocating.
+    pub const fn from_static_str(value: &'static str) -> Self {
+        StringValue(StringInner::Static(value))
+    }
+
+    /// Returns a reference to the underlying string.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            StringInner::Owned(s) => s.as_str(),
+            StringInner::Static(s) => s,
+            StringInner::Shared(s) => s.as_ref(),
+        }
+    }
+}
+
+impl Default for StringValue {
+    fn default() -> Self {
+        StringValue::from_static_str("")
+    }
+}
+
+impl Deref for StringValue {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for StringValue {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for StringValue {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for StringValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for StringValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for StringValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StringValue {}
+
+impl PartialEq<str> for StringValue {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for StringValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for StringValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StringValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for StringValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl From<&'static str> for StringValue {
+    fn from(value: &'static str) -> Self {
+        StringValue(StringInner::Static(value))
+    }
+}
+
+impl From<&String> for StringValue {
+    fn from(value: &String) -> Self {
+        StringValue(StringInner::Owned(value.clone()))
+    }
+}
+
+impl From<String> for StringValue {
+    fn from(value: String) -> Self {
+        StringValue(StringInner::Owned(value))
+    }
+}
+
+impl From<Arc<str>> for StringValue {
+    fn from(value: Arc<str>) -> Self {
+        StringValue(StringInner::Shared(value))
+    }
+}
+
+impl From<Cow<'static, str>> for StringValue {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(s) => StringValue(StringInner::Static(s)),
+            Cow::Owned(s) => StringValue(StringInner::Owned(s)),
+        }
+    }
+}
+
+impl From<StringValue> for String {
+    fn from(value: StringValue) -> Self {
+        match value.0 {
+            StringInner::Owned(s) => s,
+            StringInner::Static(s) => s.to_string(),
+            StringInner::Shared(s) => s.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl Serialize for StringValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<'de> Deserialize<'de> for StringValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Into::into)
+    }
+}
+
+/// A homogeneous array of primitive values.
+///
+/// Arrays can only be built from a `Vec` of a single primitive type, so mixed
+/// type arrays cannot be represented.
+#[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Array {
+    /// Array of bools
+    Bool(Vec<bool>),
+    /// Array of integers
+    I64(Vec<i64>),
+    /// Array of floats
+    F64(Vec<f64>),
+    /// Array of strings
+    String(Vec<StringValue>),
+}
+
+impl Array {
+    /// Returns the number of elements in the array.
+    pub fn len(&self) -> usize {
+        match self {
+            Array::Bool(values) => values.len(),
+            Array::I64(values) => values.len(),
+            Array::F64(values) => values.len(),
+            Array::String(values) => values.len(),
+        }
+    }
+
+    /// Returns `true` if the array contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for Array {
+    /// Formats the array as a JSON array.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Array::Bool(values) => display_array(f, values.iter()),
+            Array::I64(values) => display_array(f, values.iter()),
+            Array::F64(values) => display_array(f, values.iter()),
+            Array::String(values) => display_array(f, values.iter().map(|v| JsonStr(v))),
+        }
+    }
+}
+
+fn display_array<T: fmt::Display, I: Iterator<Item = T>>(
+    f: &mut fmt::Formatter<'_>,
+    values: I,
+) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, value) in values.enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    write!(f, "]")
+}
+
+/// Formats a string as a quoted and escaped JSON string.
+struct JsonStr<'a>(&'a str);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        write!(f, "\"")
+    }
+}
+
+macro_rules! from_arrays {
+   (
+        $(
+            ($t:ty, $val:expr);
+        )+
+    ) => {
+        $(
+            impl From<$t> for Array {
+                fn from(t: $t) -> Self {
+                    $val(t)
+                }
+            }
+
+            impl From<$t> for Value {
+                fn from(t: $t) -> Self {
+                    Value::Array($val(t))
+                }
+            }
+        )+
+    }
+}
+
+from_arrays!(
+    (Vec<bool>, Array::Bool);
+    (Vec<i64>, Array::I64);
+    (Vec<f64>, Array::F64);
+    (Vec<StringValue>, Array::String);
+);
+
+macro_rules! from_string_arrays {
+   (
+        $(
+            $t:ty;
+        )+
+    ) => {
+        $(
+            impl From<Vec<$t>> for Array {
+                fn from(values: Vec<$t>) -> Self {
+                    Array::String(values.into_iter().map(Into::into).collect())
+                }
+            }
+
+            impl From<Vec<$t>> for Value {
+                fn from(values: Vec<$t>) -> Self {
+                    Value::Array(values.into())
+                }
+            }
+        )+
+    }
+}
+
+from_string_arrays!(
+    &'static str;
+    String;
+    Arc<str>;
+);
+
 /// Value types for use in `KeyValue` pairs.
 #[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
 #[derive(Clone, Debug, PartialEq)]
@@ -115,11 +434,13 @@ pub enum Value {
     /// f64 values
     F64(f64),
     /// String values
-    String(String),
+    String(StringValue),
     /// Byte array values
     Bytes(Vec<u8>),
     /// Array of homogeneous values
-    Array(Vec<Value>),
+    Array(Array),
+    /// Nested key/value pairs, ordered by key
+    Map(BTreeMap<Key, Value>),
 }
 
 macro_rules! from_values {
@@ -143,15 +464,46 @@ from_values!(
     (i64, Value::I64);
     (u64, Value::U64);
     (f64, Value::F64);
-    (String, Value::String);
+    (StringValue, Value::String);
     (Vec<u8>, Value::Bytes);
-    (Vec<Value>, Value::Array);
+    (Array, Value::Array);
+    (BTreeMap<Key, Value>, Value::Map);
 );
 
-impl From<&str> for Value {
-    /// Convenience method for creating a `Value` from a `&str`.
-    fn from(value_str: &str) -> Self {
-        Value::String(value_str.to_string())
+impl From<&'static str> for Value {
+    /// Convenience method for creating a `Value` from a `&'static str`
+    /// without allocating.
+    fn from(value_str: &'static str) -> Self {
+        Value::String(value_str.into())
+    }
+}
+
+impl From<&String> for Value {
+    /// Convenience method for creating a `Value` from a `&String`.
+    fn from(value: &String) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl From<String> for Value {
+    /// Convenience method for creating a `Value` from a `String`.
+    fn from(value: String) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl From<Arc<str>> for Value {
+    /// Convenience method for creating a `Value` from a shared `Arc<str>`
+    /// without copying.
+    fn from(value: Arc<str>) -> Self {
+        Value::String(value.into())
+    }
+}
+
+impl From<Vec<KeyValue>> for Value {
+    /// Convenience method for creating a `Value::Map` from `KeyValue` pairs.
+    fn from(kvs: Vec<KeyValue>) -> Self {
+        Value::Map(kvs.into_iter().map(|kv| (kv.key, kv.value)).collect())
     }
 }
 
@@ -160,13 +512,9 @@ impl From<Value> for String {
     /// `String` values.
     fn from(value: Value) -> Self {
         match value {
-            Value::Bool(value) => value.to_string(),
-            Value::I64(value) => value.to_string(),
-            Value::U64(value) => value.to_string(),
-            Value::F64(value) => value.to_string(),
-            Value::String(value) => value,
+            Value::String(value) => value.into(),
             Value::Bytes(value) => String::from_utf8(value).unwrap_or_else(|_| String::new()),
-            Value::Array(value) => format_value_array_as_string(&value),
+            value => String::from(&value),
         }
     }
 }
@@ -174,28 +522,36 @@ impl From<Value> for String {
 impl From<&Value> for String {
     /// Convert `&Value` types to `String` for use by exporters that only use
     /// `String` values.
+    ///
+    /// Arrays and maps are formatted as JSON.
     fn from(value: &Value) -> Self {
         match value {
             Value::Bool(value) => value.to_string(),
             Value::I64(value) => value.to_string(),
             Value::U64(value) => value.to_string(),
             Value::F64(value) => value.to_string(),
-            Value::String(value) => value.clone(),
+            Value::String(value) => value.to_string(),
             Value::Bytes(value) => {
                 String::from_utf8(value.clone()).unwrap_or_else(|_| String::new())
             }
-            Value::Array(value) => format_value_array_as_string(value),
+            Value::Array(value) => value.to_string(),
+            Value::Map(value) => format_value_map_as_string(value),
         }
     }
 }
 
-fn format_value_array_as_string(v: &[Value]) -> String {
+fn format_value_map_as_string(map: &BTreeMap<Key, Value>) -> String {
     format!(
-        "[{}]",
-        v.iter()
-            .map(|elem| match elem {
-                v @ Value::String(_) | v @ Value::Bytes(_) => format!(r#""{}""#, String::from(v)),
-                v => String::from(v),
+        "{{{}}}",
+        map.iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    v @ Value::String(_) | v @ Value::Bytes(_) => {
+                        JsonStr(&String::from(v)).to_string()
+                    }
+                    v => String::from(v),
+                };
+                format!("{}:{}", JsonStr(key.as_str()), value)
             })
             .collect::<Vec<_>>()
             .join(",")
@@ -248,3 +604,48 @@ impl AsRef<str> for Unit {
         self.0.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn static_and_shared_strings_are_not_copied() {
+        const GET: &str = "GET";
+        match Value::from(GET) {
+            Value::String(s) => assert_eq!(s.as_str().as_ptr(), GET.as_ptr()),
+            other => panic!("unexpected value {:?}", other),
+        }
+
+        let shared: Arc<str> = Arc::from("postgresql");
+        let value = Key::new("db.system").string(shared.clone()).value;
+        match value {
+            Value::String(s) => assert_eq!(s.as_str().as_ptr(), shared.as_ptr()),
+            other => panic!("unexpected value {:?}", other),
+        }
+        assert_eq!(StringValue::from("a"), StringValue::from("a".to_string()));
+
+        let path = String::from("/users/42");
+        assert_eq!(Value::from(&path), Value::from(path.clone()));
+    }
+
+    #[test]
+    fn arrays_and_maps_format_as_json() {
+        let value: Value = vec!["a", "quoted \"b\""].into();
+        assert_eq!(String::from(value), r#"["a","quoted \"b\""]"#);
+
+        let value: Value = vec![1.5, 2.0].into();
+        assert_eq!(String::from(value), "[1.5,2]");
+
+        let value: Value = vec![
+            KeyValue::new("b", vec![true, false]),
+            KeyValue::new("a", "str"),
+            KeyValue::new("c", Value::from(vec![KeyValue::new("d", 1i64)])),
+        ]
+        .into();
+        assert_eq!(
+            String::from(value),
+            r#"{"a":"str","b":[true,false],"c":{"d":1}}"#
+        );
+    }
+}
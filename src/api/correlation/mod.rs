@@ -3,12 +3,13 @@
 //! A Correlation Context is used to annotate telemetry, adding context and
 //! information to metrics, traces, and logs. It is an abstract data type
 //! represented by a set of name/value pairs describing user-defined properties.
-//! Each name in a [`CorrelationContext`] is associated with exactly one value.
-//! `CorrelationContext`s are serialized according to the editor's draft of
-//! the [W3C Correlation Context] specification.
+//! Each name in a [`CorrelationContext`] is associated with exactly one value
+//! and optional [`EntryMetadata`]. `CorrelationContext`s are serialized
+//! according to the [W3C Baggage] specification.
 //!
 //! [`CorrelationContext`]: struct.CorrelationContext.html
-//! [W3C Correlation Context]: https://w3c.github.io/correlation-context/
+//! [`EntryMetadata`]: struct.EntryMetadata.html
+//! [W3C Baggage]: https://w3c.github.io/baggage/
 //!
 //! # Examples
 //!
@@ -20,14 +21,14 @@
 //!
 //! // Example correlation value passed in externally via http headers
 //! let mut headers = HashMap::new();
-//! headers.insert("otcorrelations".to_string(), "user_id=1".to_string());
+//! headers.insert("baggage".to_string(), "user_id=1".to_string());
 //!
 //! let propagator = CorrelationContextPropagator::new();
 //! // can extract from any type that impls `Extractor`, usually an HTTP header map
 //! let cx = propagator.extract(&headers);
 //!
-//! // Iterate over extracted name / value pairs
-//! for (name, value) in cx.correlation_context() {
+//! // Iterate over extracted name / value pairs and their metadata
+//! for (name, (value, metadata)) in cx.correlation_context() {
 //!   // ...
 //! }
 //!
@@ -37,7 +38,7 @@
 //! // Inject correlations into http request
 //! propagator.inject_context(&cx_with_additions, &mut headers);
 //!
-//! let header_value = headers.get("otcorrelations").expect("header is injected");
+//! let header_value = headers.get("baggage").expect("header is injected");
 //! assert!(header_value.contains("user_id=1"), "still contains previous name / value");
 //! assert!(header_value.contains("server_id=42"), "contains new name / value pair");
 //! ```
@@ -53,7 +54,7 @@ pub use propagation::{CorrelationContextExt, CorrelationContextPropagator};
 /// A set of name/value pairs describing user-defined properties across systems.
 #[derive(Debug, Default)]
 pub struct CorrelationContext {
-    inner: HashMap<api::Key, api::Value>,
+    inner: HashMap<api::Key, (api::Value, EntryMetadata)>,
 }
 
 impl CorrelationContext {
@@ -74,9 +75,31 @@ impl CorrelationContext {
     /// let mut cc = CorrelationContext::new();
     /// let _ = cc.insert("my-name", "my-value");
     ///
-    /// assert_eq!(cc.get("my-name"), Some(&Value::String("my-value".to_string())))
+    /// assert_eq!(cc.get("my-name"), Some(&Value::String("my-value".into())))
     /// ```
     pub fn get<T: Into<api::Key>>(&self, key: T) -> Option<&api::Value> {
+        self.inner.get(&key.into()).map(|(value, _metadata)| value)
+    }
+
+    /// Returns a reference to the value and metadata associated with a given name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::api::{CorrelationContext, EntryMetadata, Value};
+    ///
+    /// let mut cc = CorrelationContext::new();
+    /// let _ = cc.insert_with_metadata("my-name", "my-value", "test");
+    ///
+    /// assert_eq!(
+    ///     cc.get_with_metadata("my-name"),
+    ///     Some(&(Value::String("my-value".into()), EntryMetadata::from("test"))),
+    /// )
+    /// ```
+    pub fn get_with_metadata<T: Into<api::Key>>(
+        &self,
+        key: T,
+    ) -> Option<&(api::Value, EntryMetadata)> {
         self.inner.get(&key.into())
     }
 
@@ -93,19 +116,54 @@ impl CorrelationContext {
     /// let mut cc = CorrelationContext::new();
     /// let _ = cc.insert("my-name", "my-value");
     ///
-    /// assert_eq!(cc.get("my-name"), Some(&Value::String("my-value".to_string())))
+    /// assert_eq!(cc.get("my-name"), Some(&Value::String("my-value".into())))
     /// ```
     pub fn insert<K, V>(&mut self, key: K, value: V) -> Option<api::Value>
     where
         K: Into<api::Key>,
         V: Into<api::Value>,
     {
-        self.inner.insert(key.into(), value.into())
+        self.insert_with_metadata(key, value, EntryMetadata::default())
+            .map(|(value, _metadata)| value)
     }
 
-    /// Removes a name from the correlation context, returning the value
-    /// corresponding to the name if the pair was previously in the map.
-    pub fn remove<K: Into<api::Key>>(&mut self, key: K) -> Option<api::Value> {
+    /// Inserts a name-value pair and its metadata into the correlation context.
+    ///
+    /// If the name was not present, [`None`] is returned. If the name was present,
+    /// the value and metadata are updated, and the old value and metadata are
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::api::{CorrelationContext, EntryMetadata, Value};
+    ///
+    /// let mut cc = CorrelationContext::new();
+    /// let _ = cc.insert_with_metadata("my-name", "my-value", "test");
+    ///
+    /// assert_eq!(
+    ///     cc.get_with_metadata("my-name"),
+    ///     Some(&(Value::String("my-value".into()), EntryMetadata::from("test"))),
+    /// )
+    /// ```
+    pub fn insert_with_metadata<K, V, M>(
+        &mut self,
+        key: K,
+        value: V,
+        metadata: M,
+    ) -> Option<(api::Value, EntryMetadata)>
+    where
+        K: Into<api::Key>,
+        V: Into<api::Value>,
+        M: Into<EntryMetadata>,
+    {
+        self.inner
+            .insert(key.into(), (value.into(), metadata.into()))
+    }
+
+    /// Removes a name from the correlation context, returning the value and
+    /// metadata corresponding to the name if the pair was previously in the map.
+    pub fn remove<K: Into<api::Key>>(&mut self, key: K) -> Option<(api::Value, EntryMetadata)> {
         self.inner.remove(&key.into())
     }
 
@@ -119,7 +177,7 @@ impl CorrelationContext {
         self.inner.is_empty()
     }
 
-    /// Gets an iterator over the correlation context items, sorted by name.
+    /// Gets an iterator over the correlation context items and their metadata.
     pub fn iter(&self) -> Iter {
         self.into_iter()
     }
@@ -127,9 +185,9 @@ impl CorrelationContext {
 
 /// An iterator over the entries of a `CorrelationContext`.
 #[derive(Debug)]
-pub struct Iter<'a>(hash_map::Iter<'a, api::Key, api::Value>);
+pub struct Iter<'a>(hash_map::Iter<'a, api::Key, (api::Value, EntryMetadata)>);
 impl<'a> Iterator for Iter<'a> {
-    type Item = (&'a api::Key, &'a api::Value);
+    type Item = (&'a api::Key, &'a (api::Value, EntryMetadata));
 
     fn next(&mut self) -> Option<Self::Item> {
         self.0.next()
@@ -137,7 +195,7 @@ impl<'a> Iterator for Iter<'a> {
 }
 
 impl<'a> IntoIterator for &'a CorrelationContext {
-    type Item = (&'a api::Key, &'a api::Value);
+    type Item = (&'a api::Key, &'a (api::Value, EntryMetadata));
     type IntoIter = Iter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
@@ -145,8 +203,8 @@ impl<'a> IntoIterator for &'a CorrelationContext {
     }
 }
 
-impl FromIterator<(api::Key, api::Value)> for CorrelationContext {
-    fn from_iter<I: IntoIterator<Item = (api::Key, api::Value)>>(iter: I) -> Self {
+impl FromIterator<(api::Key, (api::Value, EntryMetadata))> for CorrelationContext {
+    fn from_iter<I: IntoIterator<Item = (api::Key, (api::Value, EntryMetadata))>>(iter: I) -> Self {
         CorrelationContext {
             inner: iter.into_iter().collect(),
         }
@@ -155,8 +213,82 @@ impl FromIterator<(api::Key, api::Value)> for CorrelationContext {
 
 impl FromIterator<api::KeyValue> for CorrelationContext {
     fn from_iter<I: IntoIterator<Item = api::KeyValue>>(iter: I) -> Self {
+        iter.into_iter().map(KeyValueMetadata::from).collect()
+    }
+}
+
+impl FromIterator<KeyValueMetadata> for CorrelationContext {
+    fn from_iter<I: IntoIterator<Item = KeyValueMetadata>>(iter: I) -> Self {
         CorrelationContext {
-            inner: iter.into_iter().map(|kv| (kv.key, kv.value)).collect(),
+            inner: iter
+                .into_iter()
+                .map(|kvm| (kvm.key, (kvm.value, kvm.metadata)))
+                .collect(),
+        }
+    }
+}
+
+/// Opaque properties attached to a correlation context entry.
+///
+/// Metadata is propagated as the `;`-separated properties that follow an
+/// entry's value, e.g. `prop1=val1;prop2` in `key=value;prop1=val1;prop2`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EntryMetadata(String);
+
+impl EntryMetadata {
+    /// Returns the metadata as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<String> for EntryMetadata {
+    fn from(metadata: String) -> Self {
+        EntryMetadata(metadata.trim().to_string())
+    }
+}
+
+impl From<&str> for EntryMetadata {
+    fn from(metadata: &str) -> Self {
+        EntryMetadata(metadata.trim().to_string())
+    }
+}
+
+/// A name/value pair with its [`EntryMetadata`].
+///
+/// [`EntryMetadata`]: struct.EntryMetadata.html
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyValueMetadata {
+    /// Dimension or event key
+    pub key: api::Key,
+    /// Dimension or event value
+    pub value: api::Value,
+    /// Metadata associated with this pair
+    pub metadata: EntryMetadata,
+}
+
+impl KeyValueMetadata {
+    /// Create a new `KeyValueMetadata` pair.
+    pub fn new<K, V, M>(key: K, value: V, metadata: M) -> Self
+    where
+        K: Into<api::Key>,
+        V: Into<api::Value>,
+        M: Into<EntryMetadata>,
+    {
+        KeyValueMetadata {
+            key: key.into(),
+            value: value.into(),
+            metadata: metadata.into(),
+        }
+    }
+}
+
+impl From<api::KeyValue> for KeyValueMetadata {
+    fn from(kv: api::KeyValue) -> Self {
+        KeyValueMetadata {
+            key: kv.key,
+            value: kv.value,
+            metadata: EntryMetadata::default(),
         }
     }
 }
@@ -1,20 +1,37 @@
-use super::CorrelationContext;
+use super::{CorrelationContext, KeyValueMetadata};
 use crate::api::context::propagation::text_propagator::FieldIter;
-use crate::api::{self, Context, KeyValue};
+use crate::api::{self, Context};
 use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
 use std::iter;
 
-static CORRELATION_CONTEXT_HEADER: &str = "otcorrelations";
-const FRAGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b';').add(b',').add(b'=');
+static BAGGAGE_HEADER: &str = "baggage";
+const FRAGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b';')
+    .add(b',')
+    .add(b'=')
+    .add(b'%')
+    .add(b'\\');
+
+// Limits from https://w3c.github.io/baggage/#limits
+const MAX_KEY_VALUE_PAIRS: usize = 180;
+const MAX_BYTES_FOR_ONE_PAIR: usize = 4096;
+const MAX_LEN_OF_ALL_PAIRS: usize = 8192;
 
 lazy_static::lazy_static! {
     static ref DEFAULT_CORRELATION_CONTEXT: CorrelationContext = CorrelationContext::default();
-    static ref CORRELATION_CONTEXT_FIELDS: [String; 1] = [CORRELATION_CONTEXT_HEADER.to_string()];
+    static ref BAGGAGE_FIELDS: [String; 1] = [BAGGAGE_HEADER.to_string()];
 }
 
-/// Propagates name/value pairs in [W3C Correlation Context] format.
+/// Propagates name/value pairs and their metadata in [W3C Baggage] format.
+///
+/// Entries that are invalid or would exceed the [W3C Baggage limits] of
+/// 180 entries, 4096 bytes per entry or 8192 bytes in total are skipped on
+/// both inject and extract.
 ///
-/// [W3C Correlation Context]: https://w3c.github.io/correlation-context/
+/// [W3C Baggage]: https://w3c.github.io/baggage/
+/// [W3C Baggage limits]: https://w3c.github.io/baggage/#limits
 #[derive(Debug, Default)]
 pub struct CorrelationContextPropagator {
     _private: (),
@@ -27,59 +44,123 @@ impl CorrelationContextPropagator {
     }
 }
 
+/// Tracks the W3C Baggage limits while entries are accepted.
+#[derive(Default)]
+struct Limits {
+    entries: usize,
+    total_len: usize,
+}
+
+impl Limits {
+    /// Returns `true` if an entry of `len` bytes fits in the remaining budget,
+    /// and accounts for it.
+    fn accept(&mut self, len: usize) -> bool {
+        // entries after the first are preceded by a `,`
+        let total_len = self.total_len + len + if self.entries > 0 { 1 } else { 0 };
+        if len > MAX_BYTES_FOR_ONE_PAIR
+            || self.entries >= MAX_KEY_VALUE_PAIRS
+            || total_len > MAX_LEN_OF_ALL_PAIRS
+        {
+            return false;
+        }
+        self.entries += 1;
+        self.total_len = total_len;
+        true
+    }
+}
+
+/// Percent-encodes the keys and values of the `;` separated metadata
+/// properties, so they decode to the same properties on extract.
+fn encode_metadata(metadata: &str) -> String {
+    metadata
+        .split(';')
+        .map(str::trim)
+        .filter(|prop| !prop.is_empty())
+        .map(|prop| {
+            let mut iter = prop.splitn(2, '=');
+            let key = utf8_percent_encode(iter.next().unwrap_or_default().trim(), FRAGMENT);
+            match iter.next() {
+                Some(value) => key
+                    .chain(iter::once("="))
+                    .chain(utf8_percent_encode(value.trim(), FRAGMENT))
+                    .collect(),
+                None => key.collect(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
 impl api::TextMapFormat for CorrelationContextPropagator {
     /// Encodes the values of the `Context` and injects them into the provided `Injector`.
     fn inject_context(&self, cx: &Context, injector: &mut dyn api::Injector) {
         let correlation_cx = cx.correlation_context();
         if !correlation_cx.is_empty() {
+            let mut limits = Limits::default();
             let header_value = correlation_cx
                 .iter()
-                .map(|(name, value)| {
-                    utf8_percent_encode(name.as_str().trim(), FRAGMENT)
+                .filter_map(|(name, (value, metadata))| {
+                    let name = name.as_str().trim();
+                    if name.is_empty() {
+                        return None;
+                    }
+                    let metadata = encode_metadata(metadata.as_str());
+                    let entry: String = utf8_percent_encode(name, FRAGMENT)
                         .chain(iter::once("="))
                         .chain(utf8_percent_encode(String::from(value).trim(), FRAGMENT))
-                        .collect()
+                        .chain(iter::once(if metadata.is_empty() { "" } else { ";" }))
+                        .chain(iter::once(metadata.as_str()))
+                        .collect();
+                    if limits.accept(entry.len()) {
+                        Some(entry)
+                    } else {
+                        None
+                    }
                 })
                 .collect::<Vec<String>>()
                 .join(",");
-            injector.set(CORRELATION_CONTEXT_HEADER, header_value);
+            if !header_value.is_empty() {
+                injector.set(BAGGAGE_HEADER, header_value);
+            }
         }
     }
 
     /// Extracts a `Context` with correlation context values from a `Extractor`.
     fn extract_with_context(&self, cx: &Context, extractor: &dyn api::Extractor) -> Context {
-        if let Some(header_value) = extractor.get(CORRELATION_CONTEXT_HEADER) {
-            let correlations = header_value.split(',').flat_map(|context_value| {
-                if let Some((name_and_value, props)) = context_value
-                    .split(';')
-                    .collect::<Vec<&str>>()
-                    .split_first()
-                {
-                    let mut iter = name_and_value.split('=');
-                    if let (Some(name), Some(value)) = (iter.next(), iter.next()) {
-                        let name = percent_decode_str(name).decode_utf8().map_err(|_| ())?;
-                        let value = percent_decode_str(value).decode_utf8().map_err(|_| ())?;
-
-                        // TODO: handle props from https://w3c.github.io/correlation-context/
-                        // for now just append to value
-                        let decoded_props = props
-                            .iter()
-                            .flat_map(|prop| percent_decode_str(prop).decode_utf8())
-                            .map(|prop| format!(";{}", prop.as_ref().trim()))
-                            .collect::<String>();
-
-                        Ok(KeyValue::new(
-                            name.trim().to_owned(),
-                            value.trim().to_string() + decoded_props.as_str(),
-                        ))
-                    } else {
-                        // Invalid name / value format
-                        Err(())
-                    }
-                } else {
-                    // Invalid correlation context value format
-                    Err(())
+        if let Some(header_value) = extractor.get(BAGGAGE_HEADER) {
+            let mut limits = Limits::default();
+            let correlations = header_value.split(',').flat_map(|entry| {
+                let entry = entry.trim();
+                let (name_and_value, props) = match entry.find(';') {
+                    Some(idx) => (&entry[..idx], &entry[idx + 1..]),
+                    None => (entry, ""),
+                };
+                let mut iter = name_and_value.splitn(2, '=');
+                let (name, value) = match (iter.next(), iter.next()) {
+                    (Some(name), Some(value)) => (name, value),
+                    // Invalid name / value format
+                    _ => return None,
+                };
+                let name = percent_decode_str(name).decode_utf8().ok()?;
+                let value = percent_decode_str(value).decode_utf8().ok()?;
+                let name = name.trim();
+                if name.is_empty() || !limits.accept(entry.len()) {
+                    return None;
                 }
+
+                let metadata = props
+                    .split(';')
+                    .flat_map(|prop| percent_decode_str(prop).decode_utf8())
+                    .map(|prop| prop.trim().to_string())
+                    .filter(|prop| !prop.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(";");
+
+                Some(KeyValueMetadata::new(
+                    name.to_owned(),
+                    value.trim().to_string(),
+                    metadata,
+                ))
             });
             cx.with_correlations(correlations)
         } else {
@@ -88,7 +169,7 @@ impl api::TextMapFormat for CorrelationContextPropagator {
     }
 
     fn fields(&self) -> FieldIter {
-        FieldIter::new(CORRELATION_CONTEXT_FIELDS.as_ref())
+        FieldIter::new(BAGGAGE_FIELDS.as_ref())
     }
 }
 
@@ -107,10 +188,12 @@ pub trait CorrelationContextExt {
     ///
     /// assert_eq!(
     ///     cx.correlation_context().get("my-name"),
-    ///     Some(&Value::String("my-value".to_string())),
+    ///     Some(&Value::String("my-value".into())),
     /// )
     /// ```
-    fn current_with_correlations<T: IntoIterator<Item = KeyValue>>(correlations: T) -> Self;
+    fn current_with_correlations<T: IntoIterator<Item = I>, I: Into<KeyValueMetadata>>(
+        correlations: T,
+    ) -> Self;
 
     /// Returns a clone of the given context with the included name / value pairs.
     ///
@@ -124,10 +207,13 @@ pub trait CorrelationContextExt {
     ///
     /// assert_eq!(
     ///     cx.correlation_context().get("my-name"),
-    ///     Some(&Value::String("my-value".to_string())),
+    ///     Some(&Value::String("my-value".into())),
     /// )
     /// ```
-    fn with_correlations<T: IntoIterator<Item = KeyValue>>(&self, correlations: T) -> Self;
+    fn with_correlations<T: IntoIterator<Item = I>, I: Into<KeyValueMetadata>>(
+        &self,
+        correlations: T,
+    ) -> Self;
 
     /// Returns a clone of the given context with the included name / value pairs.
     ///
@@ -148,16 +234,23 @@ pub trait CorrelationContextExt {
 }
 
 impl CorrelationContextExt for Context {
-    fn current_with_correlations<T: IntoIterator<Item = KeyValue>>(kvs: T) -> Self {
+    fn current_with_correlations<T: IntoIterator<Item = I>, I: Into<KeyValueMetadata>>(
+        kvs: T,
+    ) -> Self {
         Context::current().with_correlations(kvs)
     }
 
-    fn with_correlations<T: IntoIterator<Item = KeyValue>>(&self, kvs: T) -> Self {
+    fn with_correlations<T: IntoIterator<Item = I>, I: Into<KeyValueMetadata>>(
+        &self,
+        kvs: T,
+    ) -> Self {
         let merged = self
             .correlation_context()
             .iter()
-            .map(|(key, value)| KeyValue::new(key.clone(), value.clone()))
-            .chain(kvs.into_iter())
+            .map(|(key, (value, metadata))| {
+                KeyValueMetadata::new(key.clone(), value.clone(), metadata.clone())
+            })
+            .chain(kvs.into_iter().map(Into::into))
             .collect();
 
         self.with_value(Correlations(merged))
@@ -178,7 +271,7 @@ impl CorrelationContextExt for Context {
 mod tests {
     use super::*;
     use crate::api::TextMapFormat;
-    use crate::api::{Key, Value};
+    use crate::api::{EntryMetadata, Key, KeyValue, Value};
     use std::collections::HashMap;
 
     #[rustfmt::skip]
@@ -188,14 +281,28 @@ mod tests {
             ("key1=val1,key2=val2", vec![(Key::new("key1"), Value::from("val1")), (Key::new("key2"), Value::from("val2"))].into_iter().collect()),
             // "valid w3cHeader with spaces"
             ("key1 =   val1,  key2 =val2   ", vec![(Key::new("key1"), Value::from("val1")), (Key::new("key2"), Value::from("val2"))].into_iter().collect()),
-            // "valid w3cHeader with properties"
-            ("key1=val1,key2=val2;prop=1", vec![(Key::new("key1"), Value::from("val1")), (Key::new("key2"), Value::from("val2;prop=1"))].into_iter().collect()),
             // "valid header with url-escaped comma"
             ("key1=val1,key2=val2%2Cval3", vec![(Key::new("key1"), Value::from("val1")), (Key::new("key2"), Value::from("val2,val3"))].into_iter().collect()),
             // "valid header with an invalid header"
             ("key1=val1,key2=val2,a,val3", vec![(Key::new("key1"), Value::from("val1")), (Key::new("key2"), Value::from("val2"))].into_iter().collect()),
             // "valid header with no value"
             ("key1=,key2=val2", vec![(Key::new("key1"), Value::from("")), (Key::new("key2"), Value::from("val2"))].into_iter().collect()),
+            // "valid header with invalid percent-encoding and empty name"
+            ("key1=%ff,=val2,key3=val3", vec![(Key::new("key3"), Value::from("val3"))].into_iter().collect()),
+        ]
+    }
+
+    type CorrelationsWithMetadata = HashMap<Key, (Value, EntryMetadata)>;
+
+    #[rustfmt::skip]
+    fn valid_extract_data_with_metadata() -> Vec<(&'static str, CorrelationsWithMetadata)> {
+        vec![
+            // "valid w3cHeader with properties"
+            ("key1=val1,key2=val2;prop=1", vec![(Key::new("key1"), (Value::from("val1"), EntryMetadata::default())), (Key::new("key2"), (Value::from("val2"), EntryMetadata::from("prop=1")))].into_iter().collect()),
+            // "valid w3cHeader with multiple properties and spaces"
+            ("key1=val1 ; prop1 = 1 ; prop2", vec![(Key::new("key1"), (Value::from("val1"), EntryMetadata::from("prop1 = 1;prop2")))].into_iter().collect()),
+            // "valid header with url-escaped property"
+            ("key1=val1;prop=a%2Cb", vec![(Key::new("key1"), (Value::from("val1"), EntryMetadata::from("prop=a,b")))].into_iter().collect()),
         ]
     }
 
@@ -206,6 +313,8 @@ mod tests {
             (vec![KeyValue::new("key1", "val1"), KeyValue::new("key2", "val2")], vec!["key1=val1", "key2=val2"]),
             // "two values with escaped chars"
             (vec![KeyValue::new("key1", "val1,val2"), KeyValue::new("key2", "val3=4")], vec!["key1=val1%2Cval2", "key2=val3%3D4"]),
+            // "values with percent and backslash"
+            (vec![KeyValue::new("key1", "100%"), KeyValue::new("key2", "a\\b")], vec!["key1=100%25", "key2=a%5Cb"]),
             // "values of non-string non-array types"
             (
                 vec![
@@ -224,41 +333,85 @@ mod tests {
             // "values of array types"
             (
                 vec![
-                    KeyValue::new("key1", Value::Array(vec![Value::Bool(true), Value::Bool(false)])),
-                    KeyValue::new("key2", Value::Array(vec![Value::I64(123), Value::I64(456)])),
-                    KeyValue::new("key3", Value::Array(vec![Value::String("val1".to_string()), Value::String("val2".to_string())])),
-                    KeyValue::new("key4", Value::Array(vec![Value::Bytes(vec![118, 97, 108, 49]), Value::Bytes(vec![118, 97, 108, 50])])),
+                    KeyValue::new("key1", vec![true, false]),
+                    KeyValue::new("key2", vec![123i64, 456]),
+                    KeyValue::new("key3", vec!["val1", "val2"]),
+                    KeyValue::new("key4", vec![1.5, 2.0]),
                 ],
                 vec![
                     "key1=[true%2Cfalse]",
                     "key2=[123%2C456]",
                     "key3=[%22val1%22%2C%22val2%22]",
-                    "key4=[%22val1%22%2C%22val2%22]",
+                    "key4=[1.5%2C2]",
                 ],
             )
         ]
     }
 
+    #[rustfmt::skip]
+    fn valid_inject_data_with_metadata() -> Vec<(Vec<KeyValueMetadata>, Vec<&'static str>)> {
+        vec![
+            // "values with metadata"
+            (
+                vec![
+                    KeyValueMetadata::new("key1", "val1", "prop1"),
+                    KeyValueMetadata::new("key2", "val2", "prop2=1;prop3"),
+                    KeyValueMetadata::new("key3", "val3", ""),
+                ],
+                vec![
+                    "key1=val1;prop1",
+                    "key2=val2;prop2=1;prop3",
+                    "key3=val3",
+                ],
+            ),
+            // "metadata with escaped chars"
+            (
+                vec![
+                    KeyValueMetadata::new("key1", "val1", "prop1=a,b"),
+                    KeyValueMetadata::new("key2", "val2", "prop 2 = 100%"),
+                ],
+                vec![
+                    "key1=val1;prop1=a%2Cb",
+                    "key2=val2;prop%202=100%25",
+                ],
+            ),
+        ]
+    }
+
     #[test]
     fn extract_correlations() {
         let propagator = CorrelationContextPropagator::new();
 
         for (header_value, kvs) in valid_extract_data() {
             let mut extractor: HashMap<String, String> = HashMap::new();
-            extractor.insert(
-                CORRELATION_CONTEXT_HEADER.to_string(),
-                header_value.to_string(),
-            );
+            extractor.insert(BAGGAGE_HEADER.to_string(), header_value.to_string());
             let context = propagator.extract(&extractor);
             let correlations = context.correlation_context();
 
             assert_eq!(kvs.len(), correlations.len());
-            for (key, value) in correlations {
+            for (key, (value, _metadata)) in correlations {
                 assert_eq!(Some(value), kvs.get(key))
             }
         }
     }
 
+    #[test]
+    fn extract_correlations_with_metadata() {
+        let propagator = CorrelationContextPropagator::new();
+
+        for (header_value, kvms) in valid_extract_data_with_metadata() {
+            let mut extractor: HashMap<String, String> = HashMap::new();
+            extractor.insert(BAGGAGE_HEADER.to_string(), header_value.to_string());
+            let context = propagator.extract(&extractor);
+            let correlations = context.correlation_context();
+
+            assert_eq!(kvms.len(), correlations.len());
+            for (key, value_and_metadata) in correlations {
+                assert_eq!(Some(value_and_metadata), kvms.get(key))
+            }
+        }
+    }
+
     #[test]
     fn inject_correlations() {
         let propagator = CorrelationContextPropagator::new();
@@ -267,7 +420,7 @@ mod tests {
             let mut injector = HashMap::new();
             let cx = Context::current_with_correlations(kvs);
             propagator.inject_context(&cx, &mut injector);
-            let header_value = injector.get(CORRELATION_CONTEXT_HEADER).unwrap();
+            let header_value = injector.get(BAGGAGE_HEADER).unwrap();
 
             assert_eq!(header_parts.join(",").len(), header_value.len(),);
             for header_part in &header_parts {
@@ -275,4 +428,105 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn inject_correlations_with_metadata() {
+        let propagator = CorrelationContextPropagator::new();
+
+        for (kvms, header_parts) in valid_inject_data_with_metadata() {
+            let mut injector = HashMap::new();
+            let cx = Context::current_with_correlations(kvms);
+            propagator.inject_context(&cx, &mut injector);
+            let header_value = injector.get(BAGGAGE_HEADER).unwrap();
+
+            assert_eq!(header_parts.join(",").len(), header_value.len(),);
+            for header_part in &header_parts {
+                assert!(header_value.contains(header_part),)
+            }
+        }
+    }
+
+    #[test]
+    fn inject_extract_round_trip() {
+        let propagator = CorrelationContextPropagator::new();
+        let kvms = vec![
+            KeyValueMetadata::new("key1", "a,b;c", "prop=x,y;flag"),
+            KeyValueMetadata::new("key2", "val%2C", "prop=%2C=1"),
+        ];
+
+        let mut injector = HashMap::new();
+        propagator.inject_context(&Context::new().with_correlations(kvms), &mut injector);
+        let context = propagator.extract(&injector);
+        let correlations = context.correlation_context();
+
+        assert_eq!(correlations.len(), 2);
+        assert_eq!(
+            correlations.get_with_metadata("key1"),
+            Some(&(Value::from("a,b;c"), EntryMetadata::from("prop=x,y;flag")))
+        );
+        assert_eq!(
+            correlations.get_with_metadata("key2"),
+            Some(&(Value::from("val%2C"), EntryMetadata::from("prop=%2C=1")))
+        );
+    }
+
+    #[test]
+    fn limits_are_enforced() {
+        let propagator = CorrelationContextPropagator::new();
+
+        // too many entries
+        let kvs = (0..200).map(|i| KeyValue::new(format!("k{}", i), i as i64));
+        let mut injector = HashMap::new();
+        propagator.inject_context(&Context::new().with_correlations(kvs), &mut injector);
+        let header_value = injector.get(BAGGAGE_HEADER).unwrap();
+        assert_eq!(header_value.split(',').count(), MAX_KEY_VALUE_PAIRS);
+        let cx = propagator.extract(&injector);
+        assert_eq!(cx.correlation_context().len(), MAX_KEY_VALUE_PAIRS);
+
+        let mut extractor = HashMap::new();
+        let header_value = (0..200)
+            .map(|i| format!("k{}={}", i, i))
+            .collect::<Vec<_>>()
+            .join(",");
+        extractor.insert(BAGGAGE_HEADER.to_string(), header_value);
+        let cx = propagator.extract(&extractor);
+        assert_eq!(cx.correlation_context().len(), MAX_KEY_VALUE_PAIRS);
+
+        // entry too large
+        let large_value = "v".repeat(MAX_BYTES_FOR_ONE_PAIR);
+        let kvs = vec![
+            KeyValue::new("large", large_value.clone()),
+            KeyValue::new("small", "v"),
+        ];
+        let mut injector = HashMap::new();
+        propagator.inject_context(&Context::new().with_correlations(kvs), &mut injector);
+        assert_eq!(injector.get(BAGGAGE_HEADER), Some(&"small=v".to_string()));
+
+        let mut extractor = HashMap::new();
+        extractor.insert(
+            BAGGAGE_HEADER.to_string(),
+            format!("large={},small=v", large_value),
+        );
+        let cx = propagator.extract(&extractor);
+        assert_eq!(cx.correlation_context().len(), 1);
+        assert!(cx.correlation_context().get("small").is_some());
+
+        // total size too large
+        let value = "v".repeat(MAX_BYTES_FOR_ONE_PAIR - 4);
+        let kvs = (0..3).map(|i| KeyValue::new(format!("k{}", i), value.clone()));
+        let mut injector = HashMap::new();
+        propagator.inject_context(&Context::new().with_correlations(kvs), &mut injector);
+        let header_value = injector.get(BAGGAGE_HEADER).unwrap();
+        assert_eq!(header_value.split(',').count(), 2);
+        assert!(header_value.len() <= MAX_LEN_OF_ALL_PAIRS);
+
+        let mut extractor = HashMap::new();
+        let header_value = (0..3)
+            .map(|i| format!("k{}={}", i, value))
+            .collect::<Vec<_>>()
+            .join(",");
+        extractor.insert(BAGGAGE_HEADER.to_string(), header_value);
+        let cx = propagator.extract(&extractor);
+        assert_eq!(cx.correlation_context().len(), 2);
+    }
 }
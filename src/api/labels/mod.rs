@@ -1,5 +1,5 @@
 //! OpenTelemetry Labels
-use crate::api::{Key, KeyValue, Value};
+use crate::api::{Array, Key, KeyValue, Value};
 use std::cmp::Ordering;
 use std::collections::{btree_map, BTreeMap};
 use std::hash::{Hash, Hasher};
@@ -84,9 +84,16 @@ fn hash_value<H: Hasher>(state: &mut H, value: &Value) {
         }
         Value::String(s) => s.hash(state),
         Value::Bytes(b) => state.write(b),
-        Value::Array(arr) => {
-            // recursively hash array values
-            for val in arr {
+        Value::Array(arr) => match arr {
+            Array::Bool(values) => values.hash(state),
+            Array::I64(values) => values.hash(state),
+            Array::F64(values) => values.iter().for_each(|f| f.to_bits().hash(state)),
+            Array::String(values) => values.hash(state),
+        },
+        Value::Map(map) => {
+            // recursively hash map values
+            for (key, val) in map {
+                key.hash(state);
                 hash_value(state, val);
             }
         }
@@ -21,18 +21,15 @@ impl Descriptor {
         instrument_kind: InstrumentKind,
         number_kind: NumberKind,
     ) -> Self {
-        let mut hasher = FnvHasher::default();
-        name.hash(&mut hasher);
-        instrumentation_name.hash(&mut hasher);
-        instrument_kind.hash(&mut hasher);
-        number_kind.hash(&mut hasher);
+        let attribute_hash =
+            attribute_hash(&name, &instrumentation_name, &instrument_kind, &number_kind);
 
         Descriptor {
             name,
             instrument_kind,
             number_kind,
             config: InstrumentConfig::with_instrumentation_name(instrumentation_name),
-            attribute_hash: hasher.finish(),
+            attribute_hash,
         }
     }
 
@@ -41,6 +38,17 @@ impl Descriptor {
         self.name.as_str()
     }
 
+    /// Assign a new name, updating the attribute hash
+    pub(crate) fn set_name(&mut self, name: String) {
+        self.attribute_hash = attribute_hash(
+            &name,
+            &self.config.instrumentation_name,
+            &self.instrument_kind,
+            &self.number_kind,
+        );
+        self.name = name;
+    }
+
     /// The specific kind of instrument.
     pub fn instrument_kind(&self) -> &InstrumentKind {
         &self.instrument_kind
@@ -77,3 +85,17 @@ impl Descriptor {
         self.attribute_hash
     }
 }
+
+fn attribute_hash(
+    name: &str,
+    instrumentation_name: &str,
+    instrument_kind: &InstrumentKind,
+    number_kind: &NumberKind,
+) -> u64 {
+    let mut hasher = FnvHasher::default();
+    name.hash(&mut hasher);
+    instrumentation_name.hash(&mut hasher);
+    instrument_kind.hash(&mut hasher);
+    number_kind.hash(&mut hasher);
+    hasher.finish()
+}
@@ -68,6 +68,12 @@ pub enum MetricsError {
     /// Errors when aggregator cannot subtract
     #[error("Aggregator does not subtract")]
     NoSubtraction,
+    /// Errors when an instrument reaches its label set cardinality limit
+    #[error("Label set cardinality limit reached for instrument {0}, recording new label sets in the overflow series")]
+    CardinalityLimit(String),
+    /// Errors when a view could rename more than one instrument to the same name
+    #[error("View for {0} renames instruments matched with wildcards or to the name of another view, ignoring it")]
+    InvalidView(String),
 }
 
 impl<T> From<PoisonError<T>> for MetricsError {
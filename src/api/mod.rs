@@ -23,21 +23,24 @@ pub mod metrics;
 #[cfg(feature = "trace")]
 pub mod trace;
 
-pub use self::core::{Key, KeyValue, Unit, Value};
+pub use self::core::{Array, Key, KeyValue, StringValue, Unit, Value};
 #[cfg(feature = "trace")]
 pub use context::propagation::{
     composite_propagator::TextMapCompositePropagator, text_propagator::FieldIter,
-    text_propagator::TextMapFormat, Extractor, Injector,
+    text_propagator::TextMapFormat, Extractor, Injector, PropagationError,
 };
 pub use context::Context;
 #[cfg(feature = "trace")]
-pub use correlation::{CorrelationContext, CorrelationContextExt, CorrelationContextPropagator};
+pub use correlation::{
+    CorrelationContext, CorrelationContextExt, CorrelationContextPropagator, EntryMetadata,
+    KeyValueMetadata,
+};
 
 #[cfg(feature = "trace")]
 pub use trace::{
     context::TraceContextExt,
     event::Event,
-    futures::FutureExt,
+    futures::{FutureExt, InSpan, RecordError},
     id_generator::IdGenerator,
     link::Link,
     noop::{NoopProvider, NoopSpan, NoopSpanExporter, NoopTracer},
@@ -50,4 +53,5 @@ pub use trace::{
     span_processor::SpanProcessor,
     trace_context_propagator::TraceContextPropagator,
     tracer::{SpanBuilder, Tracer},
+    TraceError, TraceResult,
 };
@@ -6,12 +6,20 @@
 //! This main trait is [`FutureExt`], which allows a [`Context`],
 //! to be attached to a future, sink, or stream.
 //!
+//! [`InSpan`] futures and streams, created with `Tracer::in_span_async` and
+//! `Tracer::with_span_async`, also end their span when they complete or are
+//! dropped. [`RecordError`] futures, created with the `_result` variants of
+//! these methods, additionally record `Err` outputs on the span.
+//!
 //! [`futures`]: https://doc.rust-lang.org/std/future/trait.Future.html
 //! [`FutureExt`]: trait.FutureExt.html
+//! [`InSpan`]: struct.InSpan.html
+//! [`RecordError`]: struct.RecordError.html
 //! [`Context`]: ../../context/struct.Context.html
-use crate::api::context::Context as OpenTelemetryContext;
-use pin_project::pin_project;
+use crate::api::{self, context::Context as OpenTelemetryContext, TraceContextExt};
+use pin_project::{pin_project, pinned_drop};
 use std::{
+    fmt,
     pin::Pin,
     task::{Context as TaskContext, Poll},
 };
@@ -116,3 +124,267 @@ pub trait FutureExt: Sized {
         self.with_context(otel_cx)
     }
 }
+
+/// A future or stream that is the only user of a span.
+///
+/// The span's context is attached on every poll. The span ends when the
+/// future completes or the stream is exhausted, without changing its status.
+/// If it is dropped before that, the span ends with a `Canceled` status.
+#[pin_project(PinnedDrop)]
+#[derive(Debug)]
+pub struct InSpan<T> {
+    #[pin]
+    inner: T,
+    otel_cx: Option<OpenTelemetryContext>,
+}
+
+impl<T> InSpan<T> {
+    /// `otel_cx` must contain the span to end.
+    pub(crate) fn new(inner: T, otel_cx: OpenTelemetryContext) -> Self {
+        InSpan {
+            inner,
+            otel_cx: Some(otel_cx),
+        }
+    }
+}
+
+impl<T: std::future::Future> std::future::Future for InSpan<T> {
+    type Output = T::Output;
+
+    fn poll(self: Pin<&mut Self>, task_cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let otel_cx = match this.otel_cx.take() {
+            Some(otel_cx) => otel_cx,
+            None => return this.inner.poll(task_cx),
+        };
+
+        let poll = {
+            let _guard = otel_cx.clone().attach();
+            this.inner.poll(task_cx)
+        };
+        match &poll {
+            Poll::Ready(_) => otel_cx.span().end(),
+            Poll::Pending => *this.otel_cx = Some(otel_cx),
+        }
+
+        poll
+    }
+}
+
+impl<T: futures::Stream> futures::Stream for InSpan<T> {
+    type Item = T::Item;
+
+    fn poll_next(self: Pin<&mut Self>, task_cx: &mut TaskContext<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let otel_cx = match this.otel_cx.take() {
+            Some(otel_cx) => otel_cx,
+            None => return T::poll_next(this.inner, task_cx),
+        };
+
+        let poll = {
+            let _guard = otel_cx.clone().attach();
+            T::poll_next(this.inner, task_cx)
+        };
+        match &poll {
+            Poll::Ready(None) => otel_cx.span().end(),
+            _ => *this.otel_cx = Some(otel_cx),
+        }
+
+        poll
+    }
+}
+
+#[pinned_drop]
+impl<T> PinnedDrop for InSpan<T> {
+    fn drop(self: Pin<&mut Self>) {
+        if let Some(otel_cx) = self.project().otel_cx.take() {
+            let span = otel_cx.span();
+            span.set_status(api::StatusCode::Canceled, String::new());
+            span.end();
+        }
+    }
+}
+
+/// A future that records its `Err` output on the active span.
+///
+/// The error message, from its `Display` implementation, is recorded as an
+/// `exception` event and sets an `Unknown` status. Wrapped in an [`InSpan`], the span then ends as
+/// usual.
+///
+/// [`InSpan`]: struct.InSpan.html
+#[pin_project]
+#[derive(Debug)]
+pub struct RecordError<T> {
+    #[pin]
+    inner: T,
+}
+
+impl<T> RecordError<T> {
+    pub(crate) fn new(inner: T) -> Self {
+        RecordError { inner }
+    }
+}
+
+impl<T, O, E> std::future::Future for RecordError<T>
+where
+    T: std::future::Future<Output = Result<O, E>>,
+    E: fmt::Display,
+{
+    type Output = T::Output;
+
+    fn poll(self: Pin<&mut Self>, task_cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let poll = self.project().inner.poll(task_cx);
+        if let Poll::Ready(Err(err)) = &poll {
+            let otel_cx = OpenTelemetryContext::current();
+            let span = otel_cx.span();
+            let message = err.to_string();
+            span.add_event(
+                "exception".to_string(),
+                vec![api::KeyValue::new("exception.message", message.clone())],
+            );
+            span.set_status(api::StatusCode::Unknown, message);
+        }
+
+        poll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+    use std::time::SystemTime;
+
+    /// Records the status and events of a span, and whether it ended.
+    #[derive(Debug, Default)]
+    struct Recorded {
+        status: Option<(api::StatusCode, String)>,
+        events: Vec<String>,
+        ended: bool,
+        active_on_poll: bool,
+    }
+
+    #[derive(Debug)]
+    struct TestSpan(Arc<Mutex<Recorded>>);
+
+    impl api::Span for TestSpan {
+        fn add_event_with_timestamp(
+            &self,
+            name: String,
+            _timestamp: SystemTime,
+            _attributes: Vec<api::KeyValue>,
+        ) {
+            self.0.lock().unwrap().events.push(name);
+        }
+        fn span_context(&self) -> api::SpanContext {
+            api::SpanContext::empty_context()
+        }
+        fn is_recording(&self) -> bool {
+            true
+        }
+        fn set_attribute(&self, _attribute: api::KeyValue) {
+            // used by the test futures to mark that the span was active
+            self.0.lock().unwrap().active_on_poll = true;
+        }
+        fn set_status(&self, code: api::StatusCode, message: String) {
+            self.0.lock().unwrap().status = Some((code, message));
+        }
+        fn update_name(&self, _new_name: String) {}
+        fn end_with_timestamp(&self, _timestamp: SystemTime) {
+            self.0.lock().unwrap().ended = true;
+        }
+    }
+
+    fn in_span<T>(inner: T) -> (InSpan<T>, Arc<Mutex<Recorded>>) {
+        let recorded = Arc::new(Mutex::new(Recorded::default()));
+        let otel_cx = OpenTelemetryContext::new().with_span(TestSpan(recorded.clone()));
+        (InSpan::new(inner, otel_cx), recorded)
+    }
+
+    fn mark_active() {
+        OpenTelemetryContext::current()
+            .span()
+            .set_attribute(api::KeyValue::new("active", true));
+    }
+
+    #[test]
+    fn future_any_output() {
+        let (future, recorded) = in_span(async {
+            futures::future::ready(()).await;
+            mark_active();
+            42u32
+        });
+        assert_eq!(futures::executor::block_on(future), 42);
+
+        let recorded = recorded.lock().unwrap();
+        assert!(recorded.active_on_poll);
+        assert!(recorded.ended);
+        assert_eq!(recorded.status, None);
+    }
+
+    #[test]
+    fn future_ok() {
+        let (future, recorded) = in_span(RecordError::new(async {
+            futures::future::ready(()).await;
+            mark_active();
+            Ok::<_, io::Error>(1)
+        }));
+        assert_eq!(futures::executor::block_on(future).unwrap(), 1);
+
+        let recorded = recorded.lock().unwrap();
+        assert!(recorded.active_on_poll);
+        assert!(recorded.ended);
+        assert_eq!(recorded.status, None);
+    }
+
+    #[test]
+    fn future_err() {
+        let (future, recorded) = in_span(RecordError::new(async {
+            Err::<(), Box<dyn std::error::Error>>("connection reset".into())
+        }));
+        assert!(futures::executor::block_on(future).is_err());
+
+        let recorded = recorded.lock().unwrap();
+        assert!(recorded.ended);
+        assert_eq!(
+            recorded.status,
+            Some((api::StatusCode::Unknown, "connection reset".to_string()))
+        );
+        assert_eq!(recorded.events, vec!["exception".to_string()]);
+    }
+
+    #[test]
+    fn future_dropped() {
+        let (future, recorded) = in_span(futures::future::pending::<()>());
+        let mut future = Box::pin(future);
+        let waker = futures::task::noop_waker();
+        let mut task_cx = TaskContext::from_waker(&waker);
+        assert!(future.as_mut().poll(&mut task_cx).is_pending());
+        assert!(!recorded.lock().unwrap().ended);
+        drop(future);
+
+        let recorded = recorded.lock().unwrap();
+        assert!(recorded.ended);
+        assert_eq!(
+            recorded.status,
+            Some((api::StatusCode::Canceled, String::new()))
+        );
+    }
+
+    #[test]
+    fn stream_exhausted() {
+        use futures::StreamExt;
+
+        let (stream, recorded) =
+            in_span(futures::stream::iter(vec![1, 2]).inspect(|_| mark_active()));
+        let items: Vec<_> = futures::executor::block_on(stream.collect());
+        assert_eq!(items, vec![1, 2]);
+
+        let recorded = recorded.lock().unwrap();
+        assert!(recorded.active_on_poll);
+        assert!(recorded.ended);
+        assert_eq!(recorded.status, None);
+    }
+}
@@ -110,6 +110,11 @@
 //! Please review the W3C specification for details on the [Tracestate
 //! field](https://www.w3.org/TR/trace-context/#tracestate-field).
 //!
+use crate::exporter::ExportError;
+use std::result;
+use std::time::Duration;
+use thiserror::Error;
+
 pub mod context;
 pub mod event;
 pub mod futures;
@@ -122,3 +127,40 @@ pub mod span_context;
 pub mod span_processor;
 pub mod trace_context_propagator;
 pub mod tracer;
+
+/// A specialized `Result` type for trace operations.
+pub type TraceResult<T> = result::Result<T, TraceError>;
+
+/// Errors returned by the trace API and SDK.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum TraceError {
+    /// Export failed with the error returned by the exporter
+    #[error("Exporter {} encountered the following error(s): {0}", .0.exporter_name())]
+    ExportFailed(Box<dyn ExportError>),
+    /// Export did not complete within the configured timeout
+    #[error("Exporting timed out after {} seconds", .0.as_secs())]
+    ExportTimedOut(Duration),
+    /// An environment variable holds a value that cannot be used as configuration.
+    #[error("Invalid value {value:?} for environment variable {name}: {reason}")]
+    InvalidEnvVar {
+        /// Name of the environment variable
+        name: &'static str,
+        /// The value found in the environment
+        value: String,
+        /// Why the value was rejected
+        reason: String,
+    },
+    /// Other errors not covered by specific cases.
+    #[error("Trace error: {0}")]
+    Other(String),
+}
+
+impl<T> From<T> for TraceError
+where
+    T: ExportError,
+{
+    fn from(err: T) -> Self {
+        TraceError::ExportFailed(Box::new(err))
+    }
+}
@@ -109,14 +109,20 @@ impl api::Tracer for NoopTracer {
     /// Starts a new `NoopSpan` in a given context.
     ///
     /// If the context contains a valid span context, it is progagated.
-    fn start_from_context(&self, name: &str, cx: &api::Context) -> Self::Span {
+    fn start_from_context<T>(&self, name: T, cx: &api::Context) -> Self::Span
+    where
+        T: Into<api::StringValue>,
+    {
         let builder = self.span_builder(name);
         self.build_with_context(builder, cx)
     }
 
     /// Starts a `SpanBuilder`.
-    fn span_builder(&self, name: &str) -> api::SpanBuilder {
-        api::SpanBuilder::from_name(name.to_string())
+    fn span_builder<T>(&self, name: T) -> api::SpanBuilder
+    where
+        T: Into<api::StringValue>,
+    {
+        api::SpanBuilder::from_name(name)
     }
 
     /// Builds a `NoopSpan` from a `SpanBuilder`.
@@ -49,7 +49,7 @@ pub struct TraceContextPropagator {
 ///
 /// [W3C specification]: https://www.w3.org/TR/trace-context/#tracestate-header
 #[derive(Debug)]
-struct TraceState(String);
+pub(crate) struct TraceState(pub(crate) String);
 
 impl TraceContextPropagator {
     /// Create a new `TraceContextPropagator`.
@@ -141,13 +141,36 @@
 //! [`Future::with_context`] attaches a context to the future, ensuring that the
 //! context's lifetime is as long as the future's.
 //!
+//! To start a span for a future, [`Tracer::in_span_async`] makes the span
+//! active while the future is polled and ends it when the future completes.
+//! [`Tracer::in_span_async_result`] also records `Err` outputs on the span:
+//!
+//! ```
+//! # async fn run() -> Result<(), std::io::Error> {
+//! use opentelemetry::{global, api::Tracer};
+//! let tracer = global::tracer("my-component");
+//!
+//! let answer = tracer.in_span_async("foo", async { 42 }).await;
+//!
+//! tracer
+//!     .in_span_async_result("bar", async {
+//!         // ...
+//!         Ok(())
+//!     })
+//!     .await
+//! # }
+//! ```
+//!
 //! [`std::future::Future`]: https://doc.rust-lang.org/stable/std/future/trait.Future.html
 //! [`FutureExt`]: ../futures/trait.FutureExt.html
 //! [`Future::with_context`]: ../futures/trait.FutureExt.html#method.with_context
+//! [`Tracer::in_span_async`]: trait.Tracer.html#method.in_span_async
+//! [`Tracer::in_span_async_result`]: trait.Tracer.html#method.in_span_async_result
 //! [`Context`]: ../../context/struct.Context.html
 use crate::api::{
     self,
     context::{Context, ContextGuard},
+    trace::futures::{InSpan, RecordError},
     TraceContextExt,
 };
 use crate::sdk;
@@ -186,7 +209,10 @@ pub trait Tracer: fmt::Debug + 'static {
     /// created in another process. Each propagators' deserialization must set
     /// `is_remote` to true on a parent `SpanContext` so `Span` creation knows if the
     /// parent is remote.
-    fn start(&self, name: &str) -> Self::Span {
+    fn start<T>(&self, name: T) -> Self::Span
+    where
+        T: Into<api::StringValue>,
+    {
         self.start_from_context(name, &Context::current())
     }
 
@@ -213,12 +239,16 @@ pub trait Tracer: fmt::Debug + 'static {
     /// created in another process. Each propagators' deserialization must set
     /// `is_remote` to true on a parent `SpanContext` so `Span` creation knows if the
     /// parent is remote.
-    fn start_from_context(&self, name: &str, context: &Context) -> Self::Span;
+    fn start_from_context<T>(&self, name: T, context: &Context) -> Self::Span
+    where
+        T: Into<api::StringValue>;
 
     /// Creates a span builder
     ///
     /// An ergonomic way for attributes to be configured before the `Span` is started.
-    fn span_builder(&self, name: &str) -> SpanBuilder;
+    fn span_builder<T>(&self, name: T) -> SpanBuilder
+    where
+        T: Into<api::StringValue>;
 
     /// Create a span from a `SpanBuilder`
     fn build(&self, builder: SpanBuilder) -> Self::Span {
@@ -370,6 +400,107 @@ pub trait Tracer: fmt::Debug + 'static {
         let _guard = cx.clone().attach();
         f(cx)
     }
+
+    /// Start a new span as a child of the current context and make it active
+    /// while the given future or stream is polled.
+    ///
+    /// The span ends when the future completes or the stream is exhausted,
+    /// and dropping the future before it completes sets a `Canceled` status.
+    /// See [`InSpan`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::{global, api::Tracer};
+    ///
+    /// async fn my_function() -> u32 {
+    ///     global::tracer("my-component")
+    ///         .in_span_async("span-name", async {
+    ///             // the span is active while the future is polled...
+    ///             my_other_function().await
+    ///         })
+    ///         .await
+    /// }
+    ///
+    /// async fn my_other_function() -> u32 {
+    ///     42
+    /// }
+    /// ```
+    ///
+    /// [`InSpan`]: ../futures/struct.InSpan.html
+    fn in_span_async<T>(&self, name: &'static str, inner: T) -> InSpan<T>
+    where
+        Self::Span: Send + Sync,
+    {
+        self.with_span_async(self.start(name), inner)
+    }
+
+    /// Like [`in_span_async`], but an `Err` output of the future is recorded
+    /// as an exception on the span and sets an error status.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::{global, api::Tracer};
+    ///
+    /// async fn my_function() -> Result<(), std::io::Error> {
+    ///     global::tracer("my-component")
+    ///         .in_span_async_result("span-name", async {
+    ///             // an error is recorded on the span before it ends
+    ///             my_other_function().await
+    ///         })
+    ///         .await
+    /// }
+    ///
+    /// async fn my_other_function() -> Result<(), std::io::Error> {
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// [`in_span_async`]: #method.in_span_async
+    fn in_span_async_result<T>(&self, name: &'static str, inner: T) -> InSpan<RecordError<T>>
+    where
+        Self::Span: Send + Sync,
+    {
+        self.in_span_async(name, RecordError::new(inner))
+    }
+
+    /// Make the given span active while the given future or stream is
+    /// polled, and end it when the future completes or the stream is
+    /// exhausted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opentelemetry::{global, api::{SpanKind, Tracer}};
+    ///
+    /// async fn my_function() {
+    ///     let tracer = global::tracer("my-component");
+    ///     let span = tracer.span_builder("span-name").with_kind(SpanKind::Client).start(&tracer);
+    ///     tracer
+    ///         .with_span_async(span, async {
+    ///             // the span is active while the future is polled...
+    ///         })
+    ///         .await
+    /// }
+    /// ```
+    fn with_span_async<T>(&self, span: Self::Span, inner: T) -> InSpan<T>
+    where
+        Self::Span: Send + Sync,
+    {
+        InSpan::new(inner, Context::current_with_span(span))
+    }
+
+    /// Like [`with_span_async`], but an `Err` output of the future is recorded
+    /// as an exception on the span and sets an error status.
+    ///
+    /// [`with_span_async`]: #method.with_span_async
+    fn with_span_async_result<T>(&self, span: Self::Span, inner: T) -> InSpan<RecordError<T>>
+    where
+        Self::Span: Send + Sync,
+    {
+        self.with_span_async(span, RecordError::new(inner))
+    }
 }
 
 /// `SpanBuilder` allows span attributes to be configured before the span
@@ -385,7 +516,7 @@ pub trait Tracer: fmt::Debug + 'static {
 ///
 /// // The builder can be used to create a span directly with the tracer
 /// let _span = tracer.build(SpanBuilder {
-///     name: "example-span-name".to_string(),
+///     name: "example-span-name".into(),
 ///     span_kind: Some(SpanKind::Server),
 ///     ..Default::default()
 /// });
@@ -407,7 +538,7 @@ pub struct SpanBuilder {
     /// Span kind
     pub span_kind: Option<api::SpanKind>,
     /// Span name
-    pub name: String,
+    pub name: api::StringValue,
     /// Span start time
     pub start_time: Option<SystemTime>,
     /// Span end time
@@ -429,13 +560,13 @@ pub struct SpanBuilder {
 /// SpanBuilder methods
 impl SpanBuilder {
     /// Create a new span builder from a span name
-    pub fn from_name(name: String) -> Self {
+    pub fn from_name<T: Into<api::StringValue>>(name: T) -> Self {
         SpanBuilder {
             parent_context: None,
             trace_id: None,
             span_id: None,
             span_kind: None,
-            name,
+            name: name.into(),
             start_time: None,
             end_time: None,
             attributes: None,
@@ -6,35 +6,33 @@
 //! `Base64Format` MUST expose the APIs that serializes values into base64 strings,
 //! and deserializes values from base64 strings. There is a blanket implementation
 //! for any implementors of `BinaryFormat`
-use crate::api;
+use crate::api::{Context, PropagationError};
 #[cfg(feature = "binary_propagator")]
 use crate::experimental::api::BinaryFormat;
 use base64::{decode, encode};
 
-/// Used to serialize and deserialize `SpanContext`s to and from a base64
+/// Used to serialize and deserialize a `Context` to and from a base64
 /// representation.
 pub trait Base64Format {
-    /// Serializes span context into a base64 encoded string
-    fn to_base64(&self, context: &api::SpanContext) -> String;
+    /// Serializes the values of the given `Context` into a base64 encoded string
+    fn to_base64(&self, cx: &Context) -> String;
 
-    /// Deserialize a span context from a base64 encoded string
-    fn from_base64(&self, base64: &str) -> api::SpanContext;
+    /// Deserialize a base64 encoded string into a copy of the given `Context`
+    fn from_base64(&self, cx: &Context, base64: &str) -> Result<Context, PropagationError>;
 }
 
 impl<Format> Base64Format for Format
 where
     Format: BinaryFormat,
 {
-    fn to_base64(&self, context: &api::SpanContext) -> String {
-        encode(&self.to_bytes(context))
+    fn to_base64(&self, cx: &Context) -> String {
+        encode(self.inject_context(cx))
     }
 
-    fn from_base64(&self, base64: &str) -> api::SpanContext {
-        if let Ok(bytes) = decode(base64.as_bytes()) {
-            self.from_bytes(bytes)
-        } else {
-            api::SpanContext::empty_context()
-        }
+    fn from_base64(&self, cx: &Context, base64: &str) -> Result<Context, PropagationError> {
+        let bytes = decode(base64.as_bytes())
+            .map_err(|err| PropagationError::extract(err.to_string(), "base64 format"))?;
+        self.extract_with_context(cx, &bytes)
     }
 }
 
@@ -42,6 +40,30 @@ where
 mod tests {
     use super::super::binary_propagator::BinaryPropagator;
     use super::*;
+    use crate::api::{self, TraceContextExt};
+
+    #[derive(Debug)]
+    struct TestSpan(api::SpanContext);
+
+    impl api::Span for TestSpan {
+        fn add_event_with_timestamp(
+            &self,
+            _name: String,
+            _timestamp: std::time::SystemTime,
+            _attributes: Vec<api::KeyValue>,
+        ) {
+        }
+        fn span_context(&self) -> api::SpanContext {
+            self.0.clone()
+        }
+        fn is_recording(&self) -> bool {
+            false
+        }
+        fn set_attribute(&self, _attribute: api::KeyValue) {}
+        fn set_status(&self, _code: api::StatusCode, _message: String) {}
+        fn update_name(&self, _new_name: String) {}
+        fn end_with_timestamp(&self, _timestamp: std::time::SystemTime) {}
+    }
 
     #[rustfmt::skip]
     fn to_base64_data() -> Vec<(api::SpanContext, String)> {
@@ -49,29 +71,23 @@ mod tests {
             (api::SpanContext::new(
                 api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736),
                 api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 1, true),
-                "AABL+S81d7NNpqPOkp0ODkc2AQDwZ6oLqQK3AgE=".to_string()
+                "AQAAEEv5LzV3s02mo86SnQ4ORzYBAAgA8GeqC6kCtwIAAQE=".to_string()
             ),
             (api::SpanContext::new(
                 api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736),
                 api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 0, true),
-                "AABL+S81d7NNpqPOkp0ODkc2AQDwZ6oLqQK3AgA=".to_string()
+                "AQAAEEv5LzV3s02mo86SnQ4ORzYBAAgA8GeqC6kCtwIAAQA=".to_string()
             ),
         ]
     }
 
-    #[rustfmt::skip]
-    fn from_base64_data() -> Vec<(api::SpanContext, String)> {
-        vec![
-            (api::SpanContext::empty_context(), "invalid base64 string".to_string())
-        ]
-    }
-
     #[test]
     fn to_base64_conversion() {
         let propagator = BinaryPropagator::new();
 
-        for (context, data) in to_base64_data() {
-            assert_eq!(propagator.to_base64(&context), data)
+        for (span_context, data) in to_base64_data() {
+            let cx = Context::new().with_span(TestSpan(span_context));
+            assert_eq!(propagator.to_base64(&cx), data)
         }
     }
 
@@ -79,11 +95,12 @@ mod tests {
     fn from_base64_conversion() {
         let propagator = BinaryPropagator::new();
 
-        for (context, data) in from_base64_data() {
-            assert_eq!(propagator.from_base64(&data), context)
-        }
-        for (context, data) in to_base64_data() {
-            assert_eq!(propagator.from_base64(&data), context)
+        assert!(propagator
+            .from_base64(&Context::new(), "invalid base64 string")
+            .is_err());
+        for (span_context, data) in to_base64_data() {
+            let cx = propagator.from_base64(&Context::new(), &data).unwrap();
+            assert_eq!(cx.remote_span_context(), Some(&span_context))
         }
     }
 }
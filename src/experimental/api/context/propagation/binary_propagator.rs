@@ -5,171 +5,412 @@
 //!
 //! `BinaryFormat` MUST expose the APIs that serializes values into bytes,
 //! and deserializes values from bytes.
-use crate::api;
+//!
+//! ## Layout
+//!
+//! `BinaryPropagator` encodes a version byte followed by a sequence of
+//! length-prefixed fields:
+//!
+//! ```text
+//! version: u8
+//! field*:  field_id: u8 | length: u16 (big endian) | value: [u8; length]
+//! ```
+//!
+//! | field id | value                                                         |
+//! |----------|---------------------------------------------------------------|
+//! | 0        | trace id, 16 bytes                                            |
+//! | 1        | span id, 8 bytes                                              |
+//! | 2        | trace flags, 1 byte                                           |
+//! | 3        | W3C trace state, UTF-8                                        |
+//! | 4        | correlation entry, length-prefixed key, value and metadata    |
+//!
+//! Unknown fields are skipped, so newer versions can add fields without
+//! breaking older readers.
+use crate::api::trace::trace_context_propagator::TraceState;
+use crate::api::{
+    self, Context, CorrelationContextExt, KeyValueMetadata, PropagationError, TraceContextExt,
+};
 use std::convert::TryInto;
+use std::fmt;
+
+const VERSION: u8 = 1;
+const TRACE_ID_FIELD: u8 = 0;
+const SPAN_ID_FIELD: u8 = 1;
+const TRACE_FLAGS_FIELD: u8 = 2;
+const TRACE_STATE_FIELD: u8 = 3;
+const CORRELATION_FIELD: u8 = 4;
 
-/// Used to serialize and deserialize `SpanContext`s to and from a binary
-/// representation.
-pub trait BinaryFormat {
-    /// Serializes span context into a byte array and returns the array.
-    fn to_bytes(&self, context: &api::SpanContext) -> [u8; 29];
+const PROPAGATOR_NAME: &str = "binary propagator";
+
+/// Used to serialize and deserialize a `Context` to and from a binary
+/// representation, e.g. for message headers or custom RPC frames.
+pub trait BinaryFormat: fmt::Debug {
+    /// Serializes the values of the current `Context` into bytes.
+    fn inject(&self) -> Vec<u8> {
+        self.inject_context(&Context::current())
+    }
+
+    /// Serializes the values of the given `Context` into bytes.
+    ///
+    /// Returns an empty buffer if there is nothing to propagate.
+    fn inject_context(&self, cx: &Context) -> Vec<u8>;
+
+    /// Deserializes bytes into a copy of the current `Context`.
+    fn extract(&self, bytes: &[u8]) -> Result<Context, PropagationError> {
+        self.extract_with_context(&Context::current(), bytes)
+    }
 
-    /// Deserializes a span context from a byte array.
-    fn from_bytes(&self, bytes: Vec<u8>) -> api::SpanContext;
+    /// Deserializes bytes into a copy of the given `Context`.
+    ///
+    /// An empty buffer returns an unchanged copy of the `Context`, malformed
+    /// input returns an error.
+    fn extract_with_context(&self, cx: &Context, bytes: &[u8])
+        -> Result<Context, PropagationError>;
 }
 
-/// Extracts and injects `SpanContext`s from byte arrays.
+/// Extracts and injects `SpanContext`s, trace state and optionally
+/// correlation context entries from byte arrays.
 #[derive(Debug, Default)]
-pub struct BinaryPropagator {}
+pub struct BinaryPropagator {
+    with_correlation_context: bool,
+}
 
 impl BinaryPropagator {
-    /// Create a new binary propagator.
+    /// Create a new binary propagator that propagates span contexts and trace
+    /// state.
     pub fn new() -> Self {
-        BinaryPropagator {}
+        BinaryPropagator::default()
+    }
+
+    /// Create a new binary propagator that also propagates correlation
+    /// context entries.
+    pub fn with_correlation_context() -> Self {
+        BinaryPropagator {
+            with_correlation_context: true,
+        }
     }
 }
 
+/// Writes a field, skipping values that do not fit in a `u16` length.
+fn write_field(buf: &mut Vec<u8>, field_id: u8, value: &[u8]) {
+    if let Ok(len) = TryInto::<u16>::try_into(value.len()) {
+        buf.push(field_id);
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(value);
+    }
+}
+
+/// Writes a `u16` length-prefixed byte string, returns `None` if too long.
+fn write_bytes(buf: &mut Vec<u8>, value: &[u8]) -> Option<()> {
+    let len: u16 = value.len().try_into().ok()?;
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(value);
+    Some(())
+}
+
+/// Reads a `u16` length-prefixed byte string.
+fn read_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], PropagationError> {
+    if bytes.len() < 2 {
+        return Err(PropagationError::extract(
+            "truncated length",
+            PROPAGATOR_NAME,
+        ));
+    }
+    let len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+    if bytes.len() < 2 + len {
+        return Err(PropagationError::extract(
+            "truncated value",
+            PROPAGATOR_NAME,
+        ));
+    }
+    let (value, rest) = bytes[2..].split_at(len);
+    *bytes = rest;
+    Ok(value)
+}
+
+fn read_str<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, PropagationError> {
+    std::str::from_utf8(read_bytes(bytes)?)
+        .map_err(|_| PropagationError::extract("invalid UTF-8 string", PROPAGATOR_NAME))
+}
+
+fn fixed<T, const N: usize>(
+    value: &[u8],
+    field: &str,
+    f: impl FnOnce([u8; N]) -> T,
+) -> Result<T, PropagationError> {
+    value.try_into().map(f).map_err(|_| {
+        PropagationError::extract(format!("invalid {} length", field), PROPAGATOR_NAME)
+    })
+}
+
 impl BinaryFormat for BinaryPropagator {
-    /// Serializes span context into a byte array and returns the array.
-    fn to_bytes(&self, context: &api::SpanContext) -> [u8; 29] {
-        let mut res = [0u8; 29];
-        if !context.is_valid() {
-            return res;
+    fn inject_context(&self, cx: &Context) -> Vec<u8> {
+        let mut buf = vec![VERSION];
+
+        let span_context = cx.span().span_context();
+        if span_context.is_valid() {
+            write_field(
+                &mut buf,
+                TRACE_ID_FIELD,
+                &span_context.trace_id().to_byte_array(),
+            );
+            write_field(
+                &mut buf,
+                SPAN_ID_FIELD,
+                &span_context.span_id().to_byte_array(),
+            );
+            write_field(&mut buf, TRACE_FLAGS_FIELD, &[span_context.trace_flags()]);
         }
-        res[2..18].copy_from_slice(&context.trace_id().to_u128().to_be_bytes());
-        res[18] = 1;
-        res[19..27].copy_from_slice(&context.span_id().to_u64().to_be_bytes());
-        res[27] = 2;
-        res[28] = context.trace_flags();
 
-        res
+        if let Some(TraceState(state)) = cx.get() {
+            write_field(&mut buf, TRACE_STATE_FIELD, state.as_bytes());
+        }
+
+        if self.with_correlation_context {
+            for (key, (value, metadata)) in cx.correlation_context() {
+                let mut entry = Vec::new();
+                let encoded = write_bytes(&mut entry, key.as_str().as_bytes())
+                    .and_then(|_| write_bytes(&mut entry, String::from(value).as_bytes()))
+                    .and_then(|_| write_bytes(&mut entry, metadata.as_str().as_bytes()));
+                if encoded.is_some() {
+                    write_field(&mut buf, CORRELATION_FIELD, &entry);
+                }
+            }
+        }
+
+        if buf.len() == 1 {
+            buf.clear();
+        }
+        buf
     }
 
-    /// Deserializes a span context from a byte array.
-    fn from_bytes(&self, bytes: Vec<u8>) -> api::SpanContext {
-        if bytes.is_empty() {
-            return api::SpanContext::empty_context();
+    fn extract_with_context(
+        &self,
+        cx: &Context,
+        bytes: &[u8],
+    ) -> Result<Context, PropagationError> {
+        let (version, mut bytes) = match bytes.split_first() {
+            Some((version, rest)) => (*version, rest),
+            None => return Ok(cx.clone()),
+        };
+        if version != VERSION {
+            return Err(PropagationError::extract(
+                format!("unsupported version {}", version),
+                PROPAGATOR_NAME,
+            ));
         }
-        let trace_id: u128;
-        let mut span_id = 0;
+
+        let mut trace_id = None;
+        let mut span_id = None;
         let mut trace_flags = 0;
-        let mut b = &bytes[1..];
-        if b.len() >= 17 && b[0] == 0 {
-            trace_id = u128::from_be_bytes(b[1..17].try_into().unwrap());
-            b = &b[17..];
-        } else {
-            return api::SpanContext::empty_context();
+        let mut trace_state = None;
+        let mut correlations = Vec::new();
+        while let Some((field_id, rest)) = bytes.split_first() {
+            bytes = rest;
+            let mut value = read_bytes(&mut bytes)?;
+            match *field_id {
+                TRACE_ID_FIELD => {
+                    trace_id = Some(fixed(value, "trace id", api::TraceId::from_byte_array)?)
+                }
+                SPAN_ID_FIELD => {
+                    span_id = Some(fixed(value, "span id", api::SpanId::from_byte_array)?)
+                }
+                TRACE_FLAGS_FIELD => trace_flags = fixed(value, "trace flags", |[b]: [u8; 1]| b)?,
+                TRACE_STATE_FIELD => {
+                    let state = std::str::from_utf8(value).map_err(|_| {
+                        PropagationError::extract("invalid trace state", PROPAGATOR_NAME)
+                    })?;
+                    trace_state = Some(state.to_string())
+                }
+                CORRELATION_FIELD => {
+                    let key = read_str(&mut value)?;
+                    let entry_value = read_str(&mut value)?;
+                    let metadata = read_str(&mut value)?;
+                    correlations.push(KeyValueMetadata::new(
+                        key.to_string(),
+                        entry_value.to_string(),
+                        metadata,
+                    ));
+                }
+                // skip fields added by newer versions
+                _ => {}
+            }
         }
-        if b.len() >= 9 && b[0] == 1 {
-            span_id = u64::from_be_bytes(b[1..9].try_into().unwrap());
-            b = &b[9..];
+
+        let mut cx = match (trace_id, span_id) {
+            (Some(trace_id), Some(span_id)) => {
+                let span_context = api::SpanContext::new(trace_id, span_id, trace_flags, true);
+                if !span_context.is_valid() {
+                    return Err(PropagationError::extract(
+                        "invalid span context",
+                        PROPAGATOR_NAME,
+                    ));
+                }
+                cx.with_remote_span_context(span_context)
+            }
+            (None, None) => cx.clone(),
+            _ => {
+                return Err(PropagationError::extract(
+                    "incomplete span context",
+                    PROPAGATOR_NAME,
+                ))
+            }
+        };
+        if let Some(state) = trace_state {
+            cx = cx.with_value(TraceState(state));
         }
-        if b.len() >= 2 && b[0] == 2 {
-            trace_flags = b[1]
+        if !correlations.is_empty() {
+            cx = cx.with_correlations(correlations);
         }
 
-        let span_context = api::SpanContext::new(
-            api::TraceId::from_u128(trace_id),
-            api::SpanId::from_u64(span_id),
-            trace_flags,
-            true,
-        );
-
-        if span_context.is_valid() {
-            span_context
-        } else {
-            api::SpanContext::empty_context()
-        }
+        Ok(cx)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::api::{KeyValue, Value};
 
-    #[rustfmt::skip]
-    fn to_bytes_data() -> Vec<(api::SpanContext, [u8; 29])> {
-        vec![
-            // Context with sampled
-            (api::SpanContext::new(
-                api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736),
-                api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 1, true), [
-                0x00, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
-                0x02, 0x01,
-            ]),
-            // Context without sampled
-            (api::SpanContext::new(
-                api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736),
-                api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 0, true), [
-                0x00, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
-                0x02, 0x00,
-            ]),
-            // Invalid context
-            (api::SpanContext::empty_context(), [0u8; 29]),
-        ]
+    #[derive(Debug)]
+    struct TestSpan(api::SpanContext);
+
+    impl api::Span for TestSpan {
+        fn add_event_with_timestamp(
+            &self,
+            _name: String,
+            _timestamp: std::time::SystemTime,
+            _attributes: Vec<api::KeyValue>,
+        ) {
+        }
+        fn span_context(&self) -> api::SpanContext {
+            self.0.clone()
+        }
+        fn is_recording(&self) -> bool {
+            false
+        }
+        fn set_attribute(&self, _attribute: api::KeyValue) {}
+        fn set_status(&self, _code: api::StatusCode, _message: String) {}
+        fn update_name(&self, _new_name: String) {}
+        fn end_with_timestamp(&self, _timestamp: std::time::SystemTime) {}
+    }
+
+    fn span_context(trace_flags: u8) -> api::SpanContext {
+        api::SpanContext::new(
+            api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736),
+            api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7),
+            trace_flags,
+            true,
+        )
     }
 
     #[rustfmt::skip]
-    fn from_bytes_data() -> Vec<(api::SpanContext, Vec<u8>)> {
+    fn span_context_bytes(trace_flags: u8) -> Vec<u8> {
         vec![
-            // Future version of the proto
-            (api::SpanContext::new(api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736), api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 1, true), vec![
-                0x02, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
-                0x02, 0x01,
-            ]),
-            // current version with sampled
-            (api::SpanContext::new(api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736), api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 1, true), vec![
-                0x02, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
-                0x02, 0x01,
-            ]),
-            // valid context without option
-            (api::SpanContext::new(api::TraceId::from_u128(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736), api::SpanId::from_u64(0x00f0_67aa_0ba9_02b7), 0, true), vec![
-                0x00, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
-            ]),
-            // zero trace id
-            (api::SpanContext::empty_context(), vec![
-                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-                0x02, 0x01,
-            ]),
-            // zero span id
-            (api::SpanContext::empty_context(), vec![
-                0x00, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-                0x02, 0x01,
-            ]),
-            // wrong trace id field number
-            (api::SpanContext::empty_context(), vec![
-                0x00, 0x01, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
-                0x01, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
-            ]),
-            // short byte array
-            (api::SpanContext::empty_context(), vec![
-                0x00, 0x00, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d,
-            ]),
+            0x01,
+            0x00, 0x00, 0x10, 0x4b, 0xf9, 0x2f, 0x35, 0x77, 0xb3, 0x4d, 0xa6, 0xa3, 0xce, 0x92, 0x9d, 0x0e, 0x0e, 0x47, 0x36,
+            0x01, 0x00, 0x08, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
+            0x02, 0x00, 0x01, trace_flags,
         ]
     }
 
     #[test]
-    fn to_bytes_conversion() {
+    fn inject_span_context() {
         let propagator = BinaryPropagator::new();
-
-        for (context, data) in to_bytes_data() {
-            assert_eq!(propagator.to_bytes(&context), data)
+        for trace_flags in &[0, 1] {
+            let cx = Context::new().with_span(TestSpan(span_context(*trace_flags)));
+            assert_eq!(
+                propagator.inject_context(&cx),
+                span_context_bytes(*trace_flags)
+            );
         }
+
+        assert!(propagator.inject_context(&Context::new()).is_empty());
     }
 
     #[test]
-    fn from_bytes_conversion() {
+    fn round_trip() {
+        let cx = Context::new()
+            .with_span(TestSpan(span_context(1)))
+            .with_value(TraceState("foo=bar".to_string()))
+            .with_correlations(vec![
+                KeyValueMetadata::new("user", "alice", "prop=1"),
+                KeyValueMetadata::new("tenant", "acme", ""),
+            ]);
+
+        let propagator = BinaryPropagator::with_correlation_context();
+        let extracted = propagator
+            .extract_with_context(&Context::new(), &propagator.inject_context(&cx))
+            .unwrap();
+        assert_eq!(extracted.remote_span_context(), Some(&span_context(1)));
+        assert_eq!(
+            extracted.get::<TraceState>().map(|state| state.0.as_str()),
+            Some("foo=bar")
+        );
+        let correlations = extracted.correlation_context();
+        assert_eq!(correlations.len(), 2);
+        assert_eq!(
+            correlations.get_with_metadata("user"),
+            Some(&(Value::from("alice"), "prop=1".into()))
+        );
+
+        // correlation entries are only injected if enabled
         let propagator = BinaryPropagator::new();
+        let extracted = propagator
+            .extract_with_context(&Context::new(), &propagator.inject_context(&cx))
+            .unwrap();
+        assert_eq!(extracted.correlation_context().len(), 0);
+    }
 
-        for (context, data) in from_bytes_data() {
-            assert_eq!(propagator.from_bytes(data), context)
+    #[test]
+    fn extract_skips_unknown_fields() {
+        let mut bytes = span_context_bytes(1);
+        bytes.extend_from_slice(&[0x7f, 0x00, 0x02, 0xaa, 0xbb]);
+
+        let cx = BinaryPropagator::new()
+            .extract_with_context(&Context::new(), &bytes)
+            .unwrap();
+        assert_eq!(cx.remote_span_context(), Some(&span_context(1)));
+    }
+
+    #[test]
+    fn extract_empty() {
+        let cx = Context::new().with_correlations(vec![KeyValue::new("k", "v")]);
+        let extracted = BinaryPropagator::new()
+            .extract_with_context(&cx, &[])
+            .unwrap();
+        assert_eq!(extracted.correlation_context().len(), 1);
+        assert!(extracted.remote_span_context().is_none());
+    }
+
+    #[rustfmt::skip]
+    fn malformed_data() -> Vec<(&'static str, Vec<u8>)> {
+        let valid = span_context_bytes(1);
+        vec![
+            ("unsupported version", { let mut b = valid.clone(); b[0] = 0x00; b }),
+            ("truncated length", vec![0x01, 0x00, 0x00]),
+            ("truncated value", valid[..valid.len() - 1].to_vec()),
+            ("invalid trace id length", vec![0x01, 0x00, 0x00, 0x01, 0xff]),
+            ("invalid span id length", vec![0x01, 0x01, 0x00, 0x01, 0xff]),
+            ("incomplete span context", valid[..20].to_vec()),
+            ("invalid span context", vec![
+                0x01,
+                0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+                0x01, 0x00, 0x08, 0x00, 0xf0, 0x67, 0xaa, 0x0b, 0xa9, 0x02, 0xb7,
+            ]),
+            ("invalid trace state", vec![0x01, 0x03, 0x00, 0x01, 0xff]),
+            ("invalid UTF-8 string", vec![0x01, 0x04, 0x00, 0x07, 0x00, 0x01, 0xff, 0x00, 0x00, 0x00, 0x00]),
+        ]
+    }
+
+    #[test]
+    fn extract_malformed() {
+        let propagator = BinaryPropagator::new();
+        for (message, bytes) in malformed_data() {
+            let err = propagator
+                .extract_with_context(&Context::new(), &bytes)
+                .expect_err(message);
+            assert!(err.to_string().contains(message), "{}: {}", message, err);
         }
     }
 }
@@ -8,29 +8,30 @@
 //! `BinaryFormat` MUST expose the APIs that serializes values into bytes,
 //! and deserializes values from bytes.
 //!
-//! ### ToBytes
+//! ### Inject
 //!
-//! Serializes the given value into the on-the-wire representation.
+//! Serializes the values of a `Context`, e.g. its `SpanContext`, trace state
+//! and correlation context, into the on-the-wire representation.
 //!
 //! Required arguments:
 //!
-//! - the value to serialize, can be `SpanContext` or `DistributedContext`.
+//! - the `Context` to serialize.
 //!
-//! Returns the on-the-wire byte representation of the value.
+//! Returns the on-the-wire byte representation of the values.
 //!
-//! ### FromBytes
+//! ### Extract
 //!
-//! Creates a value from the given on-the-wire encoded representation.
+//! Creates a new `Context` from the given on-the-wire encoded representation.
 //!
-//! If the value could not be parsed, the underlying implementation
-//! SHOULD decide to return ether an empty value, an invalid value, or
-//! a valid value.
+//! If the value could not be parsed, the underlying implementation returns
+//! an error rather than an invalid value.
 //!
 //! Required arguments:
 //!
-//! - on-the-wire byte representation of the value.
+//! - the `Context` to extend with the extracted values.
+//! - on-the-wire byte representation of the values.
 //!
-//! Returns a value deserialized from bytes.
+//! Returns a copy of the `Context` with the deserialized values.
 //!
 
 #[cfg(feature = "base64")]
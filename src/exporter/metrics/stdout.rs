@@ -8,13 +8,13 @@ use crate::api::{
 use crate::global;
 use crate::sdk::{
     export::metrics::{
-        CheckpointSet, Count, ExportKind, ExportKindSelector, Exporter, LastValue, Max, Min,
-        Quantile, Sum,
+        CheckpointSet, Count, Distribution, ExportKind, ExportKindSelector, Exporter, LastValue,
+        Max, Min, Sum,
     },
     metrics::{
         aggregators::{
-            ArrayAggregator, HistogramAggregator, LastValueAggregator, MinMaxSumCountAggregator,
-            SumAggregator,
+            ArrayAggregator, ExponentialHistogramAggregator, HistogramAggregator,
+            LastValueAggregator, MinMaxSumCountAggregator, SumAggregator,
         },
         controllers::{self, PushController, PushControllerWorker},
         selectors::simple,
@@ -135,7 +135,7 @@ where
             let encoded_inst_labels = if !desc.instrumentation_name().is_empty() {
                 let inst_labels = LabelSet::from_labels(iter::once(KeyValue::new(
                     "instrumentation.name",
-                    desc.instrumentation_name(),
+                    desc.instrumentation_name().to_string(),
                 )));
                 inst_labels.encoded(Some(self.label_encoder.as_ref()))
             } else {
@@ -144,11 +144,22 @@ where
 
             let mut expose = ExportLine::default();
 
-            if let Some(array) = agg.as_any().downcast_ref::<ArrayAggregator>() {
-                expose.min = Some(ExportNumeric(array.min()?.to_debug(kind)));
-                expose.max = Some(ExportNumeric(array.max()?.to_debug(kind)));
-                expose.sum = Some(ExportNumeric(array.sum()?.to_debug(kind)));
-                expose.count = array.count()?;
+            let distribution: Option<&dyn Distribution> =
+                if let Some(array) = agg.as_any().downcast_ref::<ArrayAggregator>() {
+                    Some(array)
+                } else if let Some(exponential) = agg
+                    .as_any()
+                    .downcast_ref::<ExponentialHistogramAggregator>()
+                {
+                    Some(exponential)
+                } else {
+                    None
+                };
+            if let Some(distribution) = distribution {
+                expose.min = Some(ExportNumeric(distribution.min()?.to_debug(kind)));
+                expose.max = Some(ExportNumeric(distribution.max()?.to_debug(kind)));
+                expose.sum = Some(ExportNumeric(distribution.sum()?.to_debug(kind)));
+                expose.count = distribution.count()?;
 
                 let quantiles = self
                     .quantiles
@@ -156,7 +167,7 @@ where
                     .map(|&q| {
                         Ok(ExporterQuantile {
                             q,
-                            v: ExportNumeric(array.quantile(q)?.to_debug(kind)),
+                            v: ExportNumeric(distribution.quantile(q)?.to_debug(kind)),
                         })
                     })
                     .collect::<Result<Vec<_>>>()?;
@@ -8,7 +8,22 @@
 //! Exporters define the interface that protocol-specific exporters must
 //! implement so that they can be plugged into OpenTelemetry SDK and support
 //! sending of telemetry data.
+use std::error::Error;
+
 #[cfg(feature = "metrics")]
 pub mod metrics;
 #[cfg(feature = "trace")]
 pub mod trace;
+
+/// Trait for errors returned by exporters.
+///
+/// Exporters report failures through [`global::handle_error`] wrapped in the
+/// top level [`global::Error`], the exporter name identifies which exporter
+/// produced the error.
+///
+/// [`global::handle_error`]: ../global/fn.handle_error.html
+/// [`global::Error`]: ../global/enum.Error.html
+pub trait ExportError: Error + Send + Sync + 'static {
+    /// The name of the exporter that returned the error
+    fn exporter_name(&self) -> &'static str;
+}
@@ -68,7 +68,7 @@ pub struct SpanData {
     /// Span kind
     pub span_kind: api::SpanKind,
     /// Span name
-    pub name: String,
+    pub name: api::StringValue,
     /// Span start time
     pub start_time: SystemTime,
     /// Span end time
@@ -125,7 +125,7 @@ mod tests {
             span_context,
             parent_span_id: api::SpanId::from_u64(parent_span_id),
             span_kind,
-            name,
+            name: name.into(),
             start_time,
             end_time,
             attributes,
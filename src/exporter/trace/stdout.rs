@@ -21,7 +21,8 @@
 //!     .build();
 //! global::set_provider(provider);
 //! ```
-use crate::exporter::trace;
+use crate::exporter::{trace, ExportError};
+use crate::{api, global};
 use std::fmt::Debug;
 use std::io::{self, stdout, Stdout, Write};
 use std::sync::{Arc, Mutex};
@@ -102,14 +103,27 @@ where
             Ok(0)
         });
 
-        if result.is_ok() {
-            trace::ExportResult::Success
-        } else {
-            // FIXME: determine retryable io::Error types
-            trace::ExportResult::FailedNotRetryable
+        match result {
+            Ok(_) => trace::ExportResult::Success,
+            Err(err) => {
+                global::handle_error(api::TraceError::from(Error(err)));
+                // FIXME: determine retryable io::Error types
+                trace::ExportResult::FailedNotRetryable
+            }
         }
     }
 
     /// Ignored for now.
     fn shutdown(&self) {}
 }
+
+/// Stdout exporter's error
+#[derive(thiserror::Error, Debug)]
+#[error(transparent)]
+struct Error(#[from] io::Error);
+
+impl ExportError for Error {
+    fn exporter_name(&self) -> &'static str {
+        "stdout"
+    }
+}
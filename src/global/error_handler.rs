@@ -1,27 +1,72 @@
-use crate::api::metrics::{MetricsError, Result};
-use std::sync::RwLock;
+#[cfg(feature = "metrics")]
+use crate::api::metrics::MetricsError;
+#[cfg(feature = "trace")]
+use crate::api::{PropagationError, TraceError};
+use crate::sdk::resource::ResourceError;
+use std::sync::{PoisonError, RwLock};
 
 lazy_static::lazy_static! {
     /// The global error handler.
     static ref GLOBAL_ERROR_HANDLER: RwLock<Option<ErrorHandler>> = RwLock::new(None);
 }
 
-struct ErrorHandler(Box<dyn Fn(MetricsError) + Send + Sync>);
+/// Wrapper for error from both tracing and metrics part of open telemetry.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    #[cfg(feature = "trace")]
+    #[error(transparent)]
+    /// Failed to export or process spans.
+    Trace(#[from] TraceError),
+    #[cfg(feature = "trace")]
+    #[error(transparent)]
+    /// Failed to inject or extract context.
+    Propagation(#[from] PropagationError),
+    #[cfg(feature = "metrics")]
+    #[error(transparent)]
+    /// An issue raised by the metrics module.
+    Metric(#[from] MetricsError),
+    #[error(transparent)]
+    /// Failed to detect resources.
+    Resource(#[from] ResourceError),
+    #[error("{0}")]
+    /// Other types of failures not covered by the variants above.
+    Other(String),
+}
+
+impl<T> From<PoisonError<T>> for Error {
+    fn from(err: PoisonError<T>) -> Self {
+        Error::Other(err.to_string())
+    }
+}
+
+struct ErrorHandler(Box<dyn Fn(Error) + Send + Sync>);
 
 /// Handle error using the globally configured error handler.
 ///
 /// Writes to stderr if unset.
-pub fn handle_error(err: MetricsError) {
+pub fn handle_error<T: Into<Error>>(err: T) {
     match GLOBAL_ERROR_HANDLER.read() {
-        Ok(handler) if handler.is_some() => (handler.as_ref().unwrap().0)(err),
-        _ => eprintln!("OpenTelemetry metrics error occurred {:?}", err),
+        Ok(handler) if handler.is_some() => (handler.as_ref().unwrap().0)(err.into()),
+        _ => match err.into() {
+            #[cfg(feature = "trace")]
+            Error::Trace(err) => eprintln!("OpenTelemetry trace error occurred {}", err),
+            #[cfg(feature = "trace")]
+            Error::Propagation(err) => {
+                eprintln!("OpenTelemetry propagation error occurred {}", err)
+            }
+            #[cfg(feature = "metrics")]
+            Error::Metric(err) => eprintln!("OpenTelemetry metrics error occurred {:?}", err),
+            Error::Resource(err) => eprintln!("OpenTelemetry resource error occurred {}", err),
+            Error::Other(err_msg) => eprintln!("OpenTelemetry error occurred {}", err_msg),
+        },
     }
 }
 
 /// Set global error handler.
-pub fn set_error_handler<F>(f: F) -> Result<()>
+pub fn set_error_handler<F>(f: F) -> std::result::Result<(), Error>
 where
-    F: Fn(MetricsError) + Send + Sync + 'static,
+    F: Fn(Error) + Send + Sync + 'static,
 {
     GLOBAL_ERROR_HANDLER
         .write()
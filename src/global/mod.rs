@@ -68,7 +68,6 @@
 //! [`trace_provider`]: fn.trace_provider.html
 //! [trait objects]: https://doc.rust-lang.org/reference/types/trait-object.html#trait-objects
 
-#[cfg(feature = "metrics")]
 mod error_handler;
 #[cfg(feature = "metrics")]
 mod metrics;
@@ -77,11 +76,13 @@ mod propagation;
 #[cfg(feature = "trace")]
 mod trace;
 
-#[cfg(feature = "metrics")]
-pub use error_handler::{handle_error, set_error_handler};
+pub use error_handler::{handle_error, set_error_handler, Error};
 #[cfg(feature = "metrics")]
 pub use metrics::{meter, meter_provider, set_meter_provider};
 #[cfg(feature = "trace")]
-pub use propagation::{get_text_map_propagator, set_text_map_propagator};
+pub use propagation::{
+    get_text_map_propagator, register_text_map_propagator, set_text_map_propagator,
+    TextMapPropagatorBuilder,
+};
 #[cfg(feature = "trace")]
 pub use trace::{set_provider, trace_provider, tracer, GenericProvider};
@@ -1,11 +1,28 @@
 use crate::api;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
 use std::sync::RwLock;
 
+/// Comma separated list of propagators, e.g. `tracecontext,baggage,b3multi`.
+/// Defaults to `tracecontext,baggage`, `none` disables propagation.
+const OTEL_PROPAGATORS: &str = "OTEL_PROPAGATORS";
+
+type TextMapPropagatorFactory =
+    Box<dyn Fn() -> Box<dyn api::TextMapFormat + Send + Sync> + Send + Sync>;
+
 lazy_static::lazy_static! {
     /// The current global `TextMapFormat` propagator.
     static ref GLOBAL_TEXT_MAP_PROPAGATOR: RwLock<Box<dyn api::TextMapFormat + Send + Sync>> = RwLock::new(Box::new(api::TextMapCompositePropagator::new(vec![Box::new(api::TraceContextPropagator::new()), Box::new(api::CorrelationContextPropagator::new())])));
     /// The global default `TextMapFormat` propagator.
     static ref DEFAULT_TEXT_MAP_PROPAGATOR: api::TextMapCompositePropagator = api::TextMapCompositePropagator::new(vec![Box::new(api::TraceContextPropagator::new()), Box::new(api::CorrelationContextPropagator::new())]);
+    /// The `TextMapFormat` factories by `OTEL_PROPAGATORS` name.
+    static ref TEXT_MAP_PROPAGATOR_FACTORIES: RwLock<HashMap<String, TextMapPropagatorFactory>> = {
+        let mut factories: HashMap<String, TextMapPropagatorFactory> = HashMap::new();
+        factories.insert("tracecontext".to_string(), Box::new(|| Box::new(api::TraceContextPropagator::new())));
+        factories.insert("baggage".to_string(), Box::new(|| Box::new(api::CorrelationContextPropagator::new())));
+        RwLock::new(factories)
+    };
 }
 
 /// Sets the given [`TextMapFormat`] propagator as the current global propagator.
@@ -57,3 +74,181 @@ where
         .map(|propagator| f(&**propagator))
         .unwrap_or_else(|_| f(&*DEFAULT_TEXT_MAP_PROPAGATOR as &dyn api::TextMapFormat))
 }
+
+/// Registers a [`TextMapFormat`] factory under the given name, so it can be
+/// selected with `OTEL_PROPAGATORS` or [`TextMapPropagatorBuilder`].
+///
+/// `tracecontext` and `baggage` are registered by default, registering an
+/// existing name replaces its factory.
+///
+/// [`TextMapFormat`]: ../api/context/propagation/trait.TextMapFormat.html
+/// [`TextMapPropagatorBuilder`]: struct.TextMapPropagatorBuilder.html
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::{api, global};
+///
+/// global::register_text_map_propagator("w3c", api::TraceContextPropagator::new);
+/// ```
+pub fn register_text_map_propagator<F, P>(name: &str, factory: F)
+where
+    F: Fn() -> P + Send + Sync + 'static,
+    P: api::TextMapFormat + Send + Sync + 'static,
+{
+    let _lock = TEXT_MAP_PROPAGATOR_FACTORIES.write().map(|mut factories| {
+        factories.insert(
+            name.to_lowercase(),
+            Box::new(move || Box::new(factory()) as Box<dyn api::TextMapFormat + Send + Sync>),
+        )
+    });
+}
+
+/// Assembles a [`TextMapCompositePropagator`] from registered propagator
+/// factories, selected by name.
+///
+/// [`TextMapCompositePropagator`]: ../api/context/propagation/composite_propagator/struct.TextMapCompositePropagator.html
+///
+/// # Examples
+///
+/// ```
+/// use opentelemetry::global;
+///
+/// // reads `OTEL_PROPAGATORS`, e.g. `tracecontext,baggage`
+/// global::TextMapPropagatorBuilder::from_env()
+///     .install()
+///     .expect("unknown propagators in OTEL_PROPAGATORS");
+/// ```
+#[derive(Default)]
+pub struct TextMapPropagatorBuilder {
+    names: Vec<String>,
+}
+
+impl fmt::Debug for TextMapPropagatorBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextMapPropagatorBuilder")
+            .field("names", &self.names)
+            .finish()
+    }
+}
+
+impl TextMapPropagatorBuilder {
+    /// Create a builder without any propagators.
+    pub fn new() -> Self {
+        TextMapPropagatorBuilder::default()
+    }
+
+    /// Create a builder with the propagators listed in `OTEL_PROPAGATORS`.
+    ///
+    /// Uses `tracecontext,baggage` if the variable is unset or empty, and no
+    /// propagators if it is `none`.
+    pub fn from_env() -> Self {
+        let value = env::var_os(OTEL_PROPAGATORS)
+            .map(|value| value.to_string_lossy().into_owned())
+            .filter(|value| !value.trim().is_empty())
+            .unwrap_or_else(|| "tracecontext,baggage".to_string());
+
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty() && *name != "none")
+            .fold(TextMapPropagatorBuilder::new(), |builder, name| {
+                builder.with_propagator(name)
+            })
+    }
+
+    /// Add the propagator registered under `name`, names are case insensitive
+    /// and duplicates are ignored.
+    pub fn with_propagator<T: Into<String>>(mut self, name: T) -> Self {
+        let name = name.into().to_lowercase();
+        if !self.names.contains(&name) {
+            self.names.push(name);
+        }
+        self
+    }
+
+    /// Build a composite propagator in the order the names were added.
+    ///
+    /// Returns an error listing all names without a registered factory.
+    pub fn build(self) -> Result<api::TextMapCompositePropagator, api::PropagationError> {
+        let factories = TEXT_MAP_PROPAGATOR_FACTORIES
+            .read()
+            .unwrap_or_else(|err| err.into_inner());
+
+        let mut propagators = Vec::with_capacity(self.names.len());
+        let mut unknown = Vec::new();
+        for name in self.names {
+            match factories.get(&name) {
+                Some(factory) => propagators.push(factory()),
+                None => unknown.push(name),
+            }
+        }
+
+        if unknown.is_empty() {
+            Ok(api::TextMapCompositePropagator::new(propagators))
+        } else {
+            Err(api::PropagationError::unknown(unknown))
+        }
+    }
+
+    /// Build the composite propagator and set it as the current global
+    /// propagator.
+    pub fn install(self) -> Result<(), api::PropagationError> {
+        set_text_map_propagator(self.build()?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{TextMapFormat, TraceContextPropagator};
+
+    #[test]
+    fn build_from_names() {
+        register_text_map_propagator("TraceContext-Copy", TraceContextPropagator::new);
+
+        let propagator = TextMapPropagatorBuilder::new()
+            .with_propagator("tracecontext")
+            .with_propagator("baggage")
+            .with_propagator("tracecontext-copy")
+            .with_propagator("TRACECONTEXT")
+            .build()
+            .unwrap();
+        let mut fields = propagator.fields().collect::<Vec<_>>();
+        fields.sort_unstable();
+        assert_eq!(fields, vec!["baggage", "traceparent", "tracestate"]);
+
+        let err = TextMapPropagatorBuilder::new()
+            .with_propagator("tracecontext")
+            .with_propagator("xray")
+            .with_propagator("unknown")
+            .build()
+            .unwrap_err();
+        assert_eq!(err.unknown_propagators(), ["xray", "unknown"]);
+    }
+
+    // All cases share the same variable, so they run in a single test to
+    // avoid races between tests running in parallel.
+    #[test]
+    fn builder_from_env() {
+        let names = |builder: TextMapPropagatorBuilder| builder.names;
+
+        env::remove_var(OTEL_PROPAGATORS);
+        assert_eq!(
+            names(TextMapPropagatorBuilder::from_env()),
+            ["tracecontext", "baggage"]
+        );
+
+        env::set_var(OTEL_PROPAGATORS, " b3multi , Jaeger,,b3multi");
+        assert_eq!(
+            names(TextMapPropagatorBuilder::from_env()),
+            ["b3multi", "jaeger"]
+        );
+
+        env::set_var(OTEL_PROPAGATORS, "none");
+        assert!(names(TextMapPropagatorBuilder::from_env()).is_empty());
+
+        env::remove_var(OTEL_PROPAGATORS);
+    }
+}
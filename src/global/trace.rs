@@ -90,15 +90,21 @@ impl api::Tracer for BoxedTracer {
     /// trace. A span is said to be a _root span_ if it does not have a parent. Each
     /// trace includes a single root span, which is the shared ancestor of all other
     /// spans in the trace.
-    fn start_from_context(&self, name: &str, cx: &api::Context) -> Self::Span {
-        BoxedSpan(self.0.start_with_context_boxed(name, cx))
+    fn start_from_context<T>(&self, name: T, cx: &api::Context) -> Self::Span
+    where
+        T: Into<api::StringValue>,
+    {
+        BoxedSpan(self.0.start_with_context_boxed(name.into(), cx))
     }
 
     /// Creates a span builder
     ///
     /// An ergonomic way for attributes to be configured before the `Span` is started.
-    fn span_builder(&self, name: &str) -> api::SpanBuilder {
-        api::SpanBuilder::from_name(name.to_string())
+    fn span_builder<T>(&self, name: T) -> api::SpanBuilder
+    where
+        T: Into<api::StringValue>,
+    {
+        api::SpanBuilder::from_name(name)
     }
 
     /// Create a span from a `SpanBuilder`
@@ -118,7 +124,7 @@ pub trait GenericTracer: fmt::Debug + 'static {
 
     /// Returns a trait object so the underlying implementation can be swapped
     /// out at runtime.
-    fn start_with_context_boxed(&self, name: &str, cx: &api::Context) -> Box<DynSpan>;
+    fn start_with_context_boxed(&self, name: api::StringValue, cx: &api::Context) -> Box<DynSpan>;
 
     /// Returns a trait object so the underlying implementation can be swapped
     /// out at runtime.
@@ -141,7 +147,7 @@ where
 
     /// Returns a trait object so the underlying implementation can be swapped
     /// out at runtime.
-    fn start_with_context_boxed(&self, name: &str, cx: &api::Context) -> Box<DynSpan> {
+    fn start_with_context_boxed(&self, name: api::StringValue, cx: &api::Context) -> Box<DynSpan> {
         Box::new(self.start_from_context(name, cx))
     }
 
@@ -2,10 +2,14 @@
 //!
 //! Implementation of `ResourceDetector` to extract a `Resource` from environment
 //! variables.
+#[cfg(feature = "trace")]
+use crate::api::{TraceError, TraceResult};
 use crate::api::{Key, KeyValue, Value};
 use crate::sdk::resource::ResourceDetector;
 use crate::sdk::Resource;
 use std::env;
+#[cfg(feature = "trace")]
+use std::{fmt, str::FromStr};
 use std::time::Duration;
 
 static OTEL_RESOURCE_ATTRIBUTES: &str = "OTEL_RESOURCE_ATTRIBUTES";
@@ -43,6 +47,32 @@ impl Default for EnvResourceDetector {
     }
 }
 
+/// Read and parse an environment variable, `None` if it is unset or empty.
+#[cfg(feature = "trace")]
+pub(crate) fn parse_env_var<T>(name: &'static str) -> TraceResult<Option<T>>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match env::var(name) {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|err: T::Err| TraceError::InvalidEnvVar {
+                name,
+                reason: err.to_string(),
+                value,
+            }),
+        Ok(_) | Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(value)) => Err(TraceError::InvalidEnvVar {
+            name,
+            value: value.to_string_lossy().into_owned(),
+            reason: "not valid unicode".to_string(),
+        }),
+    }
+}
+
 /// Extract key value pairs and construct a resource from resources string like
 /// key1=value1,key2=value2,...
 fn construct_otel_resources(s: String) -> Resource {
@@ -54,7 +84,7 @@ fn construct_otel_resources(s: String) -> Resource {
         }
 
         let key = Key::from(key_value_strs.get(0).unwrap().to_string());
-        let value = Value::String(key_value_strs.get(1).unwrap().to_string());
+        let value = Value::from(key_value_strs.get(1).unwrap().to_string());
 
         key_values.push(KeyValue::new(key, value));
     }
@@ -80,13 +110,10 @@ mod tests {
         assert_eq!(
             resource,
             Resource::new(vec![
-                KeyValue::new(
-                    Key::new("key".to_string()),
-                    Value::String("value".to_string())
-                ),
-                KeyValue::new(Key::new("k".to_string()), Value::String("v".to_string())),
-                KeyValue::new(Key::new("a".to_string()), Value::String("x".to_string())),
-                KeyValue::new(Key::new("a".to_string()), Value::String("z".to_string()))
+                KeyValue::new(Key::new("key".to_string()), Value::String("value".into())),
+                KeyValue::new(Key::new("k".to_string()), Value::String("v".into())),
+                KeyValue::new(Key::new("a".to_string()), Value::String("x".into())),
+                KeyValue::new(Key::new("a".to_string()), Value::String("z".into()))
             ])
         );
 
@@ -0,0 +1,576 @@
+use crate::api::metrics::{Descriptor, MetricsError, Number, NumberKind, Result};
+use crate::sdk::export::metrics::{
+    Aggregator, Count, Distribution, Max, Min, MinMaxSumCount, Quantile, Sum,
+};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+/// The scale of an empty histogram, the finest resolution it starts at.
+const MAX_SCALE: i32 = 20;
+
+/// Create a new `ExponentialHistogramAggregator` that keeps at most `max_size`
+/// buckets for positive and for negative values. `max_size` is at least 2.
+pub fn exponential_histogram(desc: &Descriptor, max_size: usize) -> ExponentialHistogramAggregator {
+    let kind = desc.number_kind().clone();
+    ExponentialHistogramAggregator {
+        inner: Mutex::new(Inner {
+            state: State::empty(&kind),
+            max_size: max_size.max(2),
+            kind,
+        }),
+    }
+}
+
+/// An aggregator that counts events in exponential buckets, and estimates
+/// quantiles from the bucket counts.
+///
+/// The bucket boundaries are powers of `base = 2^(2^-scale)`: the bucket with
+/// index `i` counts values in `[base^i, base^(i+1))`, negative values are
+/// counted in the same way by their absolute value, and zeros are counted
+/// separately. The scale starts at the highest resolution and is lowered as needed to
+/// keep the number of buckets within the configured maximum, by merging
+/// neighbouring buckets. The relative error of quantile estimates is bounded
+/// by the resolution of the final scale.
+#[derive(Debug)]
+pub struct ExponentialHistogramAggregator {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    max_size: usize,
+    kind: NumberKind,
+    state: State,
+}
+
+#[derive(Debug)]
+struct State {
+    count: u64,
+    sum: Number,
+    min: Number,
+    max: Number,
+    scale: i32,
+    zero_count: u64,
+    positive: BucketCounts,
+    negative: BucketCounts,
+}
+
+impl State {
+    fn empty(kind: &NumberKind) -> Self {
+        State {
+            count: 0,
+            sum: kind.zero(),
+            min: kind.zero(),
+            max: kind.zero(),
+            scale: MAX_SCALE,
+            zero_count: 0,
+            positive: BucketCounts::default(),
+            negative: BucketCounts::default(),
+        }
+    }
+
+    fn update_min_max(&mut self, kind: &NumberKind, min: &Number, max: &Number) {
+        if self.count == 0 || min.partial_cmp(kind, &self.min) == Some(Ordering::Less) {
+            self.min = min.clone();
+        }
+        if self.count == 0 || max.partial_cmp(kind, &self.max) == Some(Ordering::Greater) {
+            self.max = max.clone();
+        }
+    }
+
+    /// Lower the scale by `change`, merging neighbouring buckets.
+    fn downscale(&mut self, change: i32) {
+        self.positive.downscale(change);
+        self.negative.downscale(change);
+        self.scale -= change;
+    }
+
+    /// The value to report for the bucket of the given index, the midpoint of
+    /// its boundaries.
+    fn bucket_value(&self, index: i32) -> f64 {
+        (lower_boundary(index, self.scale) + lower_boundary(index + 1, self.scale)) / 2.0
+    }
+
+    fn quantile(&self, kind: &NumberKind, q: f64) -> Result<Number> {
+        if self.count == 0 {
+            return Err(MetricsError::NoDataCollected);
+        }
+
+        if !(0.0..=1.0).contains(&q) {
+            return Err(MetricsError::InvalidQuantile);
+        }
+
+        if q == 0.0 {
+            return Ok(self.min.clone());
+        } else if (q - 1.0).abs() < f64::EPSILON {
+            return Ok(self.max.clone());
+        }
+
+        // Walk the buckets in ascending order of their values until the rank
+        // of the quantile is reached.
+        let rank = ((self.count - 1) as f64 * q).ceil() as u64;
+        let negative = self
+            .negative
+            .iter()
+            .rev()
+            .map(|(index, count)| (-self.bucket_value(index), count));
+        let zero = std::iter::once((0.0, self.zero_count));
+        let positive = self
+            .positive
+            .iter()
+            .map(|(index, count)| (self.bucket_value(index), count));
+
+        let mut seen = 0;
+        let mut estimate = self.max.to_f64(kind);
+        for (value, count) in negative.chain(zero).chain(positive) {
+            seen += count;
+            if seen > rank {
+                estimate = value;
+                break;
+            }
+        }
+        let estimate = estimate
+            .max(self.min.to_f64(kind))
+            .min(self.max.to_f64(kind));
+
+        Ok(match kind {
+            NumberKind::I64 => Number::from(estimate.round() as i64),
+            NumberKind::F64 => Number::from(estimate),
+            NumberKind::U64 => Number::from(estimate.round() as u64),
+        })
+    }
+}
+
+/// Bucket counts of consecutive bucket indices, starting at `offset`.
+#[derive(Clone, Debug, Default)]
+struct BucketCounts {
+    offset: i32,
+    counts: Vec<u64>,
+}
+
+impl BucketCounts {
+    /// The lowest and highest index of the buckets, if there are any.
+    fn range(&self) -> Option<(i32, i32)> {
+        if self.counts.is_empty() {
+            None
+        } else {
+            Some((self.offset, self.offset + self.counts.len() as i32 - 1))
+        }
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = (i32, u64)> + '_ {
+        let offset = self.offset;
+        self.counts
+            .iter()
+            .enumerate()
+            .map(move |(i, count)| (offset + i as i32, *count))
+    }
+
+    fn increment(&mut self, index: i32, count: u64) {
+        if self.counts.is_empty() {
+            self.offset = index;
+            self.counts.push(count);
+            return;
+        }
+
+        if index < self.offset {
+            let grow = (self.offset - index) as usize;
+            self.counts.splice(0..0, vec![0; grow]);
+            self.offset = index;
+        }
+        let position = (index - self.offset) as usize;
+        if position >= self.counts.len() {
+            self.counts.resize(position + 1, 0);
+        }
+        self.counts[position] += count;
+    }
+
+    /// Merge the buckets whose indices are equal after shifting them right by
+    /// `change`.
+    fn downscale(&mut self, change: i32) {
+        if change == 0 || self.counts.is_empty() {
+            return;
+        }
+
+        let offset = self.offset;
+        for (i, count) in mem::take(&mut self.counts).into_iter().enumerate() {
+            if count > 0 {
+                self.increment((offset + i as i32) >> change, count);
+            }
+        }
+    }
+}
+
+/// The index of the bucket that counts `value` at `scale`. `value` must be
+/// positive.
+fn bucket_index(value: f64, scale: i32) -> i32 {
+    (value.log2() * 2f64.powi(scale)).floor() as i32
+}
+
+/// The lowest value counted by the bucket of the given index at `scale`.
+fn lower_boundary(index: i32, scale: i32) -> f64 {
+    (f64::from(index) / 2f64.powi(scale)).exp2()
+}
+
+/// The scale reduction that is needed to fit the buckets from `low` to `high`
+/// in `max_size` buckets.
+fn scale_change(low: i32, high: i32, max_size: usize) -> i32 {
+    // the range of indices can exceed `i32::MAX` at high scales, e.g. from a
+    // subnormal value to `f64::MAX`
+    let (low, high) = (i64::from(low), i64::from(high));
+    let mut change = 0;
+    while ((high >> change) - (low >> change)) as u64 >= max_size as u64 {
+        change += 1;
+    }
+    change
+}
+
+/// The index range that covers both ranges, if any.
+fn union(a: Option<(i32, i32)>, b: Option<(i32, i32)>) -> Option<(i32, i32)> {
+    match (a, b) {
+        (Some((a_low, a_high)), Some((b_low, b_high))) => {
+            Some((a_low.min(b_low), a_high.max(b_high)))
+        }
+        (range, None) | (None, range) => range,
+    }
+}
+
+/// Shift an index range to a lower scale.
+fn shift(range: Option<(i32, i32)>, change: i32) -> Option<(i32, i32)> {
+    range.map(|(low, high)| (low >> change, high >> change))
+}
+
+impl Min for ExponentialHistogramAggregator {
+    fn min(&self) -> Result<Number> {
+        self.inner
+            .lock()
+            .map_err(From::from)
+            .map(|inner| inner.state.min.clone())
+    }
+}
+
+impl Max for ExponentialHistogramAggregator {
+    fn max(&self) -> Result<Number> {
+        self.inner
+            .lock()
+            .map_err(From::from)
+            .map(|inner| inner.state.max.clone())
+    }
+}
+
+impl Sum for ExponentialHistogramAggregator {
+    fn sum(&self) -> Result<Number> {
+        self.inner
+            .lock()
+            .map_err(From::from)
+            .map(|inner| inner.state.sum.clone())
+    }
+}
+
+impl Count for ExponentialHistogramAggregator {
+    fn count(&self) -> Result<u64> {
+        self.inner
+            .lock()
+            .map_err(From::from)
+            .map(|inner| inner.state.count)
+    }
+}
+
+impl MinMaxSumCount for ExponentialHistogramAggregator {}
+
+impl Quantile for ExponentialHistogramAggregator {
+    fn quantile(&self, q: f64) -> Result<Number> {
+        self.inner
+            .lock()
+            .map_err(From::from)
+            .and_then(|inner| inner.state.quantile(&inner.kind, q))
+    }
+}
+
+impl Distribution for ExponentialHistogramAggregator {}
+
+impl Aggregator for ExponentialHistogramAggregator {
+    fn update(&self, number: &Number, descriptor: &Descriptor) -> Result<()> {
+        self.inner.lock().map_err(From::from).map(|mut inner| {
+            let kind = descriptor.number_kind();
+            let max_size = inner.max_size;
+            let state = &mut inner.state;
+
+            state.update_min_max(kind, number, number);
+            let sum = state.sum.to_atomic();
+            sum.fetch_add(kind, number);
+            state.sum = sum.load();
+            state.count += 1;
+
+            let value = number.to_f64(kind);
+            if value == 0.0 {
+                state.zero_count += 1;
+                return;
+            }
+
+            let mut index = bucket_index(value.abs(), state.scale);
+            let buckets = if value > 0.0 {
+                &state.positive
+            } else {
+                &state.negative
+            };
+            if let Some((low, high)) = union(buckets.range(), Some((index, index))) {
+                let change = scale_change(low, high, max_size);
+                state.downscale(change);
+                index >>= change;
+            }
+
+            if value > 0.0 {
+                state.positive.increment(index, 1);
+            } else {
+                state.negative.increment(index, 1);
+            }
+        })
+    }
+
+    fn synchronized_move(
+        &self,
+        other: &Arc<dyn Aggregator + Send + Sync>,
+        _descriptor: &Descriptor,
+    ) -> Result<()> {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.inner.lock().map_err(From::from).and_then(|mut inner| {
+                other.inner.lock().map_err(From::from).map(|mut other| {
+                    let empty = State::empty(&inner.kind);
+                    other.state = mem::replace(&mut inner.state, empty);
+                })
+            })
+        } else {
+            Err(MetricsError::InconsistentAggregator(format!(
+                "Expected {:?}, got: {:?}",
+                self, other
+            )))
+        }
+    }
+
+    fn merge(&self, other: &(dyn Aggregator + Send + Sync), desc: &Descriptor) -> Result<()> {
+        if let Some(other) = other.as_any().downcast_ref::<Self>() {
+            self.inner.lock().map_err(From::from).and_then(|mut inner| {
+                other.inner.lock().map_err(From::from).map(|other| {
+                    let kind = desc.number_kind();
+                    let max_size = inner.max_size;
+                    let state = &mut inner.state;
+                    let other = &other.state;
+                    if other.count == 0 {
+                        return;
+                    }
+
+                    // Both histograms are combined at the lower of their scales,
+                    // lowered further if the combined buckets do not fit.
+                    let mut scale = state.scale.min(other.scale);
+                    let ranges = [
+                        (state.positive.range(), other.positive.range()),
+                        (state.negative.range(), other.negative.range()),
+                    ];
+                    let change = ranges
+                        .iter()
+                        .filter_map(|(ours, theirs)| {
+                            union(
+                                shift(*ours, state.scale - scale),
+                                shift(*theirs, other.scale - scale),
+                            )
+                        })
+                        .map(|(low, high)| scale_change(low, high, max_size))
+                        .max()
+                        .unwrap_or(0);
+                    scale -= change;
+                    state.downscale(state.scale - scale);
+
+                    let other_change = other.scale - scale;
+                    for (index, count) in other.positive.iter() {
+                        state.positive.increment(index >> other_change, count);
+                    }
+                    for (index, count) in other.negative.iter() {
+                        state.negative.increment(index >> other_change, count);
+                    }
+
+                    state.update_min_max(kind, &other.min, &other.max);
+                    let sum = state.sum.to_atomic();
+                    sum.fetch_add(kind, &other.sum);
+                    state.sum = sum.load();
+                    state.count += other.count;
+                    state.zero_count += other.zero_count;
+                })
+            })
+        } else {
+            Err(MetricsError::InconsistentAggregator(format!(
+                "Expected {:?}, got: {:?}",
+                self, other
+            )))
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::metrics::InstrumentKind;
+
+    fn descriptor(kind: NumberKind) -> Descriptor {
+        Descriptor::new(
+            "latency".to_string(),
+            "test".to_string(),
+            InstrumentKind::ValueRecorder,
+            kind,
+        )
+    }
+
+    fn record(agg: &ExponentialHistogramAggregator, desc: &Descriptor, values: &[f64]) {
+        for value in values {
+            agg.update(&Number::from(*value), desc).unwrap();
+        }
+    }
+
+    #[test]
+    fn bucket_boundaries() {
+        assert_eq!(bucket_index(1.0, 0), 0);
+        assert_eq!(bucket_index(3.0, 0), 1);
+        assert_eq!(bucket_index(4.0, 0), 2);
+        assert_eq!(bucket_index(0.5, 0), -1);
+        assert_eq!(bucket_index(4.0, -1), 1);
+        assert_eq!(bucket_index(2f64.sqrt() + 0.01, 1), 1);
+        assert_eq!(lower_boundary(2, 0), 4.0);
+        assert!((lower_boundary(1, 1) - 2f64.sqrt()).abs() < 1e-12);
+        assert_eq!(scale_change(0, 3, 4), 0);
+        assert_eq!(scale_change(0, 4, 4), 1);
+        assert_eq!(scale_change(-2, 1, 2), 1);
+        assert_eq!(scale_change(i32::MIN, i32::MAX, 2), 31);
+    }
+
+    #[test]
+    fn extreme_values() {
+        let desc = descriptor(NumberKind::F64);
+        let agg = exponential_histogram(&desc, 160);
+        let smallest = f64::from_bits(1);
+        record(&agg, &desc, &[smallest, f64::MAX, -f64::MAX]);
+
+        let other = exponential_histogram(&desc, 160);
+        record(&other, &desc, &[f64::MIN_POSITIVE, 1.0]);
+        agg.merge(&other, &desc).unwrap();
+
+        let inner = agg.inner.lock().unwrap();
+        assert!(inner.state.positive.counts.len() <= 160);
+        assert_eq!(inner.state.positive.counts.iter().sum::<u64>(), 4);
+        assert_eq!(inner.state.negative.counts.iter().sum::<u64>(), 1);
+        drop(inner);
+
+        assert_eq!(agg.count().unwrap(), 5);
+        assert_eq!(agg.min().unwrap().to_f64(&NumberKind::F64), -f64::MAX);
+        assert_eq!(agg.max().unwrap().to_f64(&NumberKind::F64), f64::MAX);
+        let median = agg.quantile(0.5).unwrap().to_f64(&NumberKind::F64);
+        assert!(median > 0.0 && median < 1.0, "{}", median);
+    }
+
+    #[test]
+    fn bounded_buckets() {
+        let desc = descriptor(NumberKind::F64);
+        let agg = exponential_histogram(&desc, 20);
+        let values: Vec<f64> = (1..=10_000).map(f64::from).collect();
+        record(&agg, &desc, &values);
+        record(&agg, &desc, &[0.0, -1.0, -100.0]);
+
+        let inner = agg.inner.lock().unwrap();
+        assert!(inner.state.positive.counts.len() <= 20);
+        assert!(inner.state.scale < MAX_SCALE);
+        assert_eq!(inner.state.positive.counts.iter().sum::<u64>(), 10_000);
+        assert_eq!(inner.state.negative.counts.iter().sum::<u64>(), 2);
+        assert_eq!(inner.state.zero_count, 1);
+        drop(inner);
+
+        assert_eq!(agg.count().unwrap(), 10_003);
+        assert_eq!(agg.min().unwrap().to_f64(&NumberKind::F64), -100.0);
+        assert_eq!(agg.max().unwrap().to_f64(&NumberKind::F64), 10_000.0);
+        assert_eq!(
+            agg.sum().unwrap().to_f64(&NumberKind::F64),
+            50_005_000.0 - 101.0
+        );
+    }
+
+    #[test]
+    fn quantile_estimates() {
+        let desc = descriptor(NumberKind::F64);
+        let agg = exponential_histogram(&desc, 160);
+        let values: Vec<f64> = (1..=1000).map(f64::from).collect();
+        record(&agg, &desc, &values);
+
+        for q in &[0.1f64, 0.5, 0.9, 0.99] {
+            let expected = (999.0 * q).ceil() + 1.0;
+            let estimate = agg.quantile(*q).unwrap().to_f64(&NumberKind::F64);
+            assert!(
+                (estimate - expected).abs() / expected < 0.05,
+                "q{}: {} != {}",
+                q,
+                estimate,
+                expected
+            );
+        }
+        assert_eq!(agg.quantile(0.0).unwrap().to_f64(&NumberKind::F64), 1.0);
+        assert_eq!(agg.quantile(1.0).unwrap().to_f64(&NumberKind::F64), 1000.0);
+        assert_eq!(
+            agg.quantile(1.5).unwrap_err(),
+            MetricsError::InvalidQuantile
+        );
+
+        let empty = exponential_histogram(&desc, 160);
+        assert_eq!(
+            empty.quantile(0.5).unwrap_err(),
+            MetricsError::NoDataCollected
+        );
+    }
+
+    #[test]
+    fn integer_quantiles() {
+        let desc = descriptor(NumberKind::I64);
+        let agg = exponential_histogram(&desc, 160);
+        for value in &[-5i64, -3, 0, 0, 2] {
+            agg.update(&Number::from(*value), &desc).unwrap();
+        }
+
+        assert_eq!(agg.quantile(0.25).unwrap().to_i64(&NumberKind::I64), -3);
+        assert_eq!(agg.quantile(0.5).unwrap().to_i64(&NumberKind::I64), 0);
+        assert_eq!(agg.quantile(0.9).unwrap().to_i64(&NumberKind::I64), 2);
+        assert_eq!(agg.sum().unwrap().to_i64(&NumberKind::I64), -6);
+    }
+
+    #[test]
+    fn merge_different_scales() {
+        let desc = descriptor(NumberKind::F64);
+        let small: Arc<dyn Aggregator + Send + Sync> = Arc::new(exponential_histogram(&desc, 8));
+        let large = exponential_histogram(&desc, 8);
+        let checkpoint = exponential_histogram(&desc, 8);
+
+        record(&checkpoint, &desc, &[1.5, 1.6]);
+        record(&large, &desc, &[0.001, 1000.0]);
+        // moves the state of `checkpoint` into `small`
+        checkpoint.synchronized_move(&small, &desc).unwrap();
+        assert_eq!(checkpoint.count().unwrap(), 0);
+
+        large.merge(small.as_ref(), &desc).unwrap();
+        let inner = large.inner.lock().unwrap();
+        assert!(inner.state.positive.counts.len() <= 8);
+        assert_eq!(inner.state.positive.counts.iter().sum::<u64>(), 4);
+        drop(inner);
+
+        assert_eq!(large.count().unwrap(), 4);
+        assert_eq!(large.min().unwrap().to_f64(&NumberKind::F64), 0.001);
+        assert_eq!(large.max().unwrap().to_f64(&NumberKind::F64), 1000.0);
+        // the median is in the merged bucket of 1.5 and 1.6, which starts at 1
+        let median = large.quantile(0.5).unwrap().to_f64(&NumberKind::F64);
+        let scale = large.inner.lock().unwrap().state.scale;
+        assert_eq!(
+            median,
+            (lower_boundary(0, scale) + lower_boundary(1, scale)) / 2.0
+        );
+    }
+}
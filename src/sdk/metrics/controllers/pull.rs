@@ -6,7 +6,7 @@ use crate::sdk::{
     metrics::{
         accumulator,
         processors::{self, BasicProcessor},
-        Accumulator,
+        Accumulator, View, DEFAULT_CARDINALITY_LIMIT,
     },
     Resource,
 };
@@ -89,6 +89,14 @@ pub struct PullControllerBuilder {
     /// the controller.
     resource: Option<Resource>,
 
+    /// Views customize the aggregation and export of the instruments they
+    /// match.
+    views: Vec<View>,
+
+    /// The maximum number of distinct label sets per instrument. The default
+    /// value is `DEFAULT_CARDINALITY_LIMIT`.
+    cardinality_limit: Option<usize>,
+
     /// CachePeriod is the period which a recently-computed result will be returned
     /// without gathering metric data again.
     ///
@@ -113,6 +121,8 @@ impl PullControllerBuilder {
             aggregator_selector,
             export_selector,
             resource: None,
+            views: Vec::new(),
+            cardinality_limit: None,
             cache_period: None,
             memory: true,
         }
@@ -126,6 +136,19 @@ impl PullControllerBuilder {
         }
     }
 
+    /// Configure the views used by this controller's processor
+    pub fn with_views(self, views: Vec<View>) -> Self {
+        PullControllerBuilder { views, ..self }
+    }
+
+    /// Configure the maximum number of distinct label sets per instrument
+    pub fn with_cardinality_limit(self, limit: usize) -> Self {
+        PullControllerBuilder {
+            cardinality_limit: Some(limit),
+            ..self
+        }
+    }
+
     /// Configure the cache period for this controller
     pub fn with_cache_period(self, period: Duration) -> Self {
         PullControllerBuilder {
@@ -143,13 +166,15 @@ impl PullControllerBuilder {
 
     /// Build a new `PullController` from the current configuration.
     pub fn build(self) -> PullController {
-        let processor = Arc::new(processors::basic(
-            self.aggregator_selector,
-            self.export_selector,
-            self.memory,
-        ));
+        let cardinality_limit = self.cardinality_limit.unwrap_or(DEFAULT_CARDINALITY_LIMIT);
+        let processor = Arc::new(
+            processors::basic(self.aggregator_selector, self.export_selector, self.memory)
+                .with_views(self.views)
+                .with_cardinality_limit(cardinality_limit),
+        );
 
         let accumulator = accumulator(processor.clone())
+            .with_cardinality_limit(cardinality_limit)
             .with_resource(self.resource.unwrap_or_default())
             .build();
         let provider = registry::meter_provider(Arc::new(accumulator.clone()));
@@ -5,7 +5,7 @@ use crate::sdk::{
     metrics::{
         self,
         processors::{self, BasicProcessor},
-        Accumulator,
+        Accumulator, View, DEFAULT_CARDINALITY_LIMIT,
     },
     Resource,
 };
@@ -40,6 +40,8 @@ where
         spawn,
         interval,
         resource: None,
+        views: Vec::new(),
+        cardinality_limit: None,
         stateful: None,
         period: None,
         timeout: None,
@@ -132,6 +134,8 @@ pub struct PushControllerBuilder<S, I> {
     spawn: S,
     interval: I,
     resource: Option<Resource>,
+    views: Vec<View>,
+    cardinality_limit: Option<usize>,
     stateful: Option<bool>,
     period: Option<time::Duration>,
     timeout: Option<time::Duration>,
@@ -167,11 +171,28 @@ where
         }
     }
 
+    /// Configure the views used by this controller's processor
+    pub fn with_views(self, views: Vec<View>) -> Self {
+        PushControllerBuilder { views, ..self }
+    }
+
+    /// Configure the maximum number of distinct label sets per instrument
+    pub fn with_cardinality_limit(self, limit: usize) -> Self {
+        PushControllerBuilder {
+            cardinality_limit: Some(limit),
+            ..self
+        }
+    }
+
     /// Build a new `PushController` with this configuration.
     pub fn build(self) -> PushController {
-        let processor = processors::basic(self.aggregator_selector, self.export_selector, false);
+        let cardinality_limit = self.cardinality_limit.unwrap_or(DEFAULT_CARDINALITY_LIMIT);
+        let processor = processors::basic(self.aggregator_selector, self.export_selector, false)
+            .with_views(self.views)
+            .with_cardinality_limit(cardinality_limit);
         let processor = Arc::new(processor);
-        let mut accumulator = metrics::accumulator(processor.clone());
+        let mut accumulator =
+            metrics::accumulator(processor.clone()).with_cardinality_limit(cardinality_limit);
 
         if let Some(resource) = self.resource {
             accumulator = accumulator.with_resource(resource);
@@ -1,11 +1,12 @@
 //! # OpenTelemetry Metrics SDK
 use crate::api::metrics::{
     sdk_api::{self, InstrumentCore as _, SyncBoundInstrumentCore as _},
-    AsyncRunner, AtomicNumber, Descriptor, Measurement, Number, NumberKind, Observation, Result,
+    AsyncRunner, AtomicNumber, Descriptor, Measurement, MetricsError, Number, NumberKind,
+    Observation, Result,
 };
 use crate::api::{
     labels::{hash_labels, LabelSet},
-    Context, KeyValue,
+    Context, Key, KeyValue, Value,
 };
 use crate::global;
 use crate::sdk::{
@@ -20,20 +21,46 @@ use std::any::Any;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::sync::atomic::{self, AtomicUsize};
 use std::sync::{Arc, Mutex};
 
 pub mod aggregators;
 pub mod controllers;
 pub mod processors;
 pub mod selectors;
+pub mod views;
 
 pub use controllers::{PullController, PushController, PushControllerWorker};
+pub use views::{Aggregation, View};
+
+/// The default maximum number of distinct label sets recorded per instrument.
+pub const DEFAULT_CARDINALITY_LIMIT: usize = 2000;
+
+/// The key of the label that identifies the overflow series of an instrument.
+///
+/// Once an instrument reaches its cardinality limit, measurements with new
+/// label sets are recorded in a single series with the label set
+/// `otel.metric.overflow=true`.
+pub const OVERFLOW_LABEL_KEY: &str = "otel.metric.overflow";
+
+fn overflow_labels() -> LabelSet {
+    LabelSet::from_labels(vec![KeyValue::new(OVERFLOW_LABEL_KEY, true)])
+}
+
+/// The overflow series does not count towards the cardinality limit.
+fn is_overflow(labels: &LabelSet) -> bool {
+    labels.len() == 1
+        && labels
+            .iter()
+            .all(|(key, _)| key.as_str() == OVERFLOW_LABEL_KEY)
+}
 
 /// Creates a new accumulator builder
 pub fn accumulator(processor: Arc<dyn Processor + Send + Sync>) -> AccumulatorBuilder {
     AccumulatorBuilder {
         processor,
         resource: None,
+        cardinality_limit: None,
     }
 }
 
@@ -42,6 +69,7 @@ pub fn accumulator(processor: Arc<dyn Processor + Send + Sync>) -> AccumulatorBu
 pub struct AccumulatorBuilder {
     processor: Arc<dyn Processor + Send + Sync>,
     resource: Option<Resource>,
+    cardinality_limit: Option<usize>,
 }
 
 impl AccumulatorBuilder {
@@ -53,11 +81,24 @@ impl AccumulatorBuilder {
         }
     }
 
+    /// The maximum number of distinct label sets recorded per instrument,
+    /// `DEFAULT_CARDINALITY_LIMIT` by default.
+    ///
+    /// Past the limit, measurements with new label sets are recorded in the
+    /// instrument's overflow series.
+    pub fn with_cardinality_limit(self, limit: usize) -> Self {
+        AccumulatorBuilder {
+            cardinality_limit: Some(limit),
+            ..self
+        }
+    }
+
     /// Create a new accumulator from this configuration
     pub fn build(self) -> Accumulator {
         Accumulator(Arc::new(AccumulatorCore::new(
             self.processor,
             self.resource.unwrap_or_default(),
+            self.cardinality_limit.unwrap_or(DEFAULT_CARDINALITY_LIMIT),
         )))
     }
 }
@@ -123,16 +164,23 @@ struct AccumulatorCore {
     processor: Arc<dyn Processor + Send + Sync>,
     /// The resource applied to all records in this Accumulator.
     resource: Resource,
+    /// The maximum number of distinct label sets recorded per instrument.
+    cardinality_limit: usize,
 }
 
 impl AccumulatorCore {
-    fn new(processor: Arc<dyn Processor + Send + Sync>, resource: Resource) -> Self {
+    fn new(
+        processor: Arc<dyn Processor + Send + Sync>,
+        resource: Resource,
+        cardinality_limit: usize,
+    ) -> Self {
         AccumulatorCore {
             current: dashmap::DashMap::new(),
             async_instruments: Mutex::new(AsyncInstrumentState::default()),
             current_epoch: NumberKind::U64.zero().to_atomic(),
             processor,
             resource,
+            cardinality_limit,
         }
     }
 
@@ -177,6 +225,9 @@ impl AccumulatorCore {
 
     fn collect_sync_instruments(&self, locked_processor: &mut dyn LockedProcessor) -> usize {
         let mut checkpointed = 0;
+        // Records are removed once the iteration no longer holds locks on the
+        // map.
+        let mut idle = Vec::new();
 
         for element in self.current.iter() {
             let (key, value) = element.pair();
@@ -188,18 +239,33 @@ impl AccumulatorCore {
                 // checkpoint and continue.
                 checkpointed += self.checkpoint_record(value, locked_processor);
                 value.collected_count.store(mods);
-            } else {
+            } else if Arc::strong_count(value) == 1 {
                 // Having no updates since last collection, try to remove if
                 // there are no bound handles
-                if Arc::strong_count(&value) == 1 {
-                    self.current.remove(key);
-
-                    // There's a potential race between loading collected count and
-                    // loading the strong count in this function.  Since this is the
-                    // last we'll see of this record, checkpoint.
-                    if mods.partial_cmp(&NumberKind::U64, coll) != Some(Ordering::Equal) {
-                        checkpointed += self.checkpoint_record(value, locked_processor);
-                    }
+                idle.push(key.clone());
+            }
+        }
+
+        for key in idle {
+            let removed = self
+                .current
+                .remove_if(&key, |_key, value| Arc::strong_count(value) == 1);
+            if let Some((_key, value)) = removed {
+                if !value.overflow {
+                    value
+                        .instrument
+                        .instrument
+                        .label_sets
+                        .fetch_sub(1, atomic::Ordering::SeqCst);
+                }
+
+                // There's a potential race between loading collected count and
+                // loading the strong count in this function.  Since this is the
+                // last we'll see of this record, checkpoint.
+                let mods = &value.update_count.load();
+                let coll = &value.collected_count.load();
+                if mods.partial_cmp(&NumberKind::U64, coll) != Some(Ordering::Equal) {
+                    checkpointed += self.checkpoint_record(&value, locked_processor);
                 }
             }
         }
@@ -288,27 +354,52 @@ struct SyncInstrument {
 }
 
 impl SyncInstrument {
-    fn acquire_handle(&self, labels: &[KeyValue]) -> Arc<Record> {
+    fn map_key<'a, I: IntoIterator<Item = (&'a Key, &'a Value)>>(&self, labels: I) -> MapKey {
         let mut hasher = FnvHasher::default();
         self.instrument
             .descriptor
             .attribute_hash()
             .hash(&mut hasher);
 
-        hash_labels(&mut hasher, labels.iter().map(|kv| (&kv.key, &kv.value)));
+        hash_labels(&mut hasher, labels);
 
-        let map_key = MapKey {
+        MapKey {
             instrument_hash: hasher.finish(),
-        };
+        }
+    }
+
+    fn acquire_handle(&self, labels: &[KeyValue]) -> Arc<Record> {
+        let map_key = self.map_key(labels.iter().map(|kv| (&kv.key, &kv.value)));
         let current = &self.instrument.meter.0.current;
         if let Some(existing_record) = current.get(&map_key) {
             return existing_record.value().clone();
         }
 
+        let label_sets = self.instrument.label_sets.load(atomic::Ordering::SeqCst);
+        if label_sets >= self.instrument.meter.0.cardinality_limit {
+            let overflow_labels = overflow_labels();
+            let map_key = self.map_key(&overflow_labels);
+            if let Some(existing_record) = current.get(&map_key) {
+                return existing_record.value().clone();
+            }
+            self.instrument.report_overflow();
+
+            return self.insert_record(map_key, overflow_labels, true);
+        }
+
+        self.insert_record(
+            map_key,
+            LabelSet::from_labels(labels.iter().cloned()),
+            false,
+        )
+    }
+
+    fn insert_record(&self, map_key: MapKey, labels: LabelSet, overflow: bool) -> Arc<Record> {
         let record = Arc::new(Record {
             update_count: NumberKind::U64.zero().to_atomic(),
             collected_count: NumberKind::U64.zero().to_atomic(),
-            labels: LabelSet::from_labels(labels.iter().cloned()),
+            labels,
+            overflow,
             instrument: self.clone(),
             current: self
                 .instrument
@@ -325,7 +416,19 @@ impl SyncInstrument {
                 .aggregation_selector()
                 .aggregator_for(&self.instrument.descriptor),
         });
-        current.insert(map_key, record.clone());
+        if self
+            .instrument
+            .meter
+            .0
+            .current
+            .insert(map_key, record.clone())
+            .is_none()
+            && !overflow
+        {
+            self.instrument
+                .label_sets
+                .fetch_add(1, atomic::Ordering::SeqCst);
+        }
 
         record
     }
@@ -381,8 +484,33 @@ impl AsyncInstrument {
     fn get_recorder(&self, labels: &LabelSet) -> Option<Arc<dyn Aggregator + Send + Sync>> {
         self.recorders.lock().map_or(None, |mut recorders| {
             let mut hasher = FnvHasher::default();
-            hash_labels(&mut hasher, labels.into_iter());
-            let label_hash = hasher.finish();
+            hash_labels(&mut hasher, labels);
+            let mut label_hash = hasher.finish();
+
+            // Past the cardinality limit, new label sets are observed in the
+            // overflow series.
+            let mut overflow = None;
+            let contains = |recorders: &Option<HashMap<u64, LabeledRecorder>>, hash: u64| {
+                recorders
+                    .as_ref()
+                    .is_some_and(|rec| rec.contains_key(&hash))
+            };
+            let label_sets = recorders.as_ref().map_or(0, |rec| rec.len());
+            let limit = self.instrument.meter.0.cardinality_limit;
+            if label_sets >= limit && !contains(&recorders, label_hash) {
+                let overflow_labels = overflow_labels();
+                let mut hasher = FnvHasher::default();
+                hash_labels(&mut hasher, &overflow_labels);
+                let overflow_hash = hasher.finish();
+                let has_overflow = contains(&recorders, overflow_hash);
+                if label_sets - has_overflow as usize >= limit {
+                    if !has_overflow {
+                        self.instrument.report_overflow();
+                    }
+                    label_hash = overflow_hash;
+                    overflow = Some(overflow_labels);
+                }
+            }
             if let Some(recorder) = recorders.as_mut().and_then(|rec| rec.get_mut(&label_hash)) {
                 let current_epoch = self
                     .instrument
@@ -391,7 +519,9 @@ impl AsyncInstrument {
                     .current_epoch
                     .load()
                     .to_u64(&NumberKind::U64);
-                if recorder.observed_epoch == current_epoch {
+                // Observations of different label sets in the overflow series
+                // are merged into its recorder.
+                if recorder.observed_epoch == current_epoch && overflow.is_none() {
                     // last value wins for Observers, so if we see the same labels
                     // in the current epoch, we replace the old recorder
                     return self
@@ -431,7 +561,7 @@ impl AsyncInstrument {
                 label_hash,
                 LabeledRecorder {
                     observed: recorder.clone(),
-                    labels: labels.clone(),
+                    labels: overflow.unwrap_or_else(|| labels.clone()),
                     observed_epoch,
                 },
             );
@@ -471,6 +601,10 @@ struct Record {
     /// TODO: look at perf here.
     labels: LabelSet,
 
+    /// Whether this is the overflow series of the instrument, which does not
+    /// count towards the cardinality limit.
+    overflow: bool,
+
     /// The corresponding instrument.
     instrument: SyncInstrument,
 
@@ -503,6 +637,17 @@ impl sdk_api::SyncBoundInstrumentCore for Record {
 struct Instrument {
     descriptor: Descriptor,
     meter: Accumulator,
+    /// The number of label sets of this instrument in the `Accumulator.current`
+    /// map, bounded by the accumulator's cardinality limit.
+    label_sets: AtomicUsize,
+}
+
+impl Instrument {
+    fn report_overflow(&self) {
+        global::handle_error(MetricsError::CardinalityLimit(
+            self.descriptor.name().to_string(),
+        ));
+    }
 }
 
 impl sdk_api::InstrumentCore for Instrument {
@@ -520,6 +665,7 @@ impl sdk_api::MeterCore for Accumulator {
             instrument: Arc::new(Instrument {
                 descriptor,
                 meter: self.clone(),
+                label_sets: AtomicUsize::new(0),
             }),
         }))
     }
@@ -553,6 +699,7 @@ impl sdk_api::MeterCore for Accumulator {
             instrument: Arc::new(Instrument {
                 descriptor,
                 meter: self.clone(),
+                label_sets: AtomicUsize::new(0),
             }),
             recorders: Arc::new(Mutex::new(None)),
         });
@@ -562,3 +709,157 @@ impl sdk_api::MeterCore for Accumulator {
         Ok(instrument)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::labels;
+    use crate::api::metrics::{sdk_api::SyncInstrumentCore, InstrumentKind};
+    use crate::sdk::export::metrics::{Checkpointer, ExportKind};
+    use crate::sdk::metrics::processors::BasicProcessor;
+
+    fn setup(
+        name: &str,
+        cardinality_limit: usize,
+    ) -> (
+        Arc<BasicProcessor>,
+        Accumulator,
+        Arc<dyn SyncInstrumentCore + Send + Sync>,
+    ) {
+        let processor = Arc::new(processors::basic(
+            Box::new(selectors::simple::Selector::Inexpensive),
+            Box::new(ExportKind::Delta),
+            false,
+        ));
+        let accumulator = accumulator(processor.clone())
+            .with_cardinality_limit(cardinality_limit)
+            .build();
+        let instrument = sdk_api::MeterCore::new_sync_instrument(
+            &accumulator,
+            Descriptor::new(
+                name.to_string(),
+                "test".to_string(),
+                InstrumentKind::Counter,
+                NumberKind::U64,
+            ),
+        )
+        .unwrap();
+
+        (processor, accumulator, instrument)
+    }
+
+    /// Collect the accumulator, returning the exported label sets.
+    fn collect(processor: &BasicProcessor, accumulator: &Accumulator) -> Vec<String> {
+        let encoder = labels::default_encoder();
+        let mut locked = processor.lock().unwrap();
+        locked.start_collection();
+        accumulator.0.collect(&mut locked);
+        locked.finish_collection().unwrap();
+
+        let mut exported = Vec::new();
+        locked
+            .checkpoint_set()
+            .try_for_each(&ExportKind::Delta, &mut |record| {
+                exported.push(record.labels().encoded(Some(encoder.as_ref())));
+                Ok(())
+            })
+            .unwrap();
+        exported.sort();
+        exported
+    }
+
+    fn label_sets(instrument: &Arc<dyn SyncInstrumentCore + Send + Sync>) -> usize {
+        instrument
+            .as_any()
+            .downcast_ref::<SyncInstrument>()
+            .unwrap()
+            .instrument
+            .label_sets
+            .load(atomic::Ordering::SeqCst)
+    }
+
+    #[test]
+    fn idle_records_are_removed() {
+        let (processor, accumulator, instrument) = setup("idle", 10);
+        instrument.record_one(1u64.into(), &[KeyValue::new("path", "/a")]);
+        assert_eq!(label_sets(&instrument), 1);
+
+        assert_eq!(collect(&processor, &accumulator), vec!["path=/a"]);
+        assert_eq!(label_sets(&instrument), 1);
+
+        assert!(collect(&processor, &accumulator).is_empty());
+        assert_eq!(label_sets(&instrument), 0);
+        assert!(accumulator.0.current.is_empty());
+    }
+
+    #[test]
+    fn bound_handles_overflow() {
+        let (processor, accumulator, instrument) = setup("bound", 1);
+        let a = instrument.bind(&[KeyValue::new("path", "/a")]);
+        let b = instrument.bind(&[KeyValue::new("path", "/b")]);
+        a.record_one(1u64.into());
+        b.record_one(1u64.into());
+
+        assert_eq!(
+            collect(&processor, &accumulator),
+            vec!["otel.metric.overflow=true", "path=/a"]
+        );
+        // bound records are kept while idle
+        assert!(collect(&processor, &accumulator).is_empty());
+        assert_eq!(accumulator.0.current.len(), 2);
+        assert_eq!(label_sets(&instrument), 1);
+
+        drop(a);
+        drop(b);
+        collect(&processor, &accumulator);
+        assert!(accumulator.0.current.is_empty());
+        assert_eq!(label_sets(&instrument), 0);
+
+        let c = instrument.bind(&[KeyValue::new("path", "/c")]);
+        c.record_one(1u64.into());
+        assert_eq!(collect(&processor, &accumulator), vec!["path=/c"]);
+    }
+
+    #[test]
+    fn overflow_is_reported_again_after_eviction() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let handler_errors = errors.clone();
+        global::set_error_handler(move |err| {
+            handler_errors.lock().unwrap().push(err.to_string());
+        })
+        .unwrap();
+        let overflows = || {
+            errors
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|err| err.contains("instrument overflowing"))
+                .count()
+        };
+
+        let (processor, accumulator, instrument) = setup("overflowing", 1);
+        let record = || {
+            instrument.record_one(1u64.into(), &[KeyValue::new("path", "/a")]);
+            instrument.record_one(1u64.into(), &[KeyValue::new("path", "/b")]);
+            instrument.record_one(1u64.into(), &[KeyValue::new("path", "/c")]);
+        };
+
+        record();
+        assert_eq!(overflows(), 1);
+        assert_eq!(
+            collect(&processor, &accumulator),
+            vec!["otel.metric.overflow=true", "path=/a"]
+        );
+
+        // the overflow record is evicted with the idle ones
+        assert!(collect(&processor, &accumulator).is_empty());
+        assert!(accumulator.0.current.is_empty());
+
+        record();
+        assert_eq!(overflows(), 2);
+        assert_eq!(
+            collect(&processor, &accumulator),
+            vec!["otel.metric.overflow=true", "path=/a"]
+        );
+    }
+}
@@ -2,16 +2,19 @@ use crate::api::{
     labels::{hash_labels, LabelSet},
     metrics::{Descriptor, MetricsError, Result},
 };
+use crate::global;
 use crate::sdk::{
     export::metrics::{
         self, Accumulation, Aggregator, AggregatorSelector, CheckpointSet, Checkpointer,
         ExportKind, ExportKindSelector, LockedProcessor, Processor, Record, Subtractor,
     },
-    metrics::aggregators::SumAggregator,
+    metrics::{
+        aggregators::SumAggregator, is_overflow, overflow_labels, View, DEFAULT_CARDINALITY_LIMIT,
+    },
     Resource,
 };
 use fnv::FnvHasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::SystemTime;
@@ -25,6 +28,8 @@ pub fn basic(
     BasicProcessor {
         aggregator_selector,
         export_selector,
+        views: Vec::new(),
+        cardinality_limit: DEFAULT_CARDINALITY_LIMIT,
         state: Mutex::new(BasicProcessorState::with_memory(memory)),
     }
 }
@@ -34,10 +39,52 @@ pub fn basic(
 pub struct BasicProcessor {
     aggregator_selector: Box<dyn AggregatorSelector + Send + Sync>,
     export_selector: Box<dyn ExportKindSelector + Send + Sync>,
+    views: Vec<View>,
+    cardinality_limit: usize,
     state: Mutex<BasicProcessorState>,
 }
 
 impl BasicProcessor {
+    /// Customize the aggregation and export of the instruments matched by
+    /// these views. The first view that matches an instrument applies.
+    ///
+    /// Views that rename instruments matched with wildcards, or that rename
+    /// instruments to the name of a previous view, could export several
+    /// instruments under the same name. They are ignored and reported to the
+    /// global error handler.
+    pub fn with_views(self, views: Vec<View>) -> Self {
+        let mut names = HashSet::new();
+        let views = views
+            .into_iter()
+            .filter(|view| match view.name() {
+                Some(name) if view.has_wildcards() || !names.insert(name.to_string()) => {
+                    global::handle_error(MetricsError::InvalidView(
+                        view.instrument_name().to_string(),
+                    ));
+                    false
+                }
+                _ => true,
+            })
+            .collect();
+        BasicProcessor { views, ..self }
+    }
+
+    /// The maximum number of distinct label sets kept per instrument,
+    /// `DEFAULT_CARDINALITY_LIMIT` by default.
+    ///
+    /// Past the limit, accumulations with new label sets are merged into the
+    /// instrument's overflow series.
+    pub fn with_cardinality_limit(self, cardinality_limit: usize) -> Self {
+        BasicProcessor {
+            cardinality_limit,
+            ..self
+        }
+    }
+
+    fn view_for(&self, descriptor: &Descriptor) -> Option<&View> {
+        self.views.iter().find(|view| view.matches(descriptor))
+    }
+
     /// Lock this processor to return a mutable locked processor
     pub fn lock(&self) -> Result<BasicLockedProcessor<'_>> {
         self.state
@@ -52,7 +99,15 @@ impl BasicProcessor {
 
 impl Processor for BasicProcessor {
     fn aggregation_selector(&self) -> &dyn AggregatorSelector {
-        self.aggregator_selector.as_ref()
+        self
+    }
+}
+
+impl AggregatorSelector for BasicProcessor {
+    fn aggregator_for(&self, descriptor: &Descriptor) -> Option<Arc<dyn Aggregator + Send + Sync>> {
+        self.view_for(descriptor)
+            .and_then(|view| view.aggregator_for(descriptor))
+            .or_else(|| self.aggregator_selector.aggregator_for(descriptor))
     }
 }
 
@@ -70,11 +125,45 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
         }
 
         let desc = accumulation.descriptor();
-        let mut hasher = FnvHasher::default();
-        desc.attribute_hash().hash(&mut hasher);
-        hash_labels(&mut hasher, accumulation.labels().into_iter());
-        hash_labels(&mut hasher, accumulation.resource().into_iter());
-        let key = StateKey(hasher.finish());
+        let view = self.parent.view_for(desc);
+        let exported_desc = view.map(|view| view.descriptor(desc));
+        let exported_desc = exported_desc.as_ref().unwrap_or(desc);
+        let view_labels = view.and_then(|view| view.labels(accumulation.labels()));
+        let mut labels = view_labels
+            .as_ref()
+            .unwrap_or_else(|| accumulation.labels());
+
+        let state_key = |labels: &LabelSet| {
+            let mut hasher = FnvHasher::default();
+            exported_desc.attribute_hash().hash(&mut hasher);
+            hash_labels(&mut hasher, labels.into_iter());
+            hash_labels(&mut hasher, accumulation.resource().into_iter());
+            StateKey(hasher.finish())
+        };
+        let mut key = state_key(labels);
+
+        // Past the cardinality limit, new label sets are merged into the
+        // overflow series.
+        let overflow;
+        let label_sets = self
+            .state
+            .label_sets
+            .get(&exported_desc.attribute_hash())
+            .copied()
+            .unwrap_or(0);
+        if label_sets >= self.parent.cardinality_limit
+            && !is_overflow(labels)
+            && !self.state.values.contains_key(&key)
+        {
+            overflow = overflow_labels();
+            labels = &overflow;
+            key = state_key(labels);
+            if !self.state.values.contains_key(&key) {
+                global::handle_error(MetricsError::CardinalityLimit(
+                    exported_desc.name().to_string(),
+                ));
+            }
+        }
         let agg = accumulation.aggregator();
         let finished_collection = self.state.finished_collection;
         if let Some(value) = self.state.values.get_mut(&key) {
@@ -156,11 +245,18 @@ impl<'a> LockedProcessor for BasicLockedProcessor<'a> {
             None
         };
 
+        if !is_overflow(labels) {
+            *self
+                .state
+                .label_sets
+                .entry(exported_desc.attribute_hash())
+                .or_insert(0) += 1;
+        }
         self.state.values.insert(
             key,
             StateValue {
-                descriptor: desc.clone(),
-                labels: accumulation.labels().clone(),
+                descriptor: exported_desc.clone(),
+                labels: labels.clone(),
                 resource: accumulation.resource().clone(),
                 current_owned: false,
                 current: agg.clone(),
@@ -198,7 +294,9 @@ impl Checkpointer for BasicLockedProcessor<'_> {
 
         let mut result = Ok(());
 
-        self.state.values.retain(|_key, value| {
+        let state = &mut *self.state;
+        let label_sets = &mut state.label_sets;
+        state.values.retain(|_key, value| {
             // Return early if previous error
             if result.is_err() {
                 return true;
@@ -215,7 +313,13 @@ impl Checkpointer for BasicLockedProcessor<'_> {
                 // If this processor does not require memory, stale, stateless
                 // entries can be removed. This implies that they were not updated
                 // over the previous full collection interval.
-                if stale && stateless && has_memory {
+                if stale && stateless && !has_memory {
+                    if !is_overflow(&value.labels) {
+                        if let Some(count) = label_sets.get_mut(&value.descriptor.attribute_hash())
+                        {
+                            *count -= 1;
+                        }
+                    }
                     return false;
                 }
             }
@@ -270,6 +374,8 @@ struct BasicProcessorConfig {
 struct BasicProcessorState {
     config: BasicProcessorConfig,
     values: HashMap<StateKey, StateValue>,
+    /// The number of label sets in `values` per instrument descriptor hash.
+    label_sets: HashMap<u64, usize>,
     // Note: the timestamp logic currently assumes all exports are deltas.
     process_start: SystemTime,
     interval_start: SystemTime,
@@ -291,6 +397,7 @@ impl Default for BasicProcessorState {
         BasicProcessorState {
             config: BasicProcessorConfig::default(),
             values: HashMap::default(),
+            label_sets: HashMap::default(),
             process_start: SystemTime::now(),
             interval_start: SystemTime::now(),
             interval_end: SystemTime::now(),
@@ -420,3 +527,87 @@ struct StateValue {
     /// the last cumulative value.
     cumulative: Option<Arc<dyn Aggregator + Send + Sync>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{
+        labels,
+        metrics::{InstrumentKind, NumberKind},
+        KeyValue,
+    };
+    use crate::sdk::metrics::{aggregators, selectors};
+
+    #[test]
+    fn renaming_views_must_match_one_instrument() {
+        let processor = basic(
+            Box::new(selectors::simple::Selector::Inexpensive),
+            Box::new(ExportKind::Cumulative),
+            false,
+        )
+        .with_views(vec![
+            View::new("http.*.duration").with_name("request_duration"),
+            View::new("db.calls").with_name("calls"),
+            View::new("rpc.calls").with_name("calls"),
+            View::new("http.*").with_label_keys(vec![]),
+        ]);
+
+        let views = processor
+            .views
+            .iter()
+            .map(|view| view.instrument_name())
+            .collect::<Vec<_>>();
+        assert_eq!(views, vec!["db.calls", "http.*"]);
+    }
+
+    #[test]
+    fn idle_label_sets_are_evicted_without_memory() {
+        let processor = basic(
+            Box::new(selectors::simple::Selector::Inexpensive),
+            Box::new(ExportKind::Delta),
+            false,
+        )
+        .with_cardinality_limit(1);
+        let descriptor = Descriptor::new(
+            "requests".to_string(),
+            "test".to_string(),
+            InstrumentKind::Counter,
+            NumberKind::U64,
+        );
+        let resource = Resource::default();
+        let aggregator: Arc<dyn Aggregator + Send + Sync> = Arc::new(aggregators::sum());
+        let encoder = labels::default_encoder();
+
+        let collect = |path: Option<&'static str>| {
+            let mut locked = processor.lock().unwrap();
+            locked.start_collection();
+            if let Some(path) = path {
+                let labels = LabelSet::from_labels(vec![KeyValue::new("path", path)]);
+                locked
+                    .process(Accumulation::new(
+                        &descriptor,
+                        &labels,
+                        &resource,
+                        &aggregator,
+                    ))
+                    .unwrap();
+            }
+            locked.finish_collection().unwrap();
+
+            let mut exported = Vec::new();
+            locked
+                .checkpoint_set()
+                .try_for_each(&ExportKind::Delta, &mut |record| {
+                    exported.push(record.labels().encoded(Some(encoder.as_ref())));
+                    Ok(())
+                })
+                .unwrap();
+            exported
+        };
+
+        assert_eq!(collect(Some("/a")), vec!["path=/a"]);
+        // the idle label set is removed, and no longer counts towards the limit
+        assert!(collect(None).is_empty());
+        assert_eq!(collect(Some("/b")), vec!["path=/b"]);
+    }
+}
@@ -19,6 +19,11 @@ pub enum Selector {
     /// for metrics. This selector uses more memory than `Inexpensive` because
     /// it uses a counter per bucket.
     Histogram(Vec<f64>),
+    /// A simple aggregation selector that uses sum, and exponential histogram
+    /// aggregators with at most the given number of buckets for metrics. This
+    /// selector estimates quantiles in bounded memory, without configuring
+    /// bucket boundaries up front.
+    ExponentialHistogram(usize),
 }
 
 impl AggregatorSelector for Selector {
@@ -42,6 +47,12 @@ impl AggregatorSelector for Selector {
                 }
                 _ => Some(Arc::new(aggregators::sum())),
             },
+            Selector::ExponentialHistogram(max_size) => match descriptor.instrument_kind() {
+                InstrumentKind::ValueObserver | InstrumentKind::ValueRecorder => Some(Arc::new(
+                    aggregators::exponential_histogram(descriptor, *max_size),
+                )),
+                _ => Some(Arc::new(aggregators::sum())),
+            },
         }
     }
 }
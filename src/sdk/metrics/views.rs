@@ -0,0 +1,339 @@
+//! # Metric Views
+//!
+//! Views customize how the instruments they match are aggregated and
+//! exported, overriding the processor's `AggregatorSelector` per instrument.
+//! A view can change:
+//!
+//! - the exported name and description of the instrument
+//! - the aggregation, e.g. a histogram with boundaries for this instrument
+//! - the label keys to keep, the other labels are aggregated away
+//!
+//! Views match instruments by name, which may contain `*` and `?` wildcards,
+//! and optionally by the name of the instrumentation library. The first
+//! matching view applies. Exported names must be unique, so views that rename
+//! instruments must match them by their exact name and use distinct names.
+//! Processors ignore the other renaming views and report them to the global
+//! error handler.
+//!
+//! # Examples
+//!
+//! ```
+//! use opentelemetry::api::Key;
+//! use opentelemetry::sdk::metrics::{processors, selectors, Aggregation, View};
+//! use opentelemetry::sdk::export::metrics::ExportKind;
+//!
+//! let processor = processors::basic(
+//!     Box::new(selectors::simple::Selector::Inexpensive),
+//!     Box::new(ExportKind::Cumulative),
+//!     false,
+//! )
+//! .with_views(vec![
+//!     View::new("http.server.duration")
+//!         .with_aggregation(Aggregation::Histogram(vec![0.01, 0.1, 1.0]))
+//!         .with_label_keys(vec![Key::new("http.method")]),
+//!     View::new("db.client.calls")
+//!         .with_instrumentation_name("my-db-client")
+//!         .with_name("database.calls"),
+//! ]);
+//! ```
+use crate::api::labels::LabelSet;
+use crate::api::metrics::Descriptor;
+use crate::api::{Key, KeyValue};
+use crate::sdk::export::metrics::Aggregator;
+use crate::sdk::metrics::aggregators;
+use std::sync::Arc;
+
+/// The aggregation of the instruments that match a [`View`].
+///
+/// [`View`]: struct.View.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum Aggregation {
+    /// Sum of the measurements.
+    Sum,
+    /// The last measurement.
+    LastValue,
+    /// Min, max, sum and count of the measurements.
+    MinMaxSumCount,
+    /// All measurements, to compute exact quantiles.
+    Exact,
+    /// Count of the measurements per bucket, with the given bucket
+    /// boundaries.
+    Histogram(Vec<f64>),
+    /// Count of the measurements per exponential bucket, with at most the
+    /// given number of buckets, to estimate quantiles.
+    ExponentialHistogram(usize),
+}
+
+impl Aggregation {
+    fn aggregator(&self, descriptor: &Descriptor) -> Arc<dyn Aggregator + Send + Sync> {
+        match self {
+            Aggregation::Sum => Arc::new(aggregators::sum()),
+            Aggregation::LastValue => Arc::new(aggregators::last_value()),
+            Aggregation::MinMaxSumCount => Arc::new(aggregators::min_max_sum_count(descriptor)),
+            Aggregation::Exact => Arc::new(aggregators::array()),
+            Aggregation::Histogram(boundaries) => {
+                Arc::new(aggregators::histogram(descriptor, boundaries))
+            }
+            Aggregation::ExponentialHistogram(max_size) => {
+                Arc::new(aggregators::exponential_histogram(descriptor, *max_size))
+            }
+        }
+    }
+}
+
+/// Overrides the name, description, aggregation and labels of the
+/// instruments it matches.
+#[derive(Clone, Debug)]
+pub struct View {
+    instrument_name: String,
+    instrumentation_name: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+    aggregation: Option<Aggregation>,
+    label_keys: Option<Vec<Key>>,
+}
+
+impl View {
+    /// Create a view that matches instruments by name.
+    ///
+    /// The name may contain `*` wildcards, which match any sequence of
+    /// characters, and `?` wildcards, which match a single character.
+    pub fn new<T: Into<String>>(instrument_name: T) -> Self {
+        View {
+            instrument_name: instrument_name.into(),
+            instrumentation_name: None,
+            name: None,
+            description: None,
+            aggregation: None,
+            label_keys: None,
+        }
+    }
+
+    /// Only match instruments created by meters with this instrumentation
+    /// name.
+    pub fn with_instrumentation_name<T: Into<String>>(self, instrumentation_name: T) -> Self {
+        View {
+            instrumentation_name: Some(instrumentation_name.into()),
+            ..self
+        }
+    }
+
+    /// Export the matching instrument with this name.
+    ///
+    /// Names must be unique, so the view must match a single instrument: a
+    /// view whose instrument name has wildcards, or that reuses the name of
+    /// another view, is rejected when it is given to a processor.
+    pub fn with_name<T: Into<String>>(self, name: T) -> Self {
+        View {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Export the matching instruments with this description.
+    pub fn with_description<T: Into<String>>(self, description: T) -> Self {
+        View {
+            description: Some(description.into()),
+            ..self
+        }
+    }
+
+    /// Aggregate the matching instruments with this aggregation, instead of
+    /// the one of the processor's `AggregatorSelector`.
+    pub fn with_aggregation(self, aggregation: Aggregation) -> Self {
+        View {
+            aggregation: Some(aggregation),
+            ..self
+        }
+    }
+
+    /// Only keep the labels with these keys, measurements that only differ in
+    /// other labels are aggregated together.
+    pub fn with_label_keys(self, label_keys: Vec<Key>) -> Self {
+        View {
+            label_keys: Some(label_keys),
+            ..self
+        }
+    }
+
+    /// Whether this view applies to the instrument.
+    pub fn matches(&self, descriptor: &Descriptor) -> bool {
+        self.instrumentation_name
+            .as_ref()
+            .is_none_or(|name| name == descriptor.instrumentation_name())
+            && glob_match(&self.instrument_name, descriptor.name())
+    }
+
+    /// The aggregator for an instrument this view matches, if the view
+    /// overrides the aggregation.
+    pub(crate) fn aggregator_for(
+        &self,
+        descriptor: &Descriptor,
+    ) -> Option<Arc<dyn Aggregator + Send + Sync>> {
+        self.aggregation
+            .as_ref()
+            .map(|aggregation| aggregation.aggregator(descriptor))
+    }
+
+    /// The exported name of the instruments this view matches, if it renames
+    /// them.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Whether this view matches instruments with wildcards, rather than by
+    /// their exact name.
+    pub(crate) fn has_wildcards(&self) -> bool {
+        self.instrument_name.contains(|c| c == '*' || c == '?')
+    }
+
+    /// The name of the instruments this view matches, which may contain
+    /// wildcards.
+    pub(crate) fn instrument_name(&self) -> &str {
+        &self.instrument_name
+    }
+
+    /// The descriptor to export an instrument this view matches with.
+    pub(crate) fn descriptor(&self, descriptor: &Descriptor) -> Descriptor {
+        let mut exported = descriptor.clone();
+        if let Some(name) = &self.name {
+            exported.set_name(name.clone());
+        }
+        if let Some(description) = &self.description {
+            exported.set_description(description.clone());
+        }
+        exported
+    }
+
+    /// The labels to export, if the view restricts the label keys.
+    pub(crate) fn labels(&self, labels: &LabelSet) -> Option<LabelSet> {
+        self.label_keys.as_ref().map(|keys| {
+            LabelSet::from_labels(
+                labels
+                    .iter()
+                    .filter(|(key, _)| keys.contains(key))
+                    .map(|(key, value)| KeyValue::new(key.clone(), value.clone())),
+            )
+        })
+    }
+}
+
+/// Matches `name` against a pattern with `*` and `?` wildcards.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // position of the last `*` in the pattern, and the name position it
+    // currently matches up to
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::metrics::{InstrumentKind, NumberKind};
+
+    #[rustfmt::skip]
+    fn glob_data() -> Vec<(&'static str, &'static str, bool)> {
+        vec![
+            ("http.server.duration", "http.server.duration", true),
+            ("http.server.duration", "http.client.duration", false),
+            ("http.*", "http.server.duration", true),
+            ("http.*", "http", false),
+            ("*.duration", "http.server.duration", true),
+            ("*", "", true),
+            ("http.*.duration", "http.client.duration", true),
+            ("http.*.duration", "http.client.size", false),
+            ("db.??", "db.rw", true),
+            ("db.??", "db.r", false),
+            ("*a*b", "xaxxbxab", true),
+            ("*a*b", "xaxxbxa", false),
+        ]
+    }
+
+    #[test]
+    fn glob_matching() {
+        for (pattern, name, expected) in glob_data() {
+            assert_eq!(glob_match(pattern, name), expected, "{} {}", pattern, name);
+        }
+    }
+
+    #[test]
+    fn view_matches_instrumentation_name() {
+        let descriptor = Descriptor::new(
+            "db.calls".to_string(),
+            "my-db-client".to_string(),
+            InstrumentKind::Counter,
+            NumberKind::U64,
+        );
+
+        assert!(View::new("db.*").matches(&descriptor));
+        assert!(View::new("db.*")
+            .with_instrumentation_name("my-db-client")
+            .matches(&descriptor));
+        assert!(!View::new("db.*")
+            .with_instrumentation_name("other")
+            .matches(&descriptor));
+    }
+
+    #[test]
+    fn view_overrides() {
+        let descriptor = Descriptor::new(
+            "latency".to_string(),
+            "test".to_string(),
+            InstrumentKind::ValueRecorder,
+            NumberKind::F64,
+        );
+        let view = View::new("latency")
+            .with_name("http.latency")
+            .with_description("HTTP request latency")
+            .with_label_keys(vec![Key::new("method")]);
+
+        let exported = view.descriptor(&descriptor);
+        assert_eq!(exported.name(), "http.latency");
+        assert_eq!(
+            exported.description(),
+            Some(&"HTTP request latency".to_string())
+        );
+        assert_ne!(exported.attribute_hash(), descriptor.attribute_hash());
+
+        let labels = LabelSet::from_labels(vec![
+            KeyValue::new("method", "GET"),
+            KeyValue::new("path", "/users/42"),
+        ]);
+        let labels = view.labels(&labels).unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels.iter().next().unwrap().0, &Key::new("method"));
+
+        assert!(view.aggregator_for(&descriptor).is_none());
+        assert!(View::new("latency")
+            .with_aggregation(Aggregation::Histogram(vec![1.0]))
+            .aggregator_for(&descriptor)
+            .unwrap()
+            .as_any()
+            .is::<aggregators::HistogramAggregator>());
+    }
+}
@@ -15,7 +15,10 @@ pub mod resource;
 pub mod trace;
 
 pub use env::EnvResourceDetector;
-pub use resource::Resource;
+pub use resource::{
+    ContainerResourceDetector, HostResourceDetector, ProcessResourceDetector, Resource,
+    ResourceBuilder, ResourceError,
+};
 #[cfg(feature = "trace")]
 pub use trace::{
     config::Config,
@@ -25,6 +28,6 @@ pub use trace::{
     provider::{Builder, Provider},
     sampler::{Sampler, SamplingDecision, SamplingResult, ShouldSample},
     span::Span,
-    span_processor::{BatchSpanProcessor, SimpleSpanProcessor},
+    span_processor::{BatchConfig, BatchSpanProcessor, SimpleSpanProcessor},
     tracer::Tracer,
 };
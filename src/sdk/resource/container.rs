@@ -0,0 +1,313 @@
+//! ContainerResourceDetector
+//!
+//! Implementation of `ResourceDetector` to identify the container and Kubernetes
+//! pod the process runs in, using the [container] and [k8s] semantic conventions.
+//!
+//! [container]: https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/resource/semantic_conventions/container.md
+//! [k8s]: https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/resource/semantic_conventions/k8s.md
+use crate::api::{Key, KeyValue};
+use crate::sdk::resource::ResourceDetector;
+use crate::sdk::Resource;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{env, fs, thread};
+
+const CONTAINER_ID: Key = Key::from_static_str("container.id");
+const K8S_POD_NAME: Key = Key::from_static_str("k8s.pod.name");
+const K8S_POD_UID: Key = Key::from_static_str("k8s.pod.uid");
+const K8S_NAMESPACE_NAME: Key = Key::from_static_str("k8s.namespace.name");
+const K8S_NODE_NAME: Key = Key::from_static_str("k8s.node.name");
+
+/// Pod name, set from `metadata.name` with the downward API.
+const ENV_POD_NAME: &str = "K8S_POD_NAME";
+/// Pod uid, set from `metadata.uid` with the downward API.
+const ENV_POD_UID: &str = "K8S_POD_UID";
+/// Pod namespace, set from `metadata.namespace` with the downward API.
+const ENV_NAMESPACE_NAME: &str = "K8S_NAMESPACE_NAME";
+/// Node name, set from `spec.nodeName` with the downward API.
+const ENV_NODE_NAME: &str = "K8S_NODE_NAME";
+/// Set by Kubernetes in every container of a pod.
+const ENV_KUBERNETES_SERVICE_HOST: &str = "KUBERNETES_SERVICE_HOST";
+
+const CGROUP_PATH: &str = "/proc/self/cgroup";
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+const NAMESPACE_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/namespace";
+
+/// Length of the hex encoded container ids used by docker, containerd and cri-o.
+const CONTAINER_ID_LENGTH: usize = 64;
+
+/// Resource detector that identifies the container and Kubernetes pod of the
+/// current process.
+///
+/// The container id is read from `/proc/self/cgroup`, falling back to
+/// `/proc/self/mountinfo` for cgroup v2 hosts. Pod attributes are read from
+/// environment variables, which can be exposed with the downward API:
+///
+/// ```yaml
+/// env:
+///   - name: K8S_POD_NAME
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: metadata.name
+///   - name: K8S_POD_UID
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: metadata.uid
+///   - name: K8S_NAMESPACE_NAME
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: metadata.namespace
+///   - name: K8S_NODE_NAME
+///     valueFrom:
+///       fieldRef:
+///         fieldPath: spec.nodeName
+/// ```
+///
+/// Without them, the namespace is read from the service account and the pod
+/// name defaults to the hostname. Outside of a container an empty resource is
+/// returned, as is the case when detection takes longer than the timeout.
+#[derive(Debug)]
+pub struct ContainerResourceDetector {
+    cgroup_path: PathBuf,
+    mountinfo_path: PathBuf,
+    namespace_path: PathBuf,
+}
+
+impl ResourceDetector for ContainerResourceDetector {
+    fn detect(&self, timeout: Duration) -> Resource {
+        let (sender, receiver) = mpsc::channel();
+        let (cgroup_path, mountinfo_path, namespace_path) = (
+            self.cgroup_path.clone(),
+            self.mountinfo_path.clone(),
+            self.namespace_path.clone(),
+        );
+
+        // Reading from the file system may block, so detection runs on its own
+        // thread and is abandoned once the timeout has elapsed.
+        let spawned = thread::Builder::new()
+            .name("otel-container-detector".to_string())
+            .spawn(move || {
+                let attributes = detect_attributes(cgroup_path, mountinfo_path, namespace_path);
+                let _ = sender.send(attributes);
+            });
+
+        match spawned {
+            Ok(_) => receiver
+                .recv_timeout(timeout)
+                .map(Resource::new)
+                .unwrap_or_default(),
+            Err(_) => Resource::default(),
+        }
+    }
+}
+
+impl ContainerResourceDetector {
+    /// Create `ContainerResourceDetector` instance.
+    pub fn new() -> Self {
+        ContainerResourceDetector {
+            cgroup_path: PathBuf::from(CGROUP_PATH),
+            mountinfo_path: PathBuf::from(MOUNTINFO_PATH),
+            namespace_path: PathBuf::from(NAMESPACE_PATH),
+        }
+    }
+}
+
+impl Default for ContainerResourceDetector {
+    fn default() -> Self {
+        ContainerResourceDetector::new()
+    }
+}
+
+fn detect_attributes(
+    cgroup_path: PathBuf,
+    mountinfo_path: PathBuf,
+    namespace_path: PathBuf,
+) -> Vec<KeyValue> {
+    let mut attributes = Vec::new();
+    let cgroup = fs::read_to_string(cgroup_path).unwrap_or_default();
+
+    let container_id = container_id_from_cgroup(&cgroup).or_else(|| {
+        fs::read_to_string(mountinfo_path)
+            .ok()
+            .and_then(|mountinfo| container_id_from_mountinfo(&mountinfo))
+    });
+    if let Some(container_id) = container_id {
+        attributes.push(KeyValue::new(CONTAINER_ID, container_id));
+    }
+
+    if env_var(ENV_KUBERNETES_SERVICE_HOST).is_none() && env_var(ENV_POD_NAME).is_none() {
+        return attributes;
+    }
+
+    if let Some(pod_name) = env_var(ENV_POD_NAME).or_else(|| env_var("HOSTNAME")) {
+        attributes.push(KeyValue::new(K8S_POD_NAME, pod_name));
+    }
+    if let Some(pod_uid) = env_var(ENV_POD_UID).or_else(|| pod_uid_from_cgroup(&cgroup)) {
+        attributes.push(KeyValue::new(K8S_POD_UID, pod_uid));
+    }
+    let namespace = env_var(ENV_NAMESPACE_NAME).or_else(|| {
+        fs::read_to_string(namespace_path)
+            .ok()
+            .map(|namespace| namespace.trim().to_string())
+            .filter(|namespace| !namespace.is_empty())
+    });
+    if let Some(namespace) = namespace {
+        attributes.push(KeyValue::new(K8S_NAMESPACE_NAME, namespace));
+    }
+    if let Some(node_name) = env_var(ENV_NODE_NAME) {
+        attributes.push(KeyValue::new(K8S_NODE_NAME, node_name));
+    }
+
+    attributes
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.trim().is_empty())
+}
+
+/// Find the container id in the last segment of a cgroup v1 path, e.g.
+/// `/docker/<id>`, `/kubepods/besteffort/pod<uid>/<id>` or
+/// `/system.slice/docker-<id>.scope`.
+fn container_id_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup.lines().find_map(|line| {
+        let path = line.splitn(3, ':').nth(2)?;
+        let segment = path.rsplit('/').next()?;
+        let segment = segment.trim_end_matches(".scope");
+        let id = segment.rsplit('-').next()?;
+        Some(id)
+            .filter(|id| is_container_id(id))
+            .map(str::to_string)
+    })
+}
+
+/// Find the container id in the mount sources of a cgroup v2 host, e.g.
+/// `/var/lib/docker/containers/<id>/hostname`.
+fn container_id_from_mountinfo(mountinfo: &str) -> Option<String> {
+    mountinfo
+        .lines()
+        .flat_map(|line| line.split_whitespace())
+        .find_map(|field| {
+            let mut segments = field.split('/');
+            segments.find(|segment| *segment == "containers")?;
+            segments
+                .next()
+                .filter(|id| is_container_id(id))
+                .map(str::to_string)
+        })
+}
+
+/// Find the pod uid in a cgroup path segment like `pod<uid>` or, with the
+/// systemd driver, `kubepods-besteffort-pod<uid>.slice` with dashes replaced
+/// by underscores.
+fn pod_uid_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .flat_map(|line| line.split(&['/', ':'][..]))
+        .find_map(|segment| {
+            let start = segment.rfind("pod")? + "pod".len();
+            let uid = segment[start..]
+                .trim_end_matches(".slice")
+                .replace('_', "-");
+            Some(uid).filter(|uid| uid.len() == 36)
+        })
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() == CONTAINER_ID_LENGTH && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "a4d0f6d3c5fe3b9c3dbb3ee3fcbd1d4e9f9a7f23ac5e2e7a1c2b6c0d1f1e2a3b";
+
+    #[test]
+    fn test_container_id_from_cgroup() {
+        let cases = vec![
+            format!("12:memory:/docker/{}", ID),
+            format!(
+                "11:cpu:/kubepods/besteffort/pod5f6c0ee5-0a47-4d0a-9b2a-0e2c3f1f8a7b/{}",
+                ID
+            ),
+            format!("1:name=systemd:/system.slice/docker-{}.scope", ID),
+            format!(
+                "0::/kubepods.slice/kubepods-pod1.slice/cri-containerd-{}.scope",
+                ID
+            ),
+        ];
+        for cgroup in cases {
+            assert_eq!(container_id_from_cgroup(&cgroup), Some(ID.to_string()));
+        }
+
+        assert_eq!(container_id_from_cgroup("0::/\n"), None);
+        assert_eq!(container_id_from_cgroup("12:memory:/user.slice"), None);
+    }
+
+    #[test]
+    fn test_container_id_from_mountinfo() {
+        let mountinfo = format!(
+            "650 640 0:56 / / rw,relatime master:318 - overlay overlay rw\n\
+             671 650 254:1 /docker/containers/{}/hostname /etc/hostname rw,relatime - ext4 /dev/vda1 rw",
+            ID
+        );
+        assert_eq!(
+            container_id_from_mountinfo(&mountinfo),
+            Some(ID.to_string())
+        );
+        assert_eq!(container_id_from_mountinfo("650 640 0:56 / / rw"), None);
+    }
+
+    #[test]
+    fn test_pod_uid_from_cgroup() {
+        assert_eq!(
+            pod_uid_from_cgroup(&format!(
+                "11:cpu:/kubepods/besteffort/pod5f6c0ee5-0a47-4d0a-9b2a-0e2c3f1f8a7b/{}",
+                ID
+            )),
+            Some("5f6c0ee5-0a47-4d0a-9b2a-0e2c3f1f8a7b".to_string())
+        );
+        assert_eq!(
+            pod_uid_from_cgroup(
+                "0::/kubepods.slice/kubepods-besteffort-pod5f6c0ee5_0a47_4d0a_9b2a_0e2c3f1f8a7b.slice"
+            ),
+            Some("5f6c0ee5-0a47-4d0a-9b2a-0e2c3f1f8a7b".to_string())
+        );
+        assert_eq!(
+            pod_uid_from_cgroup(&format!("12:memory:/docker/{}", ID)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_outside_container() {
+        let missing = env::temp_dir().join("otel-container-detector-missing");
+        let detector = ContainerResourceDetector {
+            cgroup_path: missing.clone(),
+            mountinfo_path: missing.clone(),
+            namespace_path: missing,
+        };
+
+        assert!(detector.detect(Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn test_detect_container_id() {
+        let cgroup_path = env::temp_dir().join("otel-container-detector-cgroup");
+        fs::write(&cgroup_path, format!("12:memory:/docker/{}\n", ID)).unwrap();
+        let detector = ContainerResourceDetector {
+            cgroup_path: cgroup_path.clone(),
+            mountinfo_path: cgroup_path.clone(),
+            namespace_path: cgroup_path.clone(),
+        };
+
+        let resource = detector.detect(Duration::from_secs(1));
+        fs::remove_file(cgroup_path).unwrap();
+
+        assert_eq!(
+            resource.iter().find(|(key, _)| **key == CONTAINER_ID),
+            Some((&CONTAINER_ID, &ID.into()))
+        );
+    }
+}
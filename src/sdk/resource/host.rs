@@ -0,0 +1,140 @@
+//! HostResourceDetector
+//!
+//! Implementation of `ResourceDetector` to describe the host and operating system,
+//! using the [host] and [os] semantic conventions.
+//!
+//! [host]: https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/resource/semantic_conventions/host.md
+//! [os]: https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/resource/semantic_conventions/os.md
+use crate::api::{Key, KeyValue};
+use crate::sdk::resource::ResourceDetector;
+use crate::sdk::Resource;
+use std::time::Duration;
+use std::{env, fs};
+
+const HOST_HOSTNAME: Key = Key::from_static_str("host.hostname");
+const HOST_ARCH: Key = Key::from_static_str("host.arch");
+const OS_TYPE: Key = Key::from_static_str("os.type");
+const OS_DESCRIPTION: Key = Key::from_static_str("os.description");
+
+/// Resource detector that describes the host: its hostname, CPU architecture,
+/// operating system type and description.
+#[derive(Debug)]
+pub struct HostResourceDetector {
+    _private: (),
+}
+
+impl ResourceDetector for HostResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attributes = vec![
+            KeyValue::new(HOST_ARCH, arch(env::consts::ARCH)),
+            KeyValue::new(OS_TYPE, os_type(env::consts::OS)),
+        ];
+
+        if let Some(hostname) = hostname() {
+            attributes.push(KeyValue::new(HOST_HOSTNAME, hostname));
+        }
+        if let Some(description) = os_description() {
+            attributes.push(KeyValue::new(OS_DESCRIPTION, description));
+        }
+
+        Resource::new(attributes)
+    }
+}
+
+impl HostResourceDetector {
+    /// Create `HostResourceDetector` instance.
+    pub fn new() -> Self {
+        HostResourceDetector { _private: () }
+    }
+}
+
+impl Default for HostResourceDetector {
+    fn default() -> Self {
+        HostResourceDetector::new()
+    }
+}
+
+/// Map a Rust target architecture onto the `host.arch` values.
+fn arch(target_arch: &'static str) -> &'static str {
+    match target_arch {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "arm" => "arm32",
+        "powerpc" => "ppc32",
+        "powerpc64" => "ppc64",
+        other => other,
+    }
+}
+
+/// Map a Rust target operating system onto the `os.type` values.
+fn os_type(target_os: &'static str) -> &'static str {
+    match target_os {
+        "macos" => "darwin",
+        "dragonfly" => "dragonflybsd",
+        other => other,
+    }
+}
+
+/// The hostname, read from the kernel or the environment.
+fn hostname() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+/// Human readable OS version, from `/etc/os-release` and the kernel release
+/// where available.
+fn os_description() -> Option<String> {
+    let pretty_name = fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|os_release| {
+            os_release.lines().find_map(|line| {
+                line.strip_prefix("PRETTY_NAME=")
+                    .map(|name| name.trim_matches('"').to_string())
+            })
+        });
+    let kernel_release = fs::read_to_string("/proc/sys/kernel/osrelease")
+        .ok()
+        .map(|release| release.trim().to_string());
+
+    match (pretty_name, kernel_release) {
+        (Some(name), Some(release)) => Some(format!("{} ({})", name, release)),
+        (Some(name), None) => Some(name),
+        (None, Some(release)) => Some(format!("{} {}", env::consts::OS, release)),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Value;
+
+    #[test]
+    fn test_host_resource() {
+        let resource = HostResourceDetector::new().detect(Duration::from_secs(0));
+        let attrs = resource.iter().collect::<std::collections::HashMap<_, _>>();
+
+        assert_eq!(
+            attrs.get(&HOST_ARCH),
+            Some(&&Value::from(arch(env::consts::ARCH)))
+        );
+        assert_eq!(
+            attrs.get(&OS_TYPE),
+            Some(&&Value::from(os_type(env::consts::OS)))
+        );
+    }
+
+    #[test]
+    fn test_arch_and_os_mapping() {
+        assert_eq!(arch("x86_64"), "amd64");
+        assert_eq!(arch("aarch64"), "arm64");
+        assert_eq!(arch("x86"), "x86");
+        assert_eq!(os_type("macos"), "darwin");
+        assert_eq!(os_type("linux"), "linux");
+    }
+}
@@ -0,0 +1,560 @@
+//! # Resource
+//!
+//! A `Resource` is an immutable representation of the entity producing telemetry. For example, a
+//! process producing telemetry that is running in a container on Kubernetes has a Pod name, it is
+//! in a namespace, and possibly is part of a Deployment which also has a name. All three of these
+//! attributes can be included in the `Resource`.
+//!
+//! The primary purpose of resources as a first-class concept in the SDK is decoupling of discovery
+//! of resource information from exporters. This allows for independent development and easy
+//! customization for users that need to integrate with closed source environments. When used with
+//! distributed tracing, a resource can be associated with the [`Provider`] when it is created.
+//! That association cannot be changed later. When associated with a `Provider`, all `Span`s
+//! produced by any `Tracer` from the provider are associated with this `Resource`.
+//!
+//! Resources can be detected from the environment with [`ResourceDetector`]s, such as the
+//! [`ProcessResourceDetector`], [`HostResourceDetector`] and [`ContainerResourceDetector`].
+//!
+//! Detectors run concurrently under a single deadline. When several sources provide the same key,
+//! the first source with a non-empty value wins, so precedence is set by the order in which sources
+//! are added to a [`ResourceBuilder`]:
+//!
+//! ```
+//! use opentelemetry::api::KeyValue;
+//! use opentelemetry::sdk::{EnvResourceDetector, HostResourceDetector, Resource};
+//! use std::time::Duration;
+//!
+//! // Explicit attributes beat the environment, which beats detected attributes.
+//! let resource = Resource::builder()
+//!     .with_attributes(vec![KeyValue::new("service.name", "my-service")])
+//!     .with_detectors(vec![Box::new(EnvResourceDetector::new())])
+//!     .with_detectors(vec![Box::new(HostResourceDetector::new())])
+//!     .with_timeout(Duration::from_secs(1))
+//!     .build();
+//! ```
+//!
+//! [`Provider`]: ../../api/trace/provider/trait.Provider.html
+//! [`ResourceBuilder`]: struct.ResourceBuilder.html
+//! [`ResourceDetector`]: trait.ResourceDetector.html
+//! [`ProcessResourceDetector`]: struct.ProcessResourceDetector.html
+//! [`HostResourceDetector`]: struct.HostResourceDetector.html
+//! [`ContainerResourceDetector`]: struct.ContainerResourceDetector.html
+mod container;
+mod host;
+mod process;
+
+pub use container::ContainerResourceDetector;
+pub use host::HostResourceDetector;
+pub use process::ProcessResourceDetector;
+
+use crate::api;
+use crate::api::labels;
+use crate::api::KeyValue;
+use crate::global;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+use std::collections::{btree_map, btree_map::Entry, BTreeMap};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Default overall timeout for resource detection.
+const DEFAULT_DETECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Describes an entity about which identifying information and metadata is exposed.
+///
+/// Items are sorted by their key, and are only overwritten if the value is an empty string.
+#[cfg_attr(feature = "serialize", derive(Deserialize, Serialize))]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Resource {
+    attrs: BTreeMap<api::Key, api::Value>,
+}
+
+impl Resource {
+    /// Create a new `Resource` from key value pairs.
+    ///
+    /// Values are de-duplicated by key, and the first key-value pair with a non-empty string value
+    /// will be retained
+    pub fn new<T: IntoIterator<Item = api::KeyValue>>(kvs: T) -> Self {
+        let mut resource = Resource::default();
+
+        for kv in kvs.into_iter() {
+            resource.insert(kv);
+        }
+
+        resource
+    }
+
+    /// Create a new `Resource` from resource detectors.
+    ///
+    /// Detectors run concurrently and `timeout` bounds the overall detection. Detectors that time
+    /// out or panic are reported to the global error handler and contribute no attributes. Keys
+    /// from detectors earlier in the list have priority.
+    pub fn from_detectors(timeout: Duration, detectors: Vec<Box<dyn ResourceDetector>>) -> Self {
+        Resource::builder()
+            .with_timeout(timeout)
+            .with_detectors(detectors)
+            .build()
+    }
+
+    /// Create a new `ResourceBuilder` to combine explicit attributes and detectors.
+    pub fn builder() -> ResourceBuilder {
+        ResourceBuilder::default()
+    }
+
+    /// Create a new `Resource` by combining two resources.
+    ///
+    /// Keys from this resource have priority over keys from the merged resource.
+    pub fn merge(&self, other: &Self) -> Self {
+        if self.attrs.is_empty() {
+            return other.clone();
+        }
+        if other.attrs.is_empty() {
+            return self.clone();
+        }
+
+        let mut resource = Resource::default();
+
+        // attrs from self must be added first so they have priority
+        for (k, v) in self.attrs.iter() {
+            resource.insert(api::KeyValue {
+                key: k.clone(),
+                value: v.clone(),
+            });
+        }
+        for (k, v) in other.attrs.iter() {
+            resource.insert(api::KeyValue {
+                key: k.clone(),
+                value: v.clone(),
+            });
+        }
+
+        resource
+    }
+
+    /// Returns the number of attributes for this resource
+    pub fn len(&self) -> usize {
+        self.attrs.len()
+    }
+
+    /// Returns `true` if the resource contains no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.attrs.is_empty()
+    }
+
+    /// Gets an iterator over the attributes of this resource, sorted by key.
+    pub fn iter(&self) -> Iter {
+        self.into_iter()
+    }
+
+    /// Encoded labels
+    pub fn encoded(&self, encoder: &dyn labels::Encoder) -> String {
+        encoder.encode(&mut self.into_iter())
+    }
+
+    /// Insert a key-value pair into a `Resource`
+    fn insert(&mut self, item: api::KeyValue) {
+        match self.attrs.entry(item.key) {
+            Entry::Occupied(mut existing_item) => {
+                if let api::Value::String(s) = existing_item.get() {
+                    if s.is_empty() {
+                        existing_item.insert(item.value);
+                    }
+                }
+            }
+            Entry::Vacant(v) => {
+                v.insert(item.value);
+            }
+        }
+    }
+}
+
+/// Errors that occur while detecting resources.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ResourceError {
+    /// The detector did not return before the detection deadline.
+    #[error("Resource detector {detector} timed out after {timeout:?}")]
+    DetectorTimedOut {
+        /// Name of the detector
+        detector: &'static str,
+        /// The overall detection timeout
+        timeout: Duration,
+    },
+    /// The detector panicked.
+    #[error("Resource detector {0} failed")]
+    DetectorFailed(&'static str),
+}
+
+/// A source of resource attributes in a `ResourceBuilder`.
+enum Source {
+    Attributes(Resource),
+    Detector(Box<dyn ResourceDetector>),
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Attributes(resource) => f.debug_tuple("Attributes").field(resource).finish(),
+            Source::Detector(detector) => {
+                f.debug_tuple("Detector").field(&detector.name()).finish()
+            }
+        }
+    }
+}
+
+/// Builder combining explicit attributes and resource detectors into a `Resource`.
+///
+/// Sources take precedence in the order they are added: for each key, the value
+/// from the first source with a non-empty value is kept.
+#[derive(Debug)]
+pub struct ResourceBuilder {
+    sources: Vec<Source>,
+    timeout: Duration,
+}
+
+impl Default for ResourceBuilder {
+    fn default() -> Self {
+        ResourceBuilder {
+            sources: Vec::new(),
+            timeout: DEFAULT_DETECTION_TIMEOUT,
+        }
+    }
+}
+
+impl ResourceBuilder {
+    /// Add explicit attributes, with priority over sources added after them.
+    pub fn with_attributes<T: IntoIterator<Item = api::KeyValue>>(self, kvs: T) -> Self {
+        let mut sources = self.sources;
+        sources.push(Source::Attributes(Resource::new(kvs)));
+
+        ResourceBuilder { sources, ..self }
+    }
+
+    /// Add detectors, with priority over sources added after them.
+    ///
+    /// Detectors earlier in the list have priority over later ones.
+    pub fn with_detectors(self, detectors: Vec<Box<dyn ResourceDetector>>) -> Self {
+        let mut sources = self.sources;
+        sources.extend(detectors.into_iter().map(Source::Detector));
+
+        ResourceBuilder { sources, ..self }
+    }
+
+    /// Set the overall timeout for running all detectors. The default is 5 seconds.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        ResourceBuilder { timeout, ..self }
+    }
+
+    /// Run the detectors concurrently and merge the results.
+    pub fn build(self) -> Resource {
+        let deadline = Instant::now() + self.timeout;
+        let timeout = self.timeout;
+        let (sender, receiver) = mpsc::channel();
+        let mut results = Vec::with_capacity(self.sources.len());
+        let mut pending = 0;
+
+        for (idx, source) in self.sources.into_iter().enumerate() {
+            match source {
+                Source::Attributes(resource) => results.push(Detection::Done(resource)),
+                Source::Detector(detector) => {
+                    let name = detector.name();
+                    let sender = sender.clone();
+                    let spawned = thread::Builder::new()
+                        .name("otel-resource-detector".to_string())
+                        .spawn(move || {
+                            let detected =
+                                panic::catch_unwind(AssertUnwindSafe(|| detector.detect(timeout)));
+                            let _ = sender.send((idx, detected.ok()));
+                        });
+                    match spawned {
+                        Ok(_) => {
+                            pending += 1;
+                            results.push(Detection::Pending(name));
+                        }
+                        Err(_) => results.push(Detection::Failed(name)),
+                    }
+                }
+            }
+        }
+
+        while pending > 0 {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(remaining) {
+                Ok((idx, detected)) => {
+                    if let Detection::Pending(name) = results[idx] {
+                        results[idx] = detected
+                            .map(Detection::Done)
+                            .unwrap_or(Detection::Failed(name));
+                    }
+                    pending -= 1;
+                }
+                Err(_) => break,
+            }
+        }
+
+        let mut resource = Resource::default();
+        for result in results {
+            match result {
+                Detection::Done(detected) => {
+                    for (key, value) in detected.into_iter() {
+                        // using insert instead of merge to avoid clone.
+                        resource.insert(KeyValue::new(key, value));
+                    }
+                }
+                Detection::Pending(detector) => {
+                    global::handle_error(ResourceError::DetectorTimedOut { detector, timeout })
+                }
+                Detection::Failed(detector) => {
+                    global::handle_error(ResourceError::DetectorFailed(detector))
+                }
+            }
+        }
+
+        resource
+    }
+}
+
+/// State of a source while building a `Resource`.
+enum Detection {
+    Done(Resource),
+    Pending(&'static str),
+    Failed(&'static str),
+}
+
+/// An owned iterator over the entries of a `Resource`.
+#[derive(Debug)]
+pub struct IntoIter(btree_map::IntoIter<api::Key, api::Value>);
+
+impl Iterator for IntoIter {
+    type Item = (api::Key, api::Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl IntoIterator for Resource {
+    type Item = (api::Key, api::Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.attrs.into_iter())
+    }
+}
+
+/// An iterator over the entries of a `Resource`.
+#[derive(Debug)]
+pub struct Iter<'a>(btree_map::Iter<'a, api::Key, api::Value>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a api::Key, &'a api::Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<'a> IntoIterator for &'a Resource {
+    type Item = (&'a api::Key, &'a api::Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter(self.attrs.iter())
+    }
+}
+
+/// ResourceDetector detects OpenTelemetry resource information
+///
+/// Implementations of this trait can be passed to
+/// the `Resource::from_detectors` function to generate a Resource from the merged information.
+/// Detectors run on their own thread, concurrently with other detectors.
+pub trait ResourceDetector: Send {
+    /// detect returns an initialized Resource based on gathered information.
+    ///
+    /// timeout is used in case the detection operation takes too much time.
+    ///
+    /// If source information to construct a Resource is inaccessible, an empty Resource should be returned
+    ///
+    /// If source information to construct a Resource is invalid, for example,
+    /// missing required values. an empty Resource should be returned.
+    fn detect(&self, timeout: Duration) -> Resource;
+
+    /// Name of the detector, used when reporting detection failures.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Resource, ResourceDetector};
+    use crate::api;
+    use crate::sdk::EnvResourceDetector;
+    use std::collections::BTreeMap;
+    use std::{env, time};
+
+    #[test]
+    fn new_resource() {
+        let args_with_dupe_keys = vec![
+            api::KeyValue::new("a", ""),
+            api::KeyValue::new("a", "final"),
+        ];
+
+        let mut expected_attrs = BTreeMap::new();
+        expected_attrs.insert(api::Key::new("a"), api::Value::from("final"));
+
+        assert_eq!(
+            Resource::new(args_with_dupe_keys),
+            Resource {
+                attrs: expected_attrs
+            }
+        );
+    }
+
+    #[test]
+    fn merge_resource() {
+        let resource_a = Resource::new(vec![
+            api::KeyValue::new("a", ""),
+            api::KeyValue::new("b", "b-value"),
+        ]);
+
+        let resource_b = Resource::new(vec![
+            api::KeyValue::new("a", "final"),
+            api::KeyValue::new("c", "c-value"),
+        ]);
+
+        let mut expected_attrs = BTreeMap::new();
+        expected_attrs.insert(api::Key::new("a"), api::Value::from("final"));
+        expected_attrs.insert(api::Key::new("b"), api::Value::from("b-value"));
+        expected_attrs.insert(api::Key::new("c"), api::Value::from("c-value"));
+
+        assert_eq!(
+            resource_a.merge(&resource_b),
+            Resource {
+                attrs: expected_attrs
+            }
+        );
+    }
+
+    #[test]
+    fn detect_resource() {
+        env::set_var("OTEL_RESOURCE_ATTRIBUTES", "key=value, k = v , a= x, a=z");
+        env::set_var("irrelevant".to_uppercase(), "20200810");
+
+        let detector = EnvResourceDetector::new();
+        let resource =
+            Resource::from_detectors(time::Duration::from_secs(5), vec![Box::new(detector)]);
+        assert_eq!(
+            resource,
+            Resource::new(vec![
+                api::KeyValue::new(
+                    api::Key::new("key".to_string()),
+                    api::Value::String("value".into())
+                ),
+                api::KeyValue::new(
+                    api::Key::new("k".to_string()),
+                    api::Value::String("v".into())
+                ),
+                api::KeyValue::new(
+                    api::Key::new("a".to_string()),
+                    api::Value::String("x".into())
+                ),
+                api::KeyValue::new(
+                    api::Key::new("a".to_string()),
+                    api::Value::String("z".into())
+                )
+            ])
+        )
+    }
+
+    #[derive(Debug)]
+    struct TestDetector {
+        delay: time::Duration,
+        attributes: Vec<api::KeyValue>,
+    }
+
+    impl ResourceDetector for TestDetector {
+        fn detect(&self, _timeout: time::Duration) -> Resource {
+            std::thread::sleep(self.delay);
+            Resource::new(self.attributes.clone())
+        }
+    }
+
+    #[derive(Debug)]
+    struct PanickingDetector;
+
+    impl ResourceDetector for PanickingDetector {
+        fn detect(&self, _timeout: time::Duration) -> Resource {
+            panic!("detection failed")
+        }
+    }
+
+    fn detector(delay_ms: u64, key: &'static str, value: &'static str) -> Box<TestDetector> {
+        Box::new(TestDetector {
+            delay: time::Duration::from_millis(delay_ms),
+            attributes: vec![api::KeyValue::new(key, value)],
+        })
+    }
+
+    #[test]
+    fn builder_precedence() {
+        let resource = Resource::builder()
+            .with_attributes(vec![api::KeyValue::new("a", "explicit")])
+            .with_detectors(vec![detector(0, "a", "env"), detector(0, "b", "env")])
+            .with_detectors(vec![
+                detector(0, "b", "detected"),
+                detector(0, "c", "detected"),
+            ])
+            .build();
+
+        assert_eq!(
+            resource,
+            Resource::new(vec![
+                api::KeyValue::new("a", "explicit"),
+                api::KeyValue::new("b", "env"),
+                api::KeyValue::new("c", "detected"),
+            ])
+        );
+    }
+
+    #[test]
+    fn detectors_run_concurrently_under_one_deadline() {
+        let start = time::Instant::now();
+        let resource = Resource::from_detectors(
+            time::Duration::from_millis(500),
+            vec![
+                detector(200, "a", "a-value"),
+                detector(200, "b", "b-value"),
+                detector(200, "c", "c-value"),
+                detector(5_000, "slow", "slow-value"),
+            ],
+        );
+
+        assert!(start.elapsed() < time::Duration::from_secs(2));
+        assert_eq!(
+            resource,
+            Resource::new(vec![
+                api::KeyValue::new("a", "a-value"),
+                api::KeyValue::new("b", "b-value"),
+                api::KeyValue::new("c", "c-value"),
+            ])
+        );
+    }
+
+    #[test]
+    fn failed_detectors_are_skipped() {
+        let resource = Resource::from_detectors(
+            time::Duration::from_secs(5),
+            vec![Box::new(PanickingDetector), detector(0, "a", "a-value")],
+        );
+
+        assert_eq!(
+            resource,
+            Resource::new(vec![api::KeyValue::new("a", "a-value")])
+        );
+    }
+}
@@ -0,0 +1,122 @@
+//! ProcessResourceDetector
+//!
+//! Implementation of `ResourceDetector` to describe the running process, using the
+//! [process semantic conventions].
+//!
+//! [process semantic conventions]: https://github.com/open-telemetry/opentelemetry-specification/blob/master/specification/resource/semantic_conventions/process.md
+use crate::api::{Key, KeyValue};
+use crate::sdk::resource::ResourceDetector;
+use crate::sdk::Resource;
+use std::env;
+use std::time::Duration;
+
+const PROCESS_PID: Key = Key::from_static_str("process.pid");
+const PROCESS_EXECUTABLE_NAME: Key = Key::from_static_str("process.executable.name");
+const PROCESS_EXECUTABLE_PATH: Key = Key::from_static_str("process.executable.path");
+const PROCESS_COMMAND: Key = Key::from_static_str("process.command");
+const PROCESS_COMMAND_LINE: Key = Key::from_static_str("process.command_line");
+const PROCESS_RUNTIME_NAME: Key = Key::from_static_str("process.runtime.name");
+const PROCESS_RUNTIME_VERSION: Key = Key::from_static_str("process.runtime.version");
+
+/// Resource detector that describes the current process: its pid, executable,
+/// command line and runtime.
+///
+/// The runtime is the Rust compiler the process was built with. Its version is
+/// not known at run time, so it is only reported when set with
+/// [`with_runtime_version`].
+///
+/// [`with_runtime_version`]: #method.with_runtime_version
+#[derive(Debug)]
+pub struct ProcessResourceDetector {
+    runtime_version: Option<String>,
+}
+
+impl ResourceDetector for ProcessResourceDetector {
+    fn detect(&self, _timeout: Duration) -> Resource {
+        let mut attributes = vec![KeyValue::new(PROCESS_PID, i64::from(std::process::id()))];
+
+        if let Ok(path) = env::current_exe() {
+            if let Some(name) = path.file_name() {
+                attributes.push(KeyValue::new(
+                    PROCESS_EXECUTABLE_NAME,
+                    name.to_string_lossy().into_owned(),
+                ));
+            }
+            attributes.push(KeyValue::new(
+                PROCESS_EXECUTABLE_PATH,
+                path.to_string_lossy().into_owned(),
+            ));
+        }
+
+        let args: Vec<String> = env::args_os()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        if let Some(command) = args.first() {
+            attributes.push(KeyValue::new(PROCESS_COMMAND, command.clone()));
+            attributes.push(KeyValue::new(PROCESS_COMMAND_LINE, args.join(" ")));
+        }
+
+        attributes.push(KeyValue::new(PROCESS_RUNTIME_NAME, "rustc"));
+        if let Some(version) = &self.runtime_version {
+            attributes.push(KeyValue::new(PROCESS_RUNTIME_VERSION, version.clone()));
+        }
+
+        Resource::new(attributes)
+    }
+}
+
+impl ProcessResourceDetector {
+    /// Create `ProcessResourceDetector` instance.
+    pub fn new() -> Self {
+        ProcessResourceDetector {
+            runtime_version: None,
+        }
+    }
+
+    /// Report the given Rust compiler version, e.g. `1.46.0`, as the process
+    /// runtime version.
+    pub fn with_runtime_version<T: Into<String>>(self, version: T) -> Self {
+        ProcessResourceDetector {
+            runtime_version: Some(version.into()),
+        }
+    }
+}
+
+impl Default for ProcessResourceDetector {
+    fn default() -> Self {
+        ProcessResourceDetector::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Value;
+
+    #[test]
+    fn test_process_resource() {
+        let resource = ProcessResourceDetector::new().detect(Duration::from_secs(0));
+        let attrs = resource.iter().collect::<std::collections::HashMap<_, _>>();
+
+        assert_eq!(
+            attrs.get(&PROCESS_PID),
+            Some(&&Value::I64(i64::from(std::process::id())))
+        );
+        assert_eq!(
+            attrs.get(&PROCESS_RUNTIME_NAME),
+            Some(&&Value::from("rustc"))
+        );
+        assert!(attrs.contains_key(&PROCESS_EXECUTABLE_PATH));
+        assert!(attrs.contains_key(&PROCESS_COMMAND_LINE));
+        assert!(!attrs.contains_key(&PROCESS_RUNTIME_VERSION));
+
+        let resource = ProcessResourceDetector::new()
+            .with_runtime_version("1.46.0")
+            .detect(Duration::from_secs(0));
+        let attrs = resource.iter().collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(
+            attrs.get(&PROCESS_RUNTIME_VERSION),
+            Some(&&Value::from("1.46.0"))
+        );
+    }
+}
@@ -2,9 +2,32 @@
 //!
 //! Configuration represents the global tracing configuration, overrides
 //! can be set for the default OpenTelemetry limits and Sampler.
+//!
+//! The configuration can also be read from the standard environment variables
+//! with [`Config::from_env`].
+//!
+//! [`Config::from_env`]: struct.Config.html#method.from_env
+use crate::sdk::env::parse_env_var;
 use crate::{api, sdk};
 use std::sync::Arc;
 
+/// The sampler to use, one of `always_on`, `always_off`, `traceidratio`,
+/// `parentbased_always_on`, `parentbased_always_off` or `parentbased_traceidratio`.
+const OTEL_TRACES_SAMPLER: &str = "OTEL_TRACES_SAMPLER";
+
+/// The sampling ratio in `[0.0, 1.0]` used by the `traceidratio` samplers.
+/// Defaults to `1.0`.
+const OTEL_TRACES_SAMPLER_ARG: &str = "OTEL_TRACES_SAMPLER_ARG";
+
+/// The max events that can be added to a `Span`.
+const OTEL_SPAN_EVENT_COUNT_LIMIT: &str = "OTEL_SPAN_EVENT_COUNT_LIMIT";
+
+/// The max attributes that can be added to a `Span`.
+const OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT: &str = "OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT";
+
+/// The max links that can be added to a `Span`.
+const OTEL_SPAN_LINK_COUNT_LIMIT: &str = "OTEL_SPAN_LINK_COUNT_LIMIT";
+
 /// Tracer configuration
 #[derive(Debug)]
 pub struct Config {
@@ -22,6 +45,33 @@ pub struct Config {
     pub resource: Arc<sdk::Resource>,
 }
 
+impl Config {
+    /// Create a configuration from the standard environment variables.
+    ///
+    /// Reads `OTEL_TRACES_SAMPLER`, `OTEL_TRACES_SAMPLER_ARG`,
+    /// `OTEL_SPAN_EVENT_COUNT_LIMIT`, `OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT` and
+    /// `OTEL_SPAN_LINK_COUNT_LIMIT`. Unset variables keep their default value,
+    /// malformed ones return an error.
+    pub fn from_env() -> api::TraceResult<Self> {
+        let mut config = Config::default();
+
+        if let Some(sampler) = sampler_from_env()? {
+            config.default_sampler = Box::new(sampler);
+        }
+        if let Some(max_events) = parse_env_var(OTEL_SPAN_EVENT_COUNT_LIMIT)? {
+            config.max_events_per_span = max_events;
+        }
+        if let Some(max_attributes) = parse_env_var(OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT)? {
+            config.max_attributes_per_span = max_attributes;
+        }
+        if let Some(max_links) = parse_env_var(OTEL_SPAN_LINK_COUNT_LIMIT)? {
+            config.max_links_per_span = max_links;
+        }
+
+        Ok(config)
+    }
+}
+
 impl Default for Config {
     /// Create default global sdk configuration.
     fn default() -> Self {
@@ -35,3 +85,99 @@ impl Default for Config {
         }
     }
 }
+
+/// Map `OTEL_TRACES_SAMPLER` and `OTEL_TRACES_SAMPLER_ARG` onto a `Sampler`.
+fn sampler_from_env() -> api::TraceResult<Option<sdk::Sampler>> {
+    let name: String = match parse_env_var(OTEL_TRACES_SAMPLER)? {
+        Some(name) => name,
+        None => return Ok(None),
+    };
+
+    let ratio = || -> api::TraceResult<f64> {
+        let ratio = parse_env_var(OTEL_TRACES_SAMPLER_ARG)?.unwrap_or(1.0);
+        if (0.0..=1.0).contains(&ratio) {
+            Ok(ratio)
+        } else {
+            Err(api::TraceError::InvalidEnvVar {
+                name: OTEL_TRACES_SAMPLER_ARG,
+                value: ratio.to_string(),
+                reason: "ratio must be between 0.0 and 1.0".to_string(),
+            })
+        }
+    };
+
+    let sampler = match name.as_str() {
+        "always_on" => sdk::Sampler::AlwaysOn,
+        "always_off" => sdk::Sampler::AlwaysOff,
+        "traceidratio" => sdk::Sampler::TraceIdRatioBased(ratio()?),
+        "parentbased_always_on" => sdk::Sampler::ParentBased(Box::new(sdk::Sampler::AlwaysOn)),
+        "parentbased_always_off" => sdk::Sampler::ParentBased(Box::new(sdk::Sampler::AlwaysOff)),
+        "parentbased_traceidratio" => {
+            sdk::Sampler::ParentBased(Box::new(sdk::Sampler::TraceIdRatioBased(ratio()?)))
+        }
+        _ => {
+            return Err(api::TraceError::InvalidEnvVar {
+                name: OTEL_TRACES_SAMPLER,
+                value: name,
+                reason: "unknown sampler".to_string(),
+            })
+        }
+    };
+
+    Ok(Some(sampler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    // All cases share the same variables, so they run in a single test to avoid
+    // races between tests running in parallel.
+    #[test]
+    fn test_config_from_env() {
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.max_events_per_span, 128);
+
+        env::set_var(OTEL_TRACES_SAMPLER, "parentbased_traceidratio");
+        env::set_var(OTEL_TRACES_SAMPLER_ARG, "0.25");
+        env::set_var(OTEL_SPAN_EVENT_COUNT_LIMIT, "10");
+        env::set_var(OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT, " 20 ");
+        env::set_var(OTEL_SPAN_LINK_COUNT_LIMIT, "");
+        let config = Config::from_env().unwrap();
+        assert_eq!(
+            format!("{:?}", config.default_sampler),
+            format!(
+                "{:?}",
+                sdk::Sampler::ParentBased(Box::new(sdk::Sampler::TraceIdRatioBased(0.25)))
+            )
+        );
+        assert_eq!(config.max_events_per_span, 10);
+        assert_eq!(config.max_attributes_per_span, 20);
+        assert_eq!(config.max_links_per_span, 32);
+
+        env::set_var(OTEL_TRACES_SAMPLER_ARG, "1.5");
+        assert!(Config::from_env().is_err());
+
+        env::set_var(OTEL_TRACES_SAMPLER, "sometimes");
+        let err = Config::from_env().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid value \"sometimes\" for environment variable OTEL_TRACES_SAMPLER: unknown sampler"
+        );
+
+        env::set_var(OTEL_TRACES_SAMPLER, "always_off");
+        env::set_var(OTEL_SPAN_EVENT_COUNT_LIMIT, "many");
+        assert!(Config::from_env().is_err());
+
+        for var in &[
+            OTEL_TRACES_SAMPLER,
+            OTEL_TRACES_SAMPLER_ARG,
+            OTEL_SPAN_EVENT_COUNT_LIMIT,
+            OTEL_SPAN_ATTRIBUTE_COUNT_LIMIT,
+            OTEL_SPAN_LINK_COUNT_LIMIT,
+        ] {
+            env::remove_var(var);
+        }
+    }
+}
@@ -8,9 +8,16 @@
 //! start time is set to the current time on span creation. After the `Span` is created, it
 //! is possible to change its name, set its `Attributes`, and add `Links` and `Events`.
 //! These cannot be changed after the `Span`'s end time has been set.
+//!
+//! Calling `end` finalizes the span: span processors are notified immediately and
+//! any further mutation is ignored. Dropping the last handle to a span that was
+//! never ended will end it as a fallback.
+//!
+//! Spans started without an explicit start time are anchored to a monotonic
+//! clock, so their duration is not affected by changes to the system clock.
 use crate::{api, exporter, sdk};
 use std::sync::{Arc, Mutex};
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 
 /// Single operation within a trace.
 #[derive(Clone, Debug)]
@@ -19,10 +26,14 @@ pub struct Span {
     inner: Arc<SpanInner>,
 }
 
-/// Inner data, processed and exported on drop
+/// Inner data, processed and exported when the span ends
 #[derive(Debug)]
 struct SpanInner {
-    data: Option<Mutex<exporter::trace::SpanData>>,
+    span_context: api::SpanContext,
+    /// Monotonic anchor for the start time, `None` if the start time was given explicitly.
+    start_instant: Option<Instant>,
+    /// `None` if the span is not recording, `Some(None)` once it has ended.
+    data: Option<Mutex<Option<exporter::trace::SpanData>>>,
     tracer: sdk::Tracer,
 }
 
@@ -30,37 +41,39 @@ impl Span {
     pub(crate) fn new(
         id: api::SpanId,
         data: Option<exporter::trace::SpanData>,
+        start_instant: Option<Instant>,
         tracer: sdk::Tracer,
     ) -> Self {
+        let span_context = data
+            .as_ref()
+            .map(|data| data.span_context.clone())
+            .unwrap_or_else(|| {
+                api::SpanContext::new(api::TraceId::invalid(), api::SpanId::invalid(), 0, false)
+            });
+
         Span {
             id,
             inner: Arc::new(SpanInner {
-                data: data.map(Mutex::new),
+                span_context,
+                start_instant,
+                data: data.map(|data| Mutex::new(Some(data))),
                 tracer,
             }),
         }
     }
 
-    /// Operate on reference to span inner
-    fn with_data<T, F>(&self, f: F) -> Option<T>
-    where
-        F: FnOnce(&exporter::trace::SpanData) -> T,
-    {
-        self.inner
-            .data
-            .as_ref()
-            .and_then(|inner| inner.lock().ok().map(|span_data| f(&span_data)))
-    }
-
-    /// Operate on mutable reference to span inner
+    /// Operate on mutable reference to span inner, `None` if the span is not
+    /// recording or has already ended.
     fn with_data_mut<T, F>(&self, f: F) -> Option<T>
     where
         F: FnOnce(&mut exporter::trace::SpanData) -> T,
     {
-        self.inner
-            .data
-            .as_ref()
-            .and_then(|inner| inner.lock().ok().map(|mut span_data| f(&mut span_data)))
+        self.inner.data.as_ref().and_then(|inner| {
+            inner
+                .lock()
+                .ok()
+                .and_then(|mut span_data| span_data.as_mut().map(f))
+        })
     }
 }
 
@@ -84,16 +97,15 @@ impl api::Span for Span {
 
     /// Returns the `SpanContext` for the given `Span`.
     fn span_context(&self) -> api::SpanContext {
-        self.with_data(|data| data.span_context.clone())
-            .unwrap_or_else(|| {
-                api::SpanContext::new(api::TraceId::invalid(), api::SpanId::invalid(), 0, false)
-            })
+        self.inner.span_context.clone()
     }
 
     /// Returns true if this `Span` is recording information like events with the `add_event`
     /// operation, attributes using `set_attributes`, status with `set_status`, etc.
+    ///
+    /// Ended spans are no longer recording.
     fn is_recording(&self) -> bool {
-        self.inner.data.is_some()
+        self.with_data_mut(|_| ()).is_some()
     }
 
     /// Sets a single `Attribute` where the attribute properties are passed as arguments.
@@ -119,31 +131,202 @@ impl api::Span for Span {
     /// Updates the `Span`'s name.
     fn update_name(&self, new_name: String) {
         self.with_data_mut(|data| {
-            data.name = new_name;
+            data.name = new_name.into();
         });
     }
 
+    /// Finishes the span, measuring its duration with a monotonic clock.
+    ///
+    /// An end time set explicitly via the span builder is kept.
+    fn end(&self) {
+        self.inner.ensure_ended(None);
+    }
+
     /// Finishes the span with given timestamp.
+    ///
+    /// The span is exported to all span processors right away, subsequent calls
+    /// to `end` and to any setter are ignored.
     fn end_with_timestamp(&self, timestamp: SystemTime) {
-        self.with_data_mut(|data| {
-            data.end_time = timestamp;
-        });
+        self.inner.ensure_ended(Some(timestamp));
+    }
+}
+
+impl SpanInner {
+    /// End the span if it has not ended yet and report it to the span processors.
+    fn ensure_ended(&self, timestamp: Option<SystemTime>) {
+        let data = match self.data.as_ref() {
+            Some(data) => data,
+            None => return,
+        };
+        // Take the data out of the lock so the span becomes immutable and the
+        // processors run without holding it.
+        let mut span_data = match data.lock().ok().and_then(|mut data| data.take()) {
+            Some(span_data) => span_data,
+            None => return,
+        };
+
+        match timestamp {
+            Some(timestamp) => span_data.end_time = timestamp,
+            None if span_data.end_time == span_data.start_time => {
+                span_data.end_time = self.now(span_data.start_time)
+            }
+            None => {}
+        }
+
+        let exportable_span = Arc::new(span_data);
+        for processor in self.tracer.provider().span_processors() {
+            processor.on_end(exportable_span.clone())
+        }
+    }
+
+    /// Current time as start time plus monotonic elapsed time, or the system
+    /// time if the span has no monotonic anchor.
+    fn now(&self, start_time: SystemTime) -> SystemTime {
+        self.start_instant
+            .map(|start_instant| start_time + start_instant.elapsed())
+            .unwrap_or_else(SystemTime::now)
     }
 }
 
 impl Drop for SpanInner {
-    /// Report span on inner drop
+    /// Report span on inner drop if it was never explicitly ended
     fn drop(&mut self) {
-        if let Some(data) = self.data.take() {
-            if let Ok(mut inner) = data.lock() {
-                if inner.end_time == inner.start_time {
-                    inner.end_time = SystemTime::now();
-                }
-                let exportable_span = Arc::new(inner.clone());
-                for processor in self.tracer.provider().span_processors() {
-                    processor.on_end(exportable_span.clone())
-                }
+        self.ensure_ended(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{Provider, Span as _, Tracer as _};
+    use std::time::Duration;
+
+    #[derive(Debug, Default)]
+    struct CollectingProcessor {
+        ended: Arc<Mutex<Vec<Arc<exporter::trace::SpanData>>>>,
+    }
+
+    impl api::SpanProcessor for CollectingProcessor {
+        fn on_start(&self, _span: Arc<exporter::trace::SpanData>) {}
+
+        fn on_end(&self, span: Arc<exporter::trace::SpanData>) {
+            self.ended.lock().unwrap().push(span);
+        }
+
+        fn shutdown(&self) {}
+    }
+
+    fn tracer() -> (sdk::Tracer, Arc<Mutex<Vec<Arc<exporter::trace::SpanData>>>>) {
+        let processor = CollectingProcessor::default();
+        let ended = processor.ended.clone();
+        let provider = sdk::Provider::builder()
+            .with_span_processor(processor)
+            .build();
+
+        (provider.get_tracer("test"), ended)
+    }
+
+    #[test]
+    fn end_exports_immediately() {
+        let (tracer, ended) = tracer();
+        let span = tracer.start("span");
+        let clone = span.clone();
+
+        span.end();
+
+        assert_eq!(ended.lock().unwrap().len(), 1);
+        drop(clone);
+        drop(span);
+        assert_eq!(ended.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn static_names_are_not_copied() {
+        const NAME: &str = "static-name";
+        const VALUE: &str = "GET";
+        let (tracer, ended) = tracer();
+
+        let span = tracer.start(NAME);
+        span.set_attribute(api::KeyValue::new("http.method", VALUE));
+        span.end();
+        tracer.in_span(NAME, |_cx| {});
+
+        let ended = ended.lock().unwrap();
+        assert_eq!(ended.len(), 2);
+        for data in ended.iter() {
+            assert_eq!(data.name.as_str().as_ptr(), NAME.as_ptr());
+        }
+        match ended[0].attributes.iter().next() {
+            Some((_, api::Value::String(value))) => {
+                assert_eq!(value.as_str().as_ptr(), VALUE.as_ptr())
             }
+            other => panic!("unexpected attribute {:?}", other),
         }
     }
+
+    #[test]
+    fn ended_span_is_immutable() {
+        let (tracer, ended) = tracer();
+        let span = tracer.start("span");
+        let end_time = SystemTime::now();
+        span.end_with_timestamp(end_time);
+
+        span.set_attribute(api::KeyValue::new("after", "end"));
+        span.update_name("renamed".to_string());
+        span.add_event("event".to_string(), vec![]);
+        span.end();
+
+        assert!(!span.is_recording());
+        assert!(span.span_context().is_valid());
+        let ended = ended.lock().unwrap();
+        assert_eq!(ended.len(), 1);
+        assert_eq!(ended[0].name, "span");
+        assert_eq!(ended[0].end_time, end_time);
+        assert_eq!(ended[0].attributes.len(), 0);
+        assert_eq!(ended[0].message_events.len(), 0);
+    }
+
+    #[test]
+    fn drop_ends_span() {
+        let (tracer, ended) = tracer();
+        let span = tracer.start("span");
+        let clone = span.clone();
+
+        drop(span);
+        assert!(ended.lock().unwrap().is_empty());
+        drop(clone);
+
+        assert_eq!(ended.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn explicit_builder_timestamps_are_kept() {
+        let (tracer, ended) = tracer();
+        let start_time = SystemTime::UNIX_EPOCH + Duration::from_secs(10);
+        let end_time = start_time + Duration::from_secs(1);
+        let span = tracer
+            .span_builder("span")
+            .with_start_time(start_time)
+            .with_end_time(end_time)
+            .start(&tracer);
+
+        span.end();
+
+        let ended = ended.lock().unwrap();
+        assert_eq!(ended[0].start_time, start_time);
+        assert_eq!(ended[0].end_time, end_time);
+    }
+
+    #[test]
+    fn end_uses_monotonic_elapsed_time() {
+        let (tracer, ended) = tracer();
+        let span = tracer.start("span");
+        std::thread::sleep(Duration::from_millis(10));
+
+        span.end();
+
+        let ended = ended.lock().unwrap();
+        let duration = ended[0].end_time.duration_since(ended[0].start_time);
+        assert!(duration.unwrap() >= Duration::from_millis(10));
+    }
 }
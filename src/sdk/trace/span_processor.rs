@@ -90,16 +90,31 @@
 //! [`executor`]: https://docs.rs/futures/0.3.4/futures/executor/index.html
 //! [`tokio`]: https://tokio.rs
 //! [`async-std`]: https://async.rs
-use crate::{api, exporter};
+use crate::sdk::env::parse_env_var;
+use crate::{api, exporter, global};
 use futures::{
     channel::mpsc,
     task::{Context, Poll},
     Future, Stream, StreamExt,
 };
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc as std_mpsc, Arc, Mutex};
+use std::thread;
 use std::time;
 
+/// Delay interval between two consecutive exports, in milliseconds.
+const OTEL_BSP_SCHEDULE_DELAY: &str = "OTEL_BSP_SCHEDULE_DELAY";
+
+/// Maximum queue size.
+const OTEL_BSP_MAX_QUEUE_SIZE: &str = "OTEL_BSP_MAX_QUEUE_SIZE";
+
+/// Maximum batch size, must be less than or equal to `OTEL_BSP_MAX_QUEUE_SIZE`.
+const OTEL_BSP_MAX_EXPORT_BATCH_SIZE: &str = "OTEL_BSP_MAX_EXPORT_BATCH_SIZE";
+
+/// Maximum allowed time to export data, in milliseconds.
+const OTEL_BSP_EXPORT_TIMEOUT: &str = "OTEL_BSP_EXPORT_TIMEOUT";
+
 /// A [`SpanProcessor`] that exports synchronously when spans are finished.
 ///
 /// [`SpanProcessor`]: ../../../api/trace/span_processor/trait.SpanProcessor.html
@@ -137,6 +152,7 @@ impl api::SpanProcessor for SimpleSpanProcessor {
 #[derive(Debug)]
 pub struct BatchSpanProcessor {
     message_sender: Mutex<mpsc::Sender<BatchMessage>>,
+    dropped_spans: Arc<AtomicUsize>,
 }
 
 impl api::SpanProcessor for BatchSpanProcessor {
@@ -145,15 +161,11 @@ impl api::SpanProcessor for BatchSpanProcessor {
     }
 
     fn on_end(&self, span: Arc<exporter::trace::SpanData>) {
-        if let Ok(mut sender) = self.message_sender.lock() {
-            let _ = sender.try_send(BatchMessage::ExportSpan(span));
-        }
+        self.send(BatchMessage::ExportSpan(span));
     }
 
     fn shutdown(&self) {
-        if let Ok(mut sender) = self.message_sender.lock() {
-            let _ = sender.try_send(BatchMessage::Shutdown);
-        }
+        self.send(BatchMessage::Shutdown);
     }
 }
 
@@ -161,28 +173,97 @@ impl api::SpanProcessor for BatchSpanProcessor {
 ///
 /// This process is implemented as a [`Future`] that returns when the accompanying
 /// [`BatchSpanProcessor`] is shut down, and allows systems like [`tokio`] and [`async-std`] to
-/// process the work in the background without requiring dedicated system threads.
+/// process the work in the background without requiring dedicated system threads. Exporters
+/// are synchronous, so each batch is exported on a short-lived thread that the worker waits
+/// for until the export timeout.
 #[allow(missing_debug_implementations)]
 pub struct BatchSpanProcessorWorker {
-    exporter: Box<dyn exporter::trace::SpanExporter>,
+    exporter: Arc<dyn exporter::trace::SpanExporter>,
     messages: Pin<Box<dyn Stream<Item = BatchMessage> + Send>>,
     config: BatchConfig,
     buffer: Vec<Arc<exporter::trace::SpanData>>,
+    dropped_spans: Arc<AtomicUsize>,
+    pending_export: Option<std_mpsc::Receiver<()>>,
 }
 
 impl BatchSpanProcessorWorker {
+    /// Report the spans dropped since the last report because the queue was
+    /// full, once per export interval instead of once per span.
+    fn report_dropped_spans(&self) {
+        let dropped = self.dropped_spans.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            global::handle_error(api::TraceError::Other(format!(
+                "{} spans were dropped because the export queue was full",
+                dropped
+            )));
+        }
+    }
+
     fn export_spans(&mut self) {
         if !self.buffer.is_empty() {
             let mut spans = std::mem::replace(&mut self.buffer, Vec::new());
+            let deadline = time::Instant::now() + self.config.max_export_timeout;
             while !spans.is_empty() {
                 let batch_idx = spans
                     .len()
                     .saturating_sub(self.config.max_export_batch_size);
                 let batch = spans.split_off(batch_idx);
-                self.exporter.export(batch);
+                if !self.export_batch(batch, deadline) {
+                    // The remaining batches are dropped.
+                    global::handle_error(api::TraceError::ExportTimedOut(
+                        self.config.max_export_timeout,
+                    ));
+                    break;
+                }
             }
         }
     }
+
+    /// Export a batch on its own thread, returning `false` if the export did
+    /// not complete before `deadline`.
+    ///
+    /// An export that times out keeps running on its thread. Exporters are
+    /// never called concurrently, so the next export waits for it first.
+    fn export_batch(
+        &mut self,
+        batch: Vec<Arc<exporter::trace::SpanData>>,
+        deadline: time::Instant,
+    ) -> bool {
+        if let Some(pending) = self.pending_export.take() {
+            if !wait_for_export(&pending, deadline) {
+                self.pending_export = Some(pending);
+                return false;
+            }
+        }
+
+        let exporter = self.exporter.clone();
+        let (done, pending) = std_mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("opentelemetry-span-export".to_string())
+            .spawn(move || {
+                exporter.export(batch);
+                let _ = done.send(());
+            });
+        if let Err(err) = spawned {
+            global::handle_error(api::TraceError::Other(err.to_string()));
+            return false;
+        }
+        if !wait_for_export(&pending, deadline) {
+            self.pending_export = Some(pending);
+            return false;
+        }
+        true
+    }
+}
+
+/// Wait until an export thread is done or `deadline` has passed.
+fn wait_for_export(pending: &std_mpsc::Receiver<()>, deadline: time::Instant) -> bool {
+    let timeout = deadline.saturating_duration_since(time::Instant::now());
+    // A disconnected channel means the export panicked, which also ends it.
+    !matches!(
+        pending.recv_timeout(timeout),
+        Err(std_mpsc::RecvTimeoutError::Timeout)
+    )
 }
 
 impl Drop for BatchSpanProcessorWorker {
@@ -201,12 +282,18 @@ impl Future for BatchSpanProcessorWorker {
                 Some(BatchMessage::ExportSpan(span)) => {
                     if self.buffer.len() < self.config.max_queue_size {
                         self.buffer.push(span);
+                    } else {
+                        self.dropped_spans.fetch_add(1, Ordering::Relaxed);
                     }
                 }
                 // Span batch interval time reached, export current spans.
-                Some(BatchMessage::Tick) => self.export_spans(),
+                Some(BatchMessage::Tick) => {
+                    self.report_dropped_spans();
+                    self.export_spans();
+                }
                 // Stream has terminated or processor is shutdown, return to finish execution.
                 None | Some(BatchMessage::Shutdown) => {
+                    self.report_dropped_spans();
                     self.exporter.shutdown();
                     return Poll::Ready(());
                 }
@@ -223,8 +310,36 @@ enum BatchMessage {
 }
 
 impl BatchSpanProcessor {
+    /// Send a message to the worker.
+    ///
+    /// Spans that do not fit in a full queue are counted and reported by the
+    /// worker at the next export interval. Messages sent after the worker has
+    /// shut down are ignored.
+    fn send(&self, message: BatchMessage) {
+        let mut sender = match self.message_sender.lock() {
+            Ok(sender) => sender,
+            Err(err) => {
+                global::handle_error(api::TraceError::Other(err.to_string()));
+                return;
+            }
+        };
+
+        if let Err(err) = sender.try_send(message) {
+            if err.is_full() {
+                match err.into_inner() {
+                    BatchMessage::ExportSpan(_) => {
+                        self.dropped_spans.fetch_add(1, Ordering::Relaxed);
+                    }
+                    _ => global::handle_error(api::TraceError::Other(
+                        "export queue is full".to_string(),
+                    )),
+                }
+            }
+        }
+    }
+
     pub(crate) fn new<S, SO, I, IS, ISI>(
-        exporter: Box<dyn exporter::trace::SpanExporter>,
+        exporter: Arc<dyn exporter::trace::SpanExporter>,
         spawn: S,
         interval: I,
         config: BatchConfig,
@@ -236,6 +351,7 @@ impl BatchSpanProcessor {
     {
         let (message_sender, message_receiver) = mpsc::channel(config.max_queue_size);
         let ticker = interval(config.scheduled_delay).map(|_| BatchMessage::Tick);
+        let dropped_spans = Arc::new(AtomicUsize::new(0));
 
         // Spawn worker process via user-defined spawn function.
         spawn(BatchSpanProcessorWorker {
@@ -243,11 +359,14 @@ impl BatchSpanProcessor {
             messages: Box::pin(futures::stream::select(message_receiver, ticker)),
             config,
             buffer: Vec::new(),
+            dropped_spans: dropped_spans.clone(),
+            pending_export: None,
         });
 
         // Return batch processor with link to worker
         BatchSpanProcessor {
             message_sender: Mutex::new(message_sender),
+            dropped_spans,
         }
     }
 
@@ -287,6 +406,51 @@ pub struct BatchConfig {
     /// of spans one batch after the other without any delay. The default value
     /// is 512.
     max_export_batch_size: usize,
+
+    /// The maximum duration to export the spans buffered during an interval.
+    /// The worker stops waiting for an export once it has elapsed, and drops
+    /// the batches that have not been exported. An export that is still
+    /// running is not cancelled, the next export waits for it to return.
+    /// The default value is 30 seconds.
+    max_export_timeout: time::Duration,
+}
+
+impl BatchConfig {
+    /// Create a batch configuration from the standard environment variables.
+    ///
+    /// Reads `OTEL_BSP_SCHEDULE_DELAY`, `OTEL_BSP_MAX_QUEUE_SIZE`,
+    /// `OTEL_BSP_MAX_EXPORT_BATCH_SIZE` and `OTEL_BSP_EXPORT_TIMEOUT`, with
+    /// durations in milliseconds. Unset variables keep their default value,
+    /// malformed ones return an error.
+    pub fn from_env() -> api::TraceResult<Self> {
+        let mut config = BatchConfig::default();
+
+        if let Some(delay) = parse_env_var(OTEL_BSP_SCHEDULE_DELAY)? {
+            config.scheduled_delay = time::Duration::from_millis(delay);
+        }
+        if let Some(max_queue_size) = parse_env_var(OTEL_BSP_MAX_QUEUE_SIZE)? {
+            config.max_queue_size = max_queue_size;
+        }
+        if let Some(max_export_batch_size) = parse_env_var(OTEL_BSP_MAX_EXPORT_BATCH_SIZE)? {
+            config.max_export_batch_size = max_export_batch_size;
+        }
+        if let Some(timeout) = parse_env_var(OTEL_BSP_EXPORT_TIMEOUT)? {
+            config.max_export_timeout = time::Duration::from_millis(timeout);
+        }
+
+        if config.max_export_batch_size > config.max_queue_size {
+            return Err(api::TraceError::InvalidEnvVar {
+                name: OTEL_BSP_MAX_EXPORT_BATCH_SIZE,
+                value: config.max_export_batch_size.to_string(),
+                reason: format!(
+                    "batch size must not exceed the max queue size of {}",
+                    config.max_queue_size
+                ),
+            });
+        }
+
+        Ok(config)
+    }
 }
 
 impl Default for BatchConfig {
@@ -295,6 +459,7 @@ impl Default for BatchConfig {
             max_queue_size: 2048,
             scheduled_delay: time::Duration::from_secs(5),
             max_export_batch_size: 512,
+            max_export_timeout: time::Duration::from_secs(30),
         }
     }
 }
@@ -341,13 +506,160 @@ where
         BatchSpanProcessorBuilder { config, ..self }
     }
 
+    /// Set max timeout for exporting batches
+    pub fn with_max_export_timeout(self, timeout: time::Duration) -> Self {
+        let mut config = self.config;
+        config.max_export_timeout = timeout;
+
+        BatchSpanProcessorBuilder { config, ..self }
+    }
+
+    /// Use the given batch configuration, e.g. one created by [`BatchConfig::from_env`].
+    ///
+    /// [`BatchConfig::from_env`]: struct.BatchConfig.html#method.from_env
+    pub fn with_batch_config(self, config: BatchConfig) -> Self {
+        BatchSpanProcessorBuilder { config, ..self }
+    }
+
     /// Build a batch processor
     pub fn build(self) -> BatchSpanProcessor {
         BatchSpanProcessor::new(
-            Box::new(self.exporter),
+            Arc::new(self.exporter),
             self.spawn,
             self.interval,
             self.config,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::SpanProcessor;
+    use crate::sdk;
+    use std::env;
+
+    fn span_data() -> Arc<exporter::trace::SpanData> {
+        Arc::new(exporter::trace::SpanData {
+            span_context: api::SpanContext::empty_context(),
+            parent_span_id: api::SpanId::invalid(),
+            span_kind: api::SpanKind::Internal,
+            name: "span".into(),
+            start_time: time::SystemTime::now(),
+            end_time: time::SystemTime::now(),
+            attributes: sdk::EvictedHashMap::new(1),
+            message_events: sdk::EvictedQueue::new(1),
+            links: sdk::EvictedQueue::new(1),
+            status_code: api::StatusCode::OK,
+            status_message: String::new(),
+            resource: Arc::new(sdk::Resource::default()),
+        })
+    }
+
+    #[test]
+    fn test_full_queue_counts_dropped_spans() {
+        let workers = Mutex::new(Vec::new());
+        let processor = BatchSpanProcessor::builder(
+            api::NoopSpanExporter {},
+            |worker| workers.lock().unwrap().push(worker),
+            |_| futures::stream::pending::<()>(),
+        )
+        .with_max_queue_size(1)
+        .with_max_export_batch_size(1)
+        .build();
+
+        // the channel holds one message per sender on top of the queue size
+        for _ in 0..5 {
+            processor.on_end(span_data());
+        }
+        assert_eq!(processor.dropped_spans.load(Ordering::Relaxed), 3);
+
+        // once the worker is gone, spans are ignored instead of counted
+        workers.lock().unwrap().clear();
+        processor.on_end(span_data());
+        assert_eq!(processor.dropped_spans.load(Ordering::Relaxed), 3);
+    }
+
+    #[derive(Debug)]
+    struct BlockingExporter {
+        exports: AtomicUsize,
+        release: Mutex<std_mpsc::Receiver<()>>,
+    }
+
+    impl exporter::trace::SpanExporter for BlockingExporter {
+        fn export(
+            &self,
+            _batch: Vec<Arc<exporter::trace::SpanData>>,
+        ) -> exporter::trace::ExportResult {
+            self.exports.fetch_add(1, Ordering::SeqCst);
+            let _ = self.release.lock().unwrap().recv();
+            exporter::trace::ExportResult::Success
+        }
+    }
+
+    #[test]
+    fn test_hung_export_is_bounded_by_timeout() {
+        let (release, wait) = std_mpsc::channel();
+        let exporter = Arc::new(BlockingExporter {
+            exports: AtomicUsize::new(0),
+            release: Mutex::new(wait),
+        });
+        let mut worker = BatchSpanProcessorWorker {
+            exporter: exporter.clone(),
+            messages: Box::pin(futures::stream::pending()),
+            config: BatchConfig {
+                max_export_batch_size: 1,
+                max_export_timeout: time::Duration::from_millis(50),
+                ..Default::default()
+            },
+            buffer: vec![span_data(), span_data(), span_data()],
+            dropped_spans: Arc::new(AtomicUsize::new(0)),
+            pending_export: None,
+        };
+
+        let start = time::Instant::now();
+        worker.export_spans();
+        assert!(start.elapsed() < time::Duration::from_secs(5));
+        assert!(worker.buffer.is_empty());
+
+        // the hung export is still running, so the next interval is dropped
+        worker.buffer.push(span_data());
+        worker.export_spans();
+        assert_eq!(exporter.exports.load(Ordering::SeqCst), 1);
+
+        // exports resume once it returns
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        worker.buffer.push(span_data());
+        worker.config.max_export_timeout = time::Duration::from_secs(5);
+        worker.export_spans();
+        assert_eq!(exporter.exports.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_batch_config_from_env() {
+        env::set_var(OTEL_BSP_SCHEDULE_DELAY, "1000");
+        env::set_var(OTEL_BSP_MAX_QUEUE_SIZE, "4096");
+        env::set_var(OTEL_BSP_EXPORT_TIMEOUT, "2500");
+        let config = BatchConfig::from_env().unwrap();
+        assert_eq!(config.scheduled_delay, time::Duration::from_secs(1));
+        assert_eq!(config.max_queue_size, 4096);
+        assert_eq!(config.max_export_batch_size, 512);
+        assert_eq!(config.max_export_timeout, time::Duration::from_millis(2500));
+
+        env::set_var(OTEL_BSP_MAX_EXPORT_BATCH_SIZE, "8192");
+        assert!(BatchConfig::from_env().is_err());
+
+        env::set_var(OTEL_BSP_MAX_EXPORT_BATCH_SIZE, "-1");
+        assert!(BatchConfig::from_env().is_err());
+
+        for var in &[
+            OTEL_BSP_SCHEDULE_DELAY,
+            OTEL_BSP_MAX_QUEUE_SIZE,
+            OTEL_BSP_MAX_EXPORT_BATCH_SIZE,
+            OTEL_BSP_EXPORT_TIMEOUT,
+        ] {
+            env::remove_var(var);
+        }
+    }
+}
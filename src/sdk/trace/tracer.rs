@@ -12,7 +12,7 @@ use crate::sdk;
 use crate::{api, api::context::Context, exporter};
 use std::fmt;
 use std::sync::Arc;
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 
 /// `Tracer` implementation to create and manage spans
 #[derive(Clone)]
@@ -93,7 +93,7 @@ impl api::Tracer for Tracer {
     /// Returns a span with an inactive `SpanContext`. Used by functions that
     /// need to return a default span like `get_active_span` if no span is present.
     fn invalid(&self) -> Self::Span {
-        sdk::Span::new(api::SpanId::invalid(), None, self.clone())
+        sdk::Span::new(api::SpanId::invalid(), None, None, self.clone())
     }
 
     /// Starts a new `Span` in a given context.
@@ -103,7 +103,10 @@ impl api::Tracer for Tracer {
     /// trace. A span is said to be a _root span_ if it does not have a parent. Each
     /// trace includes a single root span, which is the shared ancestor of all other
     /// spans in the trace.
-    fn start_from_context(&self, name: &str, cx: &Context) -> Self::Span {
+    fn start_from_context<T>(&self, name: T, cx: &Context) -> Self::Span
+    where
+        T: Into<api::StringValue>,
+    {
         let builder = self.span_builder(name);
 
         self.build_with_context(builder, cx)
@@ -112,8 +115,11 @@ impl api::Tracer for Tracer {
     /// Creates a span builder
     ///
     /// An ergonomic way for attributes to be configured before the `Span` is started.
-    fn span_builder(&self, name: &str) -> api::SpanBuilder {
-        api::SpanBuilder::from_name(name.to_string())
+    fn span_builder<T>(&self, name: T) -> api::SpanBuilder
+    where
+        T: Into<api::StringValue>,
+    {
+        api::SpanBuilder::from_name(name)
     }
 
     /// Starts a span from a `SpanBuilder`.
@@ -186,6 +192,12 @@ impl api::Tracer for Tracer {
                 .map(|_| (parent_trace_flags, Vec::new()))
         };
 
+        // Anchor spans without an explicit start time to the monotonic clock.
+        let (start_time, start_instant) = match builder.start_time.take() {
+            Some(start_time) => (start_time, None),
+            None => (SystemTime::now(), Some(Instant::now())),
+        };
+
         // Build optional inner context, `None` if not recording.
         let inner = sampling_decision.map(move |(trace_flags, mut extra_attrs)| {
             attribute_options.append(&mut extra_attrs);
@@ -195,7 +207,6 @@ impl api::Tracer for Tracer {
             }
             let mut links = sdk::EvictedQueue::new(config.max_links_per_span);
             links.append_vec(&mut link_options);
-            let start_time = builder.start_time.unwrap_or_else(SystemTime::now);
             let end_time = builder.end_time.unwrap_or(start_time);
             let mut message_events = sdk::EvictedQueue::new(config.max_events_per_span);
             if let Some(mut events) = builder.message_events {
@@ -229,6 +240,6 @@ impl api::Tracer for Tracer {
             }
         }
 
-        sdk::Span::new(span_id, inner, self.clone())
+        sdk::Span::new(span_id, inner, start_instant, self.clone())
     }
 }